@@ -8,6 +8,8 @@ use nx::ipc::sf::lm::ILogService;
 
 use crate::logger;
 
+pub const MAX_SESSION_COUNT: usize = 42;
+
 pub struct Logger {
     session: sf::Session,
     log_destination: lm::LogDestination,
@@ -26,10 +28,7 @@ impl sf::IObject for Logger {
     }
 
     fn get_command_table(&self) -> sf::CommandMetadataTable {
-        ipc_server_make_command_table! {
-            log: 0,
-            set_destination: 1
-        }
+        <Self as ILogger>::make_command_table()
     }
 }
 
@@ -56,9 +55,7 @@ impl sf::IObject for LogService {
     }
 
     fn get_command_table(&self) -> sf::CommandMetadataTable {
-        ipc_server_make_command_table! {
-            open_logger: 0
-        }
+        <Self as ILogService>::make_command_table()
     }
 }
 
@@ -67,7 +64,9 @@ use crate::service::pm;
 use crate::service::pm::IInformationInterface;
 
 impl ILogService for LogService {
-    fn open_logger(&mut self, process_id: sf::ProcessId) -> Result<mem::Shared<dyn sf::IObject>> {
+    type Logger = Logger;
+
+    fn open_logger(&mut self, process_id: sf::ProcessId) -> Result<mem::Shared<Logger>> {
         let pminfo = service::new_service_object::<pm::InformationInterface>()?;
         let program_id = pminfo.get().get_program_id(process_id.process_id)?;
         
@@ -87,6 +86,6 @@ impl server::IService for LogService {
     }
 
     fn get_max_sesssions() -> i32 {
-        42
+        MAX_SESSION_COUNT as i32
     }
 }
\ No newline at end of file
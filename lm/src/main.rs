@@ -13,7 +13,7 @@ extern crate paste;
 
 use nx::result::*;
 use nx::util;
-use nx::wait;
+use nx::os;
 use nx::thread;
 use nx::diag::assert;
 use nx::ipc::sf;
@@ -41,11 +41,12 @@ pub fn pm_module_main() -> Result<()> {
     thread::get_current_thread().set_name("rust.lm.PmModule")?;
 
     let psc = service::new_service_object::<psc::PmService>()?;
-    let module = psc.get().get_pm_module()?.to::<psc::PmModule>();
+    let module = psc.get().get_pm_module()?;
 
     let event_handle = module.get().initialize(psc::ModuleId::Lm, sf::Buffer::new())?;
+    let event = os::SystemEvent::from_copy_handle(event_handle, true);
     loop {
-        wait::wait_handles(&[event_handle.handle], -1)?;
+        event.wait()?;
 
         let (state, _flags) = module.get().get_request()?;
         match state {
@@ -59,22 +60,18 @@ pub fn pm_module_main() -> Result<()> {
     Ok(())
 }
 
-pub fn pm_module_thread_fn(_: *mut u8) {
-    match pm_module_main() {
-        Err(rc) => assert::assert(assert::AssertMode::FatalThrow, rc),
-        _ => {}
-    }
-}
-
 pub fn server_main() -> Result<()> {
     thread::get_current_thread().set_name("rust.lm.IpcServer")?;
 
     logger::initialize()?;
 
-    let mut pm_module_thread = thread::Thread::new(pm_module_thread_fn, core::ptr::null_mut(), core::ptr::null_mut(), 0x2000, "rust.lm.PmModuleThread")?;
-    pm_module_thread.create_and_start(38, -2)?;
+    let pm_module_thread = thread::Builder::new().name("rust.lm.PmModuleThread").stack_size(0x2000).priority(38).cpu_id(-2).spawn(|| {
+        if let Err(rc) = pm_module_main() {
+            assert::assert(assert::AssertMode::FatalThrow, rc);
+        }
+    })?;
 
-    let mut manager = server::ServerManager::new();
+    let manager = server::ServerManager::<{ ipc::MAX_SESSION_COUNT }, 1>::new()?;
     manager.register_service_server::<ipc::LogService>()?;
     manager.loop_process()?;
 
@@ -0,0 +1,17 @@
+use std::time::Instant;
+
+// Ticks run at the console's 19.2MHz, counted since the first call
+
+pub fn get_system_tick() -> u64 {
+    static G_START: std::sync::OnceLock<Instant> = std::sync::OnceLock::new();
+    let elapsed = G_START.get_or_init(Instant::now).elapsed();
+    nanoseconds_to_ticks(elapsed.as_nanos() as u64)
+}
+
+pub const fn ticks_to_nanoseconds(ticks: u64) -> u64 {
+    (ticks * 625) / 12
+}
+
+pub const fn nanoseconds_to_ticks(nanoseconds: u64) -> u64 {
+    (nanoseconds * 12) / 625
+}
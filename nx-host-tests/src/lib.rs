@@ -0,0 +1,85 @@
+#![no_std]
+#![allow(incomplete_features)]
+#![allow(non_snake_case)]
+#![allow(clippy::redundant_field_names)]
+#![feature(adt_const_params)]
+#![feature(const_param_ty_trait)]
+#![feature(const_trait_impl)]
+#![feature(const_ops)]
+#![feature(specialization)]
+#![feature(coerce_unsized)]
+#![feature(unsize)]
+#![macro_use]
+
+// Builds nx's portable modules (and their tests) for the host, replacing the modules which need the kernel or aarch64 with host versions of just what the former use
+// nx itself isn't linted with clippy, nor written with current compilers' lints in mind, so the modules allow the lints their original code triggers (code added since is expected to be clean of the rest)
+
+#[macro_use]
+extern crate alloc;
+
+extern crate std;
+
+// Code generated by nx-macros refers to this crate as "nx"
+extern crate self as nx;
+
+#[macro_use]
+#[path = "../../nx/src/macros/mod.rs"]
+pub mod macros;
+
+#[path = "../../nx/src/result.rs"]
+pub mod result;
+
+#[path = "../../nx/src/results/mod.rs"]
+pub mod results;
+
+#[path = "../../nx/src/version.rs"]
+pub mod version;
+
+#[allow(clippy::len_zero, clippy::manual_div_ceil, clippy::manual_slice_size_calculation, clippy::missing_transmute_annotations, clippy::mut_from_ref, clippy::new_without_default, clippy::ptr_offset_with_cast, clippy::question_mark, clippy::too_many_arguments)]
+#[path = "../../nx/src/ipc/mod.rs"]
+pub mod ipc;
+
+#[path = "../../nx/src/service/mod.rs"]
+pub mod service;
+
+#[allow(static_mut_refs)]
+#[path = "../../nx/src/fs/mod.rs"]
+pub mod fs;
+
+// nx's compiler predates div_ceil()
+#[allow(clippy::manual_div_ceil, clippy::needless_range_loop, clippy::new_without_default, clippy::not_unsafe_ptr_arg_deref, clippy::ptr_offset_with_cast, clippy::redundant_pattern_matching, clippy::too_many_arguments)]
+#[path = "../../nx/src/gpu/mod.rs"]
+pub mod gpu;
+
+#[path = "../../nx/src/gfx/mod.rs"]
+pub mod gfx;
+
+#[path = "../../nx/src/input.rs"]
+pub mod input;
+
+// Host versions
+
+pub mod svc;
+
+pub mod sync;
+
+pub mod thread;
+
+pub mod mem;
+
+pub mod wait;
+
+pub mod os;
+
+pub mod util;
+
+pub mod arm;
+
+pub mod vmem;
+
+// Const generic parameter types must be declared as such on current compilers
+impl core::marker::ConstParamTy_ for ipc::BufferAttribute {}
+
+impl core::marker::ConstParamTy_ for ipc::HandleMode {}
+
+pub use paste;
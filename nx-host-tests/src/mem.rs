@@ -0,0 +1,9 @@
+#[allow(clippy::all)]
+#[path = "../../nx/src/mem/shared.rs"]
+mod shared;
+pub use shared::*;
+
+pub const PAGE_ALIGNMENT: usize = 0x1000;
+
+// Buffers are never seen by any device on the host
+pub fn flush_data_cache(_address: *mut u8, _size: usize) {}
@@ -0,0 +1,94 @@
+use crate::result::*;
+use crate::svc;
+use crate::wait;
+use crate::ipc::sf;
+use core::sync::atomic::AtomicBool;
+use core::sync::atomic::AtomicU32;
+use core::sync::atomic::Ordering;
+
+// Events without a kernel behind them: they can be signaled and checked, but not waited for
+
+static G_NEXT_EVENT_HANDLE: AtomicU32 = AtomicU32::new(0x1000);
+
+pub struct SystemEvent {
+    handle: svc::Handle,
+    auto_clear: bool,
+    signaled: AtomicBool
+}
+
+impl SystemEvent {
+    pub fn new(auto_clear: bool) -> Result<Self> {
+        Ok(Self::from_readable_handle(G_NEXT_EVENT_HANDLE.fetch_add(1, Ordering::SeqCst), auto_clear))
+    }
+
+    pub fn from_readable_handle(readable_handle: svc::Handle, auto_clear: bool) -> Self {
+        Self { handle: readable_handle, auto_clear: auto_clear, signaled: AtomicBool::new(false) }
+    }
+
+    pub fn from_copy_handle(handle: sf::CopyHandle, auto_clear: bool) -> Self {
+        Self::from_readable_handle(handle.handle, auto_clear)
+    }
+
+    pub fn is_auto_clear(&self) -> bool {
+        self.auto_clear
+    }
+
+    pub fn signal(&self) -> Result<()> {
+        self.signaled.store(true, Ordering::SeqCst);
+        Ok(())
+    }
+
+    pub fn clear(&self) -> Result<()> {
+        self.signaled.store(false, Ordering::SeqCst);
+        Ok(())
+    }
+
+    pub fn get_waiter(&self) -> Result<wait::Waiter> {
+        Ok(wait::Waiter::from_handle(self.handle))
+    }
+
+    pub fn timed_wait(&self, _timeout: i64) -> Result<()> {
+        panic!("os::SystemEvent::timed_wait was called, but there's no kernel to wait on the host")
+    }
+
+    pub fn wait(&self) -> Result<()> {
+        self.timed_wait(-1)
+    }
+
+    pub fn try_wait(&self) -> Result<bool> {
+        match self.auto_clear {
+            true => Ok(self.signaled.swap(false, Ordering::SeqCst)),
+            false => Ok(self.signaled.load(Ordering::SeqCst))
+        }
+    }
+}
+
+pub struct Event {
+    event: SystemEvent
+}
+
+impl Event {
+    pub fn new(auto_clear: bool) -> Result<Self> {
+        Ok(Self { event: SystemEvent::new(auto_clear)? })
+    }
+
+    pub fn is_auto_clear(&self) -> bool {
+        self.event.is_auto_clear()
+    }
+
+    pub fn signal(&self) -> Result<()> {
+        self.event.signal()
+    }
+
+    pub fn clear(&self) -> Result<()> {
+        self.event.clear()
+    }
+
+    pub fn get_waiter(&self) -> Result<wait::Waiter> {
+        self.event.get_waiter()
+    }
+
+    pub fn try_wait(&self) -> Result<bool> {
+        self.event.try_wait()
+    }
+}
@@ -0,0 +1,80 @@
+use crate::result::*;
+
+// Only what the portable modules refer to: there's no kernel on the host, so anything reaching these is a bug in the tested code (or test)
+
+pub type Address = *const u8;
+pub type Size = usize;
+pub type Handle = u32;
+
+pub const CURRENT_PROCESS_PSEUDO_HANDLE: Handle = 0xFFFF8001;
+
+bit_enum! {
+    MemoryPermission (u32) {
+        None = 0,
+        Read = bit!(0),
+        Write = bit!(1),
+        Execute = bit!(2),
+        ReadWrite = bit!(0) | bit!(1),
+        ReadExecute = bit!(0) | bit!(2),
+        DontCare = bit!(28)
+    }
+}
+
+bit_enum! {
+    MemoryAttribute (u32) {
+        None = 0,
+        Borrowed = bit!(0),
+        IpcMapped = bit!(1),
+        DeviceMapped = bit!(2),
+        Uncached = bit!(3)
+    }
+}
+
+fn no_kernel(name: &str) -> ! {
+    panic!("svc::{} was called, but there's no kernel on the host", name)
+}
+
+pub fn set_memory_attribute(_address: Address, _size: Size, _mask: u32, _value: MemoryAttribute) -> Result<()> {
+    no_kernel("set_memory_attribute")
+}
+
+pub fn map_shared_memory(_handle: Handle, _address: Address, _size: Size, _permission: MemoryPermission) -> Result<()> {
+    no_kernel("map_shared_memory")
+}
+
+pub fn unmap_shared_memory(_handle: Handle, _address: Address, _size: Size) -> Result<()> {
+    no_kernel("unmap_shared_memory")
+}
+
+pub fn create_transfer_memory(_address: Address, _size: Size, _permissions: MemoryPermission) -> Result<Handle> {
+    no_kernel("create_transfer_memory")
+}
+
+// Closing handles is harmless (objects owning fake handles get dropped in tests all the time)
+pub fn close_handle(_handle: Handle) -> Result<()> {
+    Ok(())
+}
+
+pub fn connect_to_named_port(_name: Address) -> Result<Handle> {
+    no_kernel("connect_to_named_port")
+}
+
+pub fn send_sync_request(_handle: Handle) -> Result<()> {
+    no_kernel("send_sync_request")
+}
+
+pub fn create_session(_is_light: bool, _unk_name: u64) -> Result<(Handle, Handle)> {
+    no_kernel("create_session")
+}
+
+pub fn accept_session(_handle: Handle) -> Result<Handle> {
+    no_kernel("accept_session")
+}
+
+pub fn reply_and_receive(_handles: *const Handle, _handle_count: u32, _reply_target: Handle, _timeout: i64) -> Result<i32> {
+    no_kernel("reply_and_receive")
+}
+
+pub fn manage_named_port(_name: Address, _max_sessions: i32) -> Result<Handle> {
+    no_kernel("manage_named_port")
+}
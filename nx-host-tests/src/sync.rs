@@ -0,0 +1,89 @@
+use std::sync as std_sync;
+use std::thread;
+
+// Same API as nx's sync types, on top of std's
+
+pub struct RawMutex {
+    recursive: bool,
+    owner: std_sync::Mutex<Option<(thread::ThreadId, u32)>>,
+    unlocked: std_sync::Condvar
+}
+
+impl RawMutex {
+    pub const fn new(recursive: bool) -> Self {
+        Self { recursive: recursive, owner: std_sync::Mutex::new(None), unlocked: std_sync::Condvar::new() }
+    }
+
+    pub fn lock(&self) {
+        let id = thread::current().id();
+        let mut owner = self.owner.lock().unwrap();
+        loop {
+            match *owner {
+                None => {
+                    *owner = Some((id, 1));
+                    return;
+                },
+                Some((owner_id, ref mut count)) if self.recursive && (owner_id == id) => {
+                    *count += 1;
+                    return;
+                },
+                _ => owner = self.unlocked.wait(owner).unwrap()
+            }
+        }
+    }
+
+    pub fn unlock(&self) {
+        let mut owner = self.owner.lock().unwrap();
+        if let Some((_, ref mut count)) = *owner {
+            *count -= 1;
+            if *count == 0 {
+                *owner = None;
+                self.unlocked.notify_one();
+            }
+        }
+    }
+}
+
+pub struct ScopedLock<'a> {
+    lock: &'a RawMutex
+}
+
+impl<'a> ScopedLock<'a> {
+    pub fn new(lock: &'a RawMutex) -> Self {
+        lock.lock();
+        Self { lock: lock }
+    }
+}
+
+impl<'a> Drop for ScopedLock<'a> {
+    fn drop(&mut self) {
+        self.lock.unlock();
+    }
+}
+
+pub struct Mutex<T: ?Sized> {
+    inner: std_sync::Mutex<T>
+}
+
+pub type MutexGuard<'a, T> = std_sync::MutexGuard<'a, T>;
+
+impl<T> Mutex<T> {
+    pub const fn new(t: T) -> Self {
+        Self { inner: std_sync::Mutex::new(t) }
+    }
+}
+
+impl<T: ?Sized> Mutex<T> {
+    pub fn lock(&self) -> MutexGuard<'_, T> {
+        // A test panicking while holding a lock shouldn't make every other test fail
+        self.inner.lock().unwrap_or_else(|err| err.into_inner())
+    }
+
+    pub fn try_lock(&self) -> Option<MutexGuard<'_, T>> {
+        match self.inner.try_lock() {
+            Ok(guard) => Some(guard),
+            Err(std_sync::TryLockError::Poisoned(err)) => Some(err.into_inner()),
+            Err(std_sync::TryLockError::WouldBlock) => None
+        }
+    }
+}
\ No newline at end of file
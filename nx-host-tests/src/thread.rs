@@ -0,0 +1,91 @@
+use crate::result::*;
+use std::any::Any;
+use std::boxed::Box;
+use std::cell::RefCell;
+use std::string::String;
+use std::vec::Vec;
+
+// thread_define_local! keys, with values stored in std's thread locals
+
+std::thread_local! {
+    static G_LOCAL_VALUES: RefCell<Vec<(usize, Box<dyn Any>)>> = RefCell::new(Vec::new());
+}
+
+pub struct LocalKey<T: 'static> {
+    init_fn: fn() -> T
+}
+
+impl<T: 'static> LocalKey<T> {
+    pub const fn new(init_fn: fn() -> T) -> Self {
+        Self { init_fn: init_fn }
+    }
+
+    pub fn try_with<F: FnOnce(&T) -> R, R>(&'static self, f: F) -> Result<R> {
+        let key = self as *const Self as usize;
+        let find_value = || G_LOCAL_VALUES.with(|values| values.borrow().iter().find(|(value_key, _)| *value_key == key).map(|(_, value)| value.downcast_ref::<T>().unwrap() as *const T));
+        let value = match find_value() {
+            Some(value) => value,
+            None => {
+                // Initializers may access other keys, so the list can't be borrowed meanwhile
+                let value: Box<dyn Any> = Box::new((self.init_fn)());
+                G_LOCAL_VALUES.with(|values| values.borrow_mut().push((key, value)));
+                find_value().unwrap()
+            }
+        };
+        // Values are boxed and only dropped when the thread exits, so they outlive this call
+        Ok(f(unsafe { &*value }))
+    }
+
+    pub fn with<F: FnOnce(&T) -> R, R>(&'static self, f: F) -> R {
+        self.try_with(f).unwrap()
+    }
+}
+
+
+// There's no kernel TLS (nor its IPC buffer) on the host: IPC code reaching this instead of going through a loopback (or explicit) buffer is a bug
+pub fn get_thread_local_storage() -> *mut Tls {
+    panic!("thread::get_thread_local_storage was called, but there's no kernel TLS on the host")
+}
+
+#[repr(C)]
+pub struct Tls {
+    pub ipc_buffer: [u8; 0x100]
+}
+
+pub struct JoinHandle<T> {
+    inner: std::thread::JoinHandle<T>
+}
+
+impl<T> JoinHandle<T> {
+    pub fn join(self) -> Result<T> {
+        Ok(self.inner.join().unwrap())
+    }
+}
+
+pub struct Builder {
+    inner: std::thread::Builder
+}
+
+// Same API as nx's Builder, which has no Default
+#[allow(clippy::new_without_default)]
+impl Builder {
+    pub fn new() -> Self {
+        Self { inner: std::thread::Builder::new() }
+    }
+
+    pub fn name(self, name: &str) -> Self {
+        Self { inner: self.inner.name(String::from(name)) }
+    }
+
+    pub fn stack_size(self, stack_size: usize) -> Self {
+        Self { inner: self.inner.stack_size(stack_size) }
+    }
+
+    pub fn spawn<F: FnOnce() -> T + Send + 'static, T: Send + 'static>(self, f: F) -> Result<JoinHandle<T>> {
+        Ok(JoinHandle { inner: self.inner.spawn(f).unwrap() })
+    }
+}
+
+pub fn spawn<F: FnOnce() -> T + Send + 'static, T: Send + 'static>(f: F) -> Result<JoinHandle<T>> {
+    Builder::new().spawn(f)
+}
\ No newline at end of file
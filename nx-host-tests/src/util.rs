@@ -0,0 +1,29 @@
+#![allow(clippy::not_unsafe_ptr_arg_deref)]
+
+use crate::result::*;
+use crate::results;
+use core::ptr;
+
+// Copies of nx's util functions (the rest of that module needs the kernel), with the same signatures
+
+pub fn get_str_from_pointer(ptr: *mut u8, ptr_size: usize) -> Result<&'static str> {
+    result_return_if!(ptr.is_null(), results::lib::util::ResultInvalidPointer);
+    result_return_if!(ptr_size == 0, results::lib::util::ResultInvalidSize);
+
+    unsafe {
+        match core::str::from_utf8(core::slice::from_raw_parts_mut(ptr, ptr_size)) {
+            Ok(name) => Ok(name.trim_matches('\0')),
+            Err(_) => Err(results::lib::util::ResultInvalidConversion::make())
+        }
+    }
+}
+
+pub fn copy_str_to_pointer(string: &str, ptr: *mut u8) -> Result<()> {
+    result_return_if!(ptr.is_null(), results::lib::util::ResultInvalidPointer);
+    result_return_if!(string.is_empty(), results::lib::util::ResultInvalidSize);
+
+    unsafe {
+        ptr::copy(string.as_ptr(), ptr, string.len());
+    }
+    Ok(())
+}
@@ -0,0 +1,22 @@
+use crate::result::*;
+use crate::svc;
+
+pub const MAX_OBJECT_COUNT: u32 = 0x40;
+
+pub struct Waiter {
+    handle: svc::Handle
+}
+
+impl Waiter {
+    pub const fn from_handle(handle: svc::Handle) -> Self {
+        Self { handle: handle }
+    }
+
+    pub fn get_handle(&self) -> svc::Handle {
+        self.handle
+    }
+}
+
+pub fn wait(_waiters: &[Waiter], _timeout: i64) -> Result<usize> {
+    panic!("wait::wait was called, but there's no kernel to wait on the host")
+}
@@ -0,0 +1,364 @@
+extern crate proc_macro;
+
+use proc_macro::TokenStream;
+use proc_macro2::TokenStream as TokenStream2;
+use quote::format_ident;
+use quote::quote;
+use syn::parse_macro_input;
+use syn::AttributeArgs;
+use syn::Error;
+use syn::FnArg;
+use syn::GenericArgument;
+use syn::Ident;
+use syn::ItemTrait;
+use syn::Lit;
+use syn::LitInt;
+use syn::Meta;
+use syn::NestedMeta;
+use syn::Pat;
+use syn::PathArguments;
+use syn::ReturnType;
+use syn::TraitItem;
+use syn::TraitItemMethod;
+use syn::Type;
+use syn::TypePath;
+
+// Note: generated code refers to the library as "nx" (nx itself declares "extern crate self as nx" for this to work inside it)
+
+struct Command {
+    method: TraitItemMethod,
+    rq_id: LitInt,
+    out_objects: Vec<(Ident, TypePath)>,
+    in_names: Vec<Ident>,
+    in_types: Vec<Type>,
+    out_types: Vec<Type>
+}
+
+struct InterfaceArgs {
+    client_names: Vec<Ident>,
+    is_control: bool
+}
+
+fn parse_interface_args(args: &AttributeArgs) -> syn::Result<InterfaceArgs> {
+    let mut client_names = Vec::new();
+    let mut is_control = false;
+    for arg in args {
+        match arg {
+            NestedMeta::Meta(Meta::NameValue(name_value)) if name_value.path.is_ident("client") => {
+                match &name_value.lit {
+                    Lit::Str(name) => client_names.push(name.parse::<Ident>()?),
+                    lit => return Err(Error::new_spanned(lit, "expected the client struct name as a string"))
+                };
+            },
+            NestedMeta::Meta(Meta::Path(path)) if path.is_ident("control") => is_control = true,
+            _ => return Err(Error::new_spanned(arg, "expected `client = \"<name>\"` or `control`"))
+        };
+    }
+
+    // Control requests are sent through ipc::ObjectInfo, whose request IDs are ipc::ControlRequestId values
+    if is_control && !client_names.is_empty() {
+        return Err(Error::new_spanned(&args[0], "control interfaces can't have generated clients"));
+    }
+    Ok(InterfaceArgs { client_names, is_control })
+}
+
+// Commands are declared as "Result<T>", "Result<()>" or "Result<(T1, T2, ...)>"
+fn parse_out_types(output: &ReturnType) -> syn::Result<Vec<Type>> {
+    if let ReturnType::Type(_, ty) = output {
+        if let Type::Path(type_path) = ty.as_ref() {
+            if let Some(segment) = type_path.path.segments.last() {
+                if segment.ident == "Result" {
+                    if let PathArguments::AngleBracketed(args) = &segment.arguments {
+                        if let Some(GenericArgument::Type(ok_type)) = args.args.first() {
+                            return Ok(match ok_type {
+                                Type::Tuple(tuple) => tuple.elems.iter().cloned().collect(),
+                                _ => vec![ok_type.clone()]
+                            });
+                        }
+                    }
+                }
+            }
+        }
+    }
+    Err(Error::new_spanned(output, "IPC commands must return Result<...>"))
+}
+
+fn get_ok_type_mut(output: &mut ReturnType) -> Option<&mut Type> {
+    if let ReturnType::Type(_, ty) = output {
+        if let Type::Path(type_path) = ty.as_mut() {
+            if let Some(segment) = type_path.path.segments.last_mut() {
+                if let PathArguments::AngleBracketed(args) = &mut segment.arguments {
+                    if let Some(GenericArgument::Type(ok_type)) = args.args.first_mut() {
+                        return Some(ok_type);
+                    }
+                }
+            }
+        }
+    }
+    None
+}
+
+// Sub-interfaces are declared as "mem::Shared<ClientType>": the client type is replaced by an associated type named after it,
+// which clients set to the declared type and servers set to whatever IServerObject they return
+fn replace_out_object(ty: &mut Type, out_objects: &mut Vec<(Ident, TypePath)>) -> syn::Result<()> {
+    if let Type::Path(type_path) = ty {
+        if let Some(segment) = type_path.path.segments.last_mut() {
+            if segment.ident == "Shared" {
+                if let PathArguments::AngleBracketed(args) = &mut segment.arguments {
+                    if let Some(GenericArgument::Type(Type::Path(object_type))) = args.args.first_mut() {
+                        if object_type.qself.is_none() {
+                            let name = match object_type.path.segments.last() {
+                                Some(object_segment) => object_segment.ident.clone(),
+                                None => return Err(Error::new_spanned(object_type, "expected a client type"))
+                            };
+                            out_objects.push((name.clone(), object_type.clone()));
+                            *object_type = syn::parse_quote!(Self::#name);
+                        }
+                    }
+                }
+            }
+        }
+    }
+    Ok(())
+}
+
+fn replace_out_objects(output: &mut ReturnType) -> syn::Result<Vec<(Ident, TypePath)>> {
+    let mut out_objects = Vec::new();
+    if let Some(ok_type) = get_ok_type_mut(output) {
+        match ok_type {
+            Type::Tuple(tuple) => {
+                for elem in tuple.elems.iter_mut() {
+                    replace_out_object(elem, &mut out_objects)?;
+                }
+            },
+            _ => replace_out_object(ok_type, &mut out_objects)?
+        };
+    }
+    Ok(out_objects)
+}
+
+fn parse_command(mut method: TraitItemMethod) -> syn::Result<Command> {
+    if let Some(default) = &method.default {
+        return Err(Error::new_spanned(default, "IPC commands can't have a default implementation"));
+    }
+
+    let mut rq_id: Option<LitInt> = None;
+    let mut attrs = Vec::new();
+    for attr in method.attrs.drain(..) {
+        if attr.path.is_ident("ipc_rid") {
+            rq_id = Some(attr.parse_args()?);
+        }
+        else {
+            attrs.push(attr);
+        }
+    }
+    method.attrs = attrs;
+
+    let rq_id = match rq_id {
+        Some(rq_id) => rq_id,
+        None => return Err(Error::new_spanned(&method.sig, "IPC commands need a request ID: #[ipc_rid(<id>)]"))
+    };
+
+    let mut in_names = Vec::new();
+    let mut in_types = Vec::new();
+    for (i, input) in method.sig.inputs.iter().enumerate() {
+        match input {
+            FnArg::Receiver(receiver) => {
+                if (i != 0) || receiver.reference.is_none() || receiver.mutability.is_none() {
+                    return Err(Error::new_spanned(receiver, "IPC commands must take &mut self"));
+                }
+            },
+            FnArg::Typed(pat_type) => {
+                match pat_type.pat.as_ref() {
+                    Pat::Ident(pat_ident) => in_names.push(pat_ident.ident.clone()),
+                    pat => return Err(Error::new_spanned(pat, "IPC command parameters must be plain identifiers"))
+                };
+                in_types.push(pat_type.ty.as_ref().clone());
+            }
+        };
+    }
+
+    let out_objects = replace_out_objects(&mut method.sig.output)?;
+    let out_types = parse_out_types(&method.sig.output)?;
+
+    Ok(Command { method, rq_id, out_objects, in_names, in_types, out_types })
+}
+
+fn make_out_names(command: &Command) -> Vec<Ident> {
+    (0..command.out_types.len()).map(|i| format_ident!("out_{}", i)).collect()
+}
+
+fn make_server_impl_fn(command: &Command, is_control: bool) -> TokenStream2 {
+    let name = &command.method.sig.ident;
+    let impl_name = format_ident!("{}_impl", name);
+    let in_names = &command.in_names;
+    let in_types = &command.in_types;
+    let out_names = make_out_names(command);
+    let write_response = match is_control {
+        true => quote!(nx::ipc::server::write_control_command_response_on_ipc_buffer(&mut ctx.ctx, <nx::result::ResultSuccess as nx::result::ResultBase>::make(), nx::ipc::CommandType::Control)?;),
+        false => quote!(nx::ipc::server::write_request_command_response_on_ipc_buffer(&mut ctx.ctx, <nx::result::ResultSuccess as nx::result::ResultBase>::make(), nx::ipc::CommandType::Request)?;)
+    };
+
+    quote! {
+        #[allow(unused_assignments)]
+        #[allow(unused_parens)]
+        #[allow(unused_mut)]
+        fn #impl_name(&mut self, mut ctx: &mut nx::ipc::server::ServerContext) -> nx::result::Result<()> {
+            ctx.raw_data_walker = nx::ipc::DataWalker::new(ctx.ctx.in_params.data_offset);
+            #( let #in_names = <#in_types as nx::ipc::server::CommandParameter<_>>::after_request_read(&mut ctx)?; )*
+
+            let ( #( #out_names ),* ) = self.#name( #( #in_names ),* )?;
+
+            ctx.raw_data_walker = nx::ipc::DataWalker::new(core::ptr::null_mut());
+            #( nx::ipc::server::CommandParameter::<_>::before_response_write(&#out_names, &mut ctx)?; )*
+            ctx.ctx.out_params.data_size = ctx.raw_data_walker.get_offset() as u32;
+
+            #write_response
+
+            ctx.raw_data_walker = nx::ipc::DataWalker::new(ctx.ctx.out_params.data_offset);
+            #( nx::ipc::server::CommandParameter::<_>::after_response_write(&#out_names, &mut ctx)?; )*
+
+            Ok(())
+        }
+    }
+}
+
+fn make_command_table_fn(commands: &[Command]) -> TokenStream2 {
+    let rq_ids = commands.iter().map(|command| &command.rq_id);
+    let impl_names = commands.iter().map(|command| format_ident!("{}_impl", command.method.sig.ident));
+
+    quote! {
+        fn make_command_table() -> nx::ipc::sf::CommandMetadataTable where Self: Sized {
+            alloc::vec![ #( nx::ipc::sf::CommandMetadata::new(#rq_ids, unsafe { core::mem::transmute(Self::#impl_names as fn(&mut Self, &mut nx::ipc::server::ServerContext) -> nx::result::Result<()>) }) ),* ]
+        }
+    }
+}
+
+fn make_out_object_types(commands: &[Command]) -> syn::Result<Vec<(Ident, TypePath)>> {
+    let mut out_object_types: Vec<(Ident, TypePath)> = Vec::new();
+    for command in commands {
+        for (name, object_type) in &command.out_objects {
+            match out_object_types.iter().find(|(other_name, _)| other_name == name) {
+                Some((_, other_type)) => {
+                    if quote!(#other_type).to_string() != quote!(#object_type).to_string() {
+                        return Err(Error::new_spanned(object_type, "different sub-interface client types can't share the same name"));
+                    }
+                },
+                None => out_object_types.push((name.clone(), object_type.clone()))
+            };
+        }
+    }
+    Ok(out_object_types)
+}
+
+fn make_client(trait_item: &ItemTrait, commands: &[Command], out_object_types: &[(Ident, TypePath)], client_name: &Ident) -> TokenStream2 {
+    let vis = &trait_item.vis;
+    let trait_name = &trait_item.ident;
+    let client_methods = commands.iter().map(|command| {
+        let sig = &command.method.sig;
+        let rq_id = &command.rq_id;
+        let in_names = &command.in_names;
+        let out_names = make_out_names(command);
+        let out_types = &command.out_types;
+
+        quote! {
+            #[allow(unused_parens)]
+            #sig {
+                nx::ipc_client_send_request_command!([self.session.object_info; #rq_id] ( #( #in_names ),* ) => ( #( #out_names: #out_types ),* ))
+            }
+        }
+    });
+    let out_object_names = out_object_types.iter().map(|(name, _)| name);
+    let out_object_client_types = out_object_types.iter().map(|(_, object_type)| object_type);
+
+    quote! {
+        #vis struct #client_name {
+            session: nx::ipc::sf::Session
+        }
+
+        impl nx::ipc::sf::IObject for #client_name {
+            fn get_session(&mut self) -> &mut nx::ipc::sf::Session {
+                &mut self.session
+            }
+
+            fn get_command_table(&self) -> nx::ipc::sf::CommandMetadataTable {
+                <Self as #trait_name>::make_command_table()
+            }
+        }
+
+        impl nx::service::IClientObject for #client_name {
+            fn new(session: nx::ipc::sf::Session) -> Self {
+                Self { session: session }
+            }
+        }
+
+        impl #trait_name for #client_name {
+            #( type #out_object_names = #out_object_client_types; )*
+
+            #( #client_methods )*
+        }
+    }
+}
+
+fn ipc_interface_impl(args: AttributeArgs, mut trait_item: ItemTrait) -> syn::Result<TokenStream2> {
+    let interface_args = parse_interface_args(&args)?;
+    if !trait_item.generics.params.is_empty() {
+        return Err(Error::new_spanned(&trait_item.generics, "IPC interfaces can't be generic"));
+    }
+
+    let mut commands = Vec::new();
+    let mut other_items = Vec::new();
+    for item in trait_item.items.drain(..) {
+        match item {
+            TraitItem::Method(method) => commands.push(parse_command(method)?),
+            item => other_items.push(item)
+        };
+    }
+
+    let out_object_types = make_out_object_types(&commands)?;
+    let out_object_names: Vec<&Ident> = out_object_types.iter().map(|(name, _)| name).collect();
+    let methods: Vec<&TraitItemMethod> = commands.iter().map(|command| &command.method).collect();
+    let server_impl_fns: Vec<TokenStream2> = commands.iter().map(|command| make_server_impl_fn(command, interface_args.is_control)).collect();
+    let command_table_fn = make_command_table_fn(&commands);
+
+    let attrs = &trait_item.attrs;
+    let vis = &trait_item.vis;
+    let trait_name = &trait_item.ident;
+    let colon_token = &trait_item.colon_token;
+    let supertraits = &trait_item.supertraits;
+    let clients: Vec<TokenStream2> = interface_args.client_names.iter().map(|client_name| make_client(&trait_item, &commands, &out_object_types, client_name)).collect();
+
+    Ok(quote! {
+        #( #attrs )*
+        #vis trait #trait_name #colon_token #supertraits {
+            #( #other_items )*
+
+            #( type #out_object_names: nx::ipc::sf::IObject + 'static; )*
+
+            #( #methods )*
+
+            #( #server_impl_fns )*
+
+            #command_table_fn
+        }
+
+        #( #clients )*
+    })
+}
+
+// Turns a trait whose methods are annotated with #[ipc_rid(<id>)] into an IPC interface:
+// - the trait gets the server-side command handlers and make_command_table(), used by servers in IObject::get_command_table()
+// - every `client = "<name>"` argument generates a client struct implementing IObject, service::IClientObject and the trait itself
+// Commands returning sub-interfaces are declared with the client type (mem::Shared<ClientType>), which becomes an associated type of the trait:
+// generated clients set it to the declared type, while servers can set it to any IServerObject of their own
+// A `control` argument makes it an interface of control commands (responded as such), which can't have generated clients
+#[proc_macro_attribute]
+pub fn ipc_interface(args: TokenStream, input: TokenStream) -> TokenStream {
+    let args = parse_macro_input!(args as AttributeArgs);
+    let trait_item = parse_macro_input!(input as ItemTrait);
+
+    match ipc_interface_impl(args, trait_item) {
+        Ok(output) => output.into(),
+        Err(err) => err.to_compile_error().into()
+    }
+}
\ No newline at end of file
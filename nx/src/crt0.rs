@@ -6,6 +6,7 @@ use crate::dynamic;
 use crate::sync;
 use crate::util;
 use crate::hbl;
+use crate::version;
 use crate::thread;
 use crate::vmem;
 
@@ -20,7 +21,7 @@ extern "Rust" {
 
 pub type ExitFn = fn(ResultCode);
 
-static mut G_EXIT_FN: sync::Locked<option::Option<ExitFn>> = sync::Locked::new(false, None);
+static G_EXIT_FN: sync::Mutex<option::Option<ExitFn>> = sync::Mutex::new(None);
 static mut G_MAIN_THREAD: thread::Thread = thread::Thread::empty();
 
 #[no_mangle]
@@ -51,6 +52,10 @@ unsafe fn __nx_crt0_entry(abi_ptr: *const hbl::AbiConfigEntry, raw_main_thread_h
                 },
                 hbl::AbiConfigEntryKey::MainThreadHandle => {
                     main_thread_handle = (*abi_entry).value[0] as svc::Handle;
+                },
+                hbl::AbiConfigEntryKey::HosVersion => {
+                    version::set_version(version::Version::from_raw((*abi_entry).value[0] as u32));
+                    version::set_is_atmosphere((*abi_entry).value[1] == version::ATMOSPHERE_MAGIC);
                 }
                 _ => {
                     
@@ -69,10 +74,10 @@ unsafe fn __nx_crt0_entry(abi_ptr: *const hbl::AbiConfigEntry, raw_main_thread_h
 
     // Set exit function (will be null for non-hbl NROs)
     if is_hbl_nro {
-        G_EXIT_FN.set(Some(lr_exit_fn));
+        *G_EXIT_FN.lock() = Some(lr_exit_fn);
     }
     else {
-        G_EXIT_FN.set(None);
+        *G_EXIT_FN.lock() = None;
     }
     
     // Initialize heap and memory allocation
@@ -87,6 +92,8 @@ unsafe fn __nx_crt0_entry(abi_ptr: *const hbl::AbiConfigEntry, raw_main_thread_h
         Err(rc) => rc,
     };
 
+    thread::exit_main_thread();
+
     // Exit
     exit(rc);
 }
@@ -97,14 +104,14 @@ unsafe fn __nx_crt0_exception_entry(_error_desc: u32, _stack_top: *mut u8) {
 }
 
 pub fn exit(rc: ResultCode) -> ! {
-    unsafe {
-        match G_EXIT_FN.get() {
-            Some(exit_fn) => {
-                exit_fn(rc);
-            },
-            None => {
-                svc::exit_process();
-            }
+    // Copy the function out so that the lock isn't held while exiting
+    let exit_fn = *G_EXIT_FN.lock();
+    match exit_fn {
+        Some(exit_fn) => {
+            exit_fn(rc);
+        },
+        None => {
+            svc::exit_process();
         }
     }
     loop {}
@@ -313,10 +313,7 @@ impl Logger for LmLogger {
     fn new() -> Self {
         let mut service = service::new_service_object::<lm::LogService>();
         let logger = match service {
-            Ok(ref mut service_obj) => match service_obj.get().open_logger(sf::ProcessId::new()) {
-                Ok(logger_obj) => Ok(logger_obj.to::<lm::Logger>()),
-                Err(rc) => Err(rc),
-            },
+            Ok(ref mut service_obj) => service_obj.get().open_logger(sf::ProcessId::new()),
             Err(rc) => Err(rc),
         };
         Self { service: service, logger: logger }
@@ -0,0 +1,475 @@
+use crate::result::*;
+use crate::results;
+use crate::ipc::sf;
+use crate::mem;
+use crate::sync;
+use crate::service;
+use crate::service::fspsrv;
+use crate::service::fspsrv::IFile;
+use crate::service::fspsrv::IDirectory;
+use crate::service::fspsrv::IFileSystem;
+use crate::service::fspsrv::IFileSystemProxy;
+use alloc::string::String;
+use alloc::vec::Vec;
+
+pub use crate::service::fspsrv::FileAttribute;
+pub use crate::service::fspsrv::DirectoryOpenMode;
+pub use crate::service::fspsrv::DirectoryEntry;
+pub use crate::service::fspsrv::DirectoryEntryType;
+pub use crate::service::fspsrv::FileTimeStampRaw;
+
+pub mod path;
+
+pub mod romfs;
+
+pub const SD_CARD_MOUNT_NAME: &str = "sdmc";
+
+bit_enum! {
+    FileOpenOption (u32) {
+        None = 0,
+        Create = bit!(0),
+        Read = bit!(1),
+        Write = bit!(2),
+        Append = bit!(3)
+    }
+}
+
+impl FileOpenOption {
+    pub fn to_file_open_mode(self) -> fspsrv::FileOpenMode {
+        let mut mode = fspsrv::FileOpenMode::from(0);
+        if self.contains(FileOpenOption::Read()) {
+            mode |= fspsrv::FileOpenMode::Read();
+        }
+        // Writable files are always opened for appending (as libnx's fsdev does), otherwise writes past their end would fail instead of growing them
+        if self.contains(FileOpenOption::Write()) || self.contains(FileOpenOption::Append()) {
+            mode |= fspsrv::FileOpenMode::Write() | fspsrv::FileOpenMode::Append();
+        }
+        mode
+    }
+}
+
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub enum SeekFrom {
+    Start(usize),
+    Current(isize),
+    End(isize)
+}
+
+pub trait Read {
+    fn read(&mut self, buf: &mut [u8]) -> Result<usize>;
+
+    fn read_exact(&mut self, buf: &mut [u8]) -> Result<()> {
+        let mut read_size: usize = 0;
+        while read_size < buf.len() {
+            let cur_read_size = self.read(&mut buf[read_size..])?;
+            result_return_if!(cur_read_size == 0, results::fs::ResultOutOfRange);
+            read_size += cur_read_size;
+        }
+        Ok(())
+    }
+
+    fn read_to_end(&mut self, buf: &mut Vec<u8>) -> Result<usize> {
+        let mut tmp_buf: [u8; 0x200] = [0; 0x200];
+        let mut read_size: usize = 0;
+        loop {
+            let cur_read_size = self.read(&mut tmp_buf)?;
+            if cur_read_size == 0 {
+                break;
+            }
+            buf.extend_from_slice(&tmp_buf[..cur_read_size]);
+            read_size += cur_read_size;
+        }
+        Ok(read_size)
+    }
+
+    fn read_val<T: Copy + Default>(&mut self) -> Result<T> {
+        let mut t = T::default();
+        self.read_exact(unsafe { core::slice::from_raw_parts_mut(&mut t as *mut T as *mut u8, core::mem::size_of::<T>()) })?;
+        Ok(t)
+    }
+}
+
+pub trait Write {
+    fn write(&mut self, buf: &[u8]) -> Result<usize>;
+    fn flush(&mut self) -> Result<()>;
+
+    fn write_all(&mut self, buf: &[u8]) -> Result<()> {
+        let mut written_size: usize = 0;
+        while written_size < buf.len() {
+            written_size += self.write(&buf[written_size..])?;
+        }
+        Ok(())
+    }
+
+    fn write_val<T: Copy>(&mut self, t: &T) -> Result<()> {
+        self.write_all(unsafe { core::slice::from_raw_parts(t as *const T as *const u8, core::mem::size_of::<T>()) })
+    }
+}
+
+pub trait Seek {
+    fn seek(&mut self, pos: SeekFrom) -> Result<usize>;
+
+    fn rewind(&mut self) -> Result<()> {
+        self.seek(SeekFrom::Start(0))?;
+        Ok(())
+    }
+
+    fn stream_position(&mut self) -> Result<usize> {
+        self.seek(SeekFrom::Current(0))
+    }
+}
+
+pub struct File {
+    file: mem::Shared<fspsrv::File>,
+    option: FileOpenOption,
+    offset: usize
+}
+
+impl File {
+    pub fn new(file: mem::Shared<fspsrv::File>, option: FileOpenOption) -> Self {
+        Self { file: file, option: option, offset: 0 }
+    }
+
+    pub fn get_size(&mut self) -> Result<usize> {
+        self.file.get().get_size()
+    }
+
+    pub fn set_size(&mut self, size: usize) -> Result<()> {
+        result_return_unless!(self.option.contains(FileOpenOption::Write()) || self.option.contains(FileOpenOption::Append()), results::lib::fs::ResultNotInFileOpenMode);
+        self.file.get().set_size(size)
+    }
+
+    pub fn get_option(&self) -> FileOpenOption {
+        self.option
+    }
+
+    pub fn get_file(&self) -> mem::Shared<fspsrv::File> {
+        self.file.clone()
+    }
+}
+
+impl Read for File {
+    fn read(&mut self, buf: &mut [u8]) -> Result<usize> {
+        result_return_unless!(self.option.contains(FileOpenOption::Read()), results::lib::fs::ResultNotInFileOpenMode);
+        let read_size = self.file.get().read(fspsrv::FileReadOption::None(), self.offset, buf.len(), sf::Buffer::from_mut(buf.as_mut_ptr(), buf.len()))?;
+        self.offset += read_size;
+        Ok(read_size)
+    }
+}
+
+impl Write for File {
+    fn write(&mut self, buf: &[u8]) -> Result<usize> {
+        result_return_unless!(self.option.contains(FileOpenOption::Write()) || self.option.contains(FileOpenOption::Append()), results::lib::fs::ResultNotInFileOpenMode);
+        if self.option.contains(FileOpenOption::Append()) {
+            self.offset = self.get_size()?;
+        }
+        self.file.get().write(fspsrv::FileWriteOption::None(), self.offset, buf.len(), sf::Buffer::from_const(buf.as_ptr(), buf.len()))?;
+        self.offset += buf.len();
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> Result<()> {
+        self.file.get().flush()
+    }
+}
+
+impl Seek for File {
+    fn seek(&mut self, pos: SeekFrom) -> Result<usize> {
+        let (base, offset) = match pos {
+            SeekFrom::Start(offset) => (0, offset as isize),
+            SeekFrom::Current(offset) => (self.offset, offset),
+            SeekFrom::End(offset) => (self.get_size()?, offset)
+        };
+        let new_offset = base as isize + offset;
+        result_return_if!(new_offset < 0, results::fs::ResultInvalidOffset);
+
+        self.offset = new_offset as usize;
+        Ok(self.offset)
+    }
+}
+
+pub struct Dir {
+    dir: mem::Shared<fspsrv::Directory>
+}
+
+impl Dir {
+    pub fn new(dir: mem::Shared<fspsrv::Directory>) -> Self {
+        Self { dir: dir }
+    }
+
+    pub fn get_entry_count(&mut self) -> Result<u64> {
+        self.dir.get().get_entry_count()
+    }
+
+    pub fn read_entries(&mut self, entries: &mut [DirectoryEntry]) -> Result<usize> {
+        let read_count = self.dir.get().read(sf::Buffer::from_mut(entries.as_mut_ptr(), core::mem::size_of_val(entries)))?;
+        Ok(read_count as usize)
+    }
+
+    pub fn read_next(&mut self) -> Result<Option<DirectoryEntry>> {
+        let mut entries: [DirectoryEntry; 1] = [DirectoryEntry::empty()];
+        match self.read_entries(&mut entries)? {
+            0 => Ok(None),
+            _ => Ok(Some(entries[0]))
+        }
+    }
+
+    pub fn get_dir(&self) -> mem::Shared<fspsrv::Directory> {
+        self.dir.clone()
+    }
+}
+
+impl Iterator for Dir {
+    type Item = Result<DirectoryEntry>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        match self.read_next() {
+            Ok(Some(entry)) => Some(Ok(entry)),
+            Ok(None) => None,
+            Err(rc) => Some(Err(rc))
+        }
+    }
+}
+
+struct Mount {
+    name: String,
+    fs: mem::Shared<fspsrv::FileSystem>
+}
+
+static G_LOCK: sync::RawMutex = sync::RawMutex::new(true);
+static mut G_FSP_SERVICE: mem::Shared<fspsrv::FileSystemProxy> = mem::Shared::empty();
+static mut G_MOUNTS: Vec<Mount> = Vec::new();
+static mut G_INITIALIZED: bool = false;
+
+unsafe fn initialize_impl() -> Result<()> {
+    if !G_INITIALIZED {
+        G_FSP_SERVICE = service::new_service_object::<fspsrv::FileSystemProxy>()?;
+        G_INITIALIZED = true;
+
+        // Mount the SD card by default, like any other homebrew library does
+        if let Err(rc) = mount_sd_card(SD_CARD_MOUNT_NAME) {
+            G_FSP_SERVICE.reset();
+            G_INITIALIZED = false;
+            return Err(rc);
+        }
+    }
+    Ok(())
+}
+
+pub fn initialize() -> Result<()> {
+    unsafe {
+        let _guard = sync::ScopedLock::new(&G_LOCK);
+        initialize_impl()
+    }
+}
+
+pub fn is_initialized() -> bool {
+    unsafe {
+        let _guard = sync::ScopedLock::new(&G_LOCK);
+        G_INITIALIZED
+    }
+}
+
+pub fn exit() {
+    unsafe {
+        let _guard = sync::ScopedLock::new(&G_LOCK);
+        if G_INITIALIZED {
+            G_MOUNTS.clear();
+            G_FSP_SERVICE.reset();
+            G_INITIALIZED = false;
+        }
+    }
+}
+
+pub fn get_fspsrv_session() -> Result<mem::Shared<fspsrv::FileSystemProxy>> {
+    unsafe {
+        let _guard = sync::ScopedLock::new(&G_LOCK);
+        initialize_impl()?;
+        Ok(G_FSP_SERVICE.clone())
+    }
+}
+
+pub fn mount(name: &str, fs: mem::Shared<fspsrv::FileSystem>) -> Result<()> {
+    result_return_unless!(path::is_valid_mount_name(name), results::lib::fs::ResultInvalidMountName);
+
+    unsafe {
+        let _guard = sync::ScopedLock::new(&G_LOCK);
+        result_return_if!(G_MOUNTS.iter().any(|mount| mount.name == name), results::lib::fs::ResultMountNameAlreadyExists);
+
+        G_MOUNTS.push(Mount { name: String::from(name), fs: fs });
+    }
+    Ok(())
+}
+
+pub fn mount_sd_card(name: &str) -> Result<()> {
+    let sd_fs = get_fspsrv_session()?.get().open_sd_card_filesystem()?;
+    mount(name, sd_fs)
+}
+
+pub fn mount_save_data(name: &str, space_id: fspsrv::SaveDataSpaceId, attribute: fspsrv::SaveDataAttribute) -> Result<()> {
+    let save_data_fs = get_fspsrv_session()?.get().open_save_data_filesystem(space_id, attribute)?;
+    mount(name, save_data_fs)
+}
+
+pub fn mount_system_save_data(name: &str, space_id: fspsrv::SaveDataSpaceId, attribute: fspsrv::SaveDataAttribute) -> Result<()> {
+    let save_data_fs = get_fspsrv_session()?.get().open_save_data_filesystem_by_system_save_data_id(space_id, attribute)?;
+    mount(name, save_data_fs)
+}
+
+pub fn mount_bis(name: &str, partition_id: fspsrv::BisPartitionId) -> Result<()> {
+    // An empty root path opens the whole partition
+    let root_path = fspsrv::Path { path: [0; 0x301] };
+    let bis_fs = get_fspsrv_session()?.get().open_bis_filesystem(partition_id, sf::Buffer::from_var(&root_path))?;
+    mount(name, bis_fs)
+}
+
+pub fn mount_content_storage(name: &str, content_storage_id: fspsrv::ContentStorageId) -> Result<()> {
+    let content_storage_fs = get_fspsrv_session()?.get().open_content_storage_filesystem(content_storage_id)?;
+    mount(name, content_storage_fs)
+}
+
+pub fn unmount(name: &str) -> Result<()> {
+    unsafe {
+        let _guard = sync::ScopedLock::new(&G_LOCK);
+        match G_MOUNTS.iter().position(|mount| mount.name == name) {
+            Some(index) => {
+                G_MOUNTS.remove(index);
+                Ok(())
+            },
+            None => Err(results::lib::fs::ResultMountNameNotFound::make())
+        }
+    }
+}
+
+pub fn get_mounted_filesystem(name: &str) -> Result<mem::Shared<fspsrv::FileSystem>> {
+    unsafe {
+        let _guard = sync::ScopedLock::new(&G_LOCK);
+        initialize_impl()?;
+        match G_MOUNTS.iter().find(|mount| mount.name == name) {
+            Some(mount) => Ok(mount.fs.clone()),
+            None => Err(results::lib::fs::ResultMountNameNotFound::make())
+        }
+    }
+}
+
+fn resolve_path(path: &str) -> Result<(mem::Shared<fspsrv::FileSystem>, fspsrv::Path)> {
+    let (mount_name, fs_path) = path::parse(path)?;
+    let fs = get_mounted_filesystem(mount_name)?;
+    Ok((fs, fspsrv::Path::from(&fs_path)?))
+}
+
+pub fn create_file(path: &str, size: usize, attribute: FileAttribute) -> Result<()> {
+    let (fs, fs_path) = resolve_path(path)?;
+    fs.get().create_file(attribute, size, sf::Buffer::from_var(&fs_path))
+}
+
+pub fn delete_file(path: &str) -> Result<()> {
+    let (fs, fs_path) = resolve_path(path)?;
+    fs.get().delete_file(sf::Buffer::from_var(&fs_path))
+}
+
+pub fn create_directory(path: &str) -> Result<()> {
+    let (fs, fs_path) = resolve_path(path)?;
+    fs.get().create_directory(sf::Buffer::from_var(&fs_path))
+}
+
+pub fn delete_directory(path: &str) -> Result<()> {
+    let (fs, fs_path) = resolve_path(path)?;
+    fs.get().delete_directory(sf::Buffer::from_var(&fs_path))
+}
+
+pub fn delete_directory_recursively(path: &str) -> Result<()> {
+    let (fs, fs_path) = resolve_path(path)?;
+    fs.get().delete_directory_recursively(sf::Buffer::from_var(&fs_path))
+}
+
+pub fn clean_directory_recursively(path: &str) -> Result<()> {
+    let (fs, fs_path) = resolve_path(path)?;
+    fs.get().clean_directory_recursively(sf::Buffer::from_var(&fs_path))
+}
+
+fn resolve_rename_paths(old_path: &str, new_path: &str) -> Result<(mem::Shared<fspsrv::FileSystem>, fspsrv::Path, fspsrv::Path)> {
+    let (old_mount_name, old_fs_path) = path::parse(old_path)?;
+    let (new_mount_name, new_fs_path) = path::parse(new_path)?;
+    // Entries can't be renamed/moved across different filesystems
+    result_return_unless!(old_mount_name == new_mount_name, results::fs::ResultInvalidPathFormat);
+
+    let fs = get_mounted_filesystem(old_mount_name)?;
+    Ok((fs, fspsrv::Path::from(&old_fs_path)?, fspsrv::Path::from(&new_fs_path)?))
+}
+
+pub fn rename_file(old_path: &str, new_path: &str) -> Result<()> {
+    let (fs, old_fs_path, new_fs_path) = resolve_rename_paths(old_path, new_path)?;
+    fs.get().rename_file(sf::Buffer::from_var(&old_fs_path), sf::Buffer::from_var(&new_fs_path))
+}
+
+pub fn rename_directory(old_path: &str, new_path: &str) -> Result<()> {
+    let (fs, old_fs_path, new_fs_path) = resolve_rename_paths(old_path, new_path)?;
+    fs.get().rename_directory(sf::Buffer::from_var(&old_fs_path), sf::Buffer::from_var(&new_fs_path))
+}
+
+pub fn get_entry_type(path: &str) -> Result<DirectoryEntryType> {
+    let (fs, fs_path) = resolve_path(path)?;
+    fs.get().get_entry_type(sf::Buffer::from_var(&fs_path))
+}
+
+pub fn exists(path: &str) -> bool {
+    get_entry_type(path).is_ok()
+}
+
+pub fn get_file_time_stamp_raw(path: &str) -> Result<FileTimeStampRaw> {
+    let (fs, fs_path) = resolve_path(path)?;
+    fs.get().get_file_time_stamp_raw(sf::Buffer::from_var(&fs_path))
+}
+
+pub fn get_free_space_size(path: &str) -> Result<usize> {
+    let (fs, fs_path) = resolve_path(path)?;
+    fs.get().get_free_space_size(sf::Buffer::from_var(&fs_path))
+}
+
+pub fn get_total_space_size(path: &str) -> Result<usize> {
+    let (fs, fs_path) = resolve_path(path)?;
+    fs.get().get_total_space_size(sf::Buffer::from_var(&fs_path))
+}
+
+pub fn commit(mount_name: &str) -> Result<()> {
+    get_mounted_filesystem(mount_name)?.get().commit()
+}
+
+pub fn open_file(path: &str, option: FileOpenOption) -> Result<File> {
+    let (fs, fs_path) = resolve_path(path)?;
+    if option.contains(FileOpenOption::Create()) {
+        // Creating the file fails if it already exists, which is fine for us here
+        let _ = fs.get().create_file(FileAttribute::None(), 0, sf::Buffer::from_var(&fs_path));
+    }
+
+    let file = fs.get().open_file(option.to_file_open_mode(), sf::Buffer::from_var(&fs_path))?;
+    Ok(File::new(file, option))
+}
+
+pub fn open_directory(path: &str, mode: DirectoryOpenMode) -> Result<Dir> {
+    let (fs, fs_path) = resolve_path(path)?;
+    let dir = fs.get().open_directory(mode, sf::Buffer::from_var(&fs_path))?;
+    Ok(Dir::new(dir))
+}
+
+pub fn read_directory(path: &str) -> Result<Dir> {
+    open_directory(path, DirectoryOpenMode::ReadDirectories() | DirectoryOpenMode::ReadFiles())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn file_open_modes() {
+        assert_eq!(FileOpenOption::Read().to_file_open_mode(), fspsrv::FileOpenMode::Read());
+
+        // Writes past the end of the file only grow it with the append mode
+        let write_mode = fspsrv::FileOpenMode::Write() | fspsrv::FileOpenMode::Append();
+        assert_eq!(FileOpenOption::Write().to_file_open_mode(), write_mode);
+        assert_eq!((FileOpenOption::Create() | FileOpenOption::Write()).to_file_open_mode(), write_mode);
+        assert_eq!(FileOpenOption::Append().to_file_open_mode(), write_mode);
+        assert_eq!((FileOpenOption::Read() | FileOpenOption::Write()).to_file_open_mode(), fspsrv::FileOpenMode::Read() | write_mode);
+    }
+}
\ No newline at end of file
@@ -0,0 +1,172 @@
+use crate::result::*;
+use crate::results;
+use alloc::string::String;
+use alloc::vec::Vec;
+
+pub const MOUNT_NAME_SEPARATOR: char = ':';
+pub const PATH_SEPARATOR: char = '/';
+pub const MAX_MOUNT_NAME_LENGTH: usize = 0xF;
+pub const MAX_PATH_LENGTH: usize = 0x300;
+
+const INVALID_PATH_CHARACTERS: [char; 6] = [':', '*', '?', '<', '>', '|'];
+
+pub fn is_valid_mount_name(name: &str) -> bool {
+    !name.is_empty() && (name.len() <= MAX_MOUNT_NAME_LENGTH) && !name.contains(PATH_SEPARATOR) && !name.contains(MOUNT_NAME_SEPARATOR)
+}
+
+// Splits "<mount-name>:/<path>" into the mount name and the (non-normalized) path inside the mount
+pub fn split_mount_name(path: &str) -> Result<(&str, &str)> {
+    match path.find(MOUNT_NAME_SEPARATOR) {
+        Some(separator_index) => {
+            let mount_name = &path[..separator_index];
+            let inner_path = &path[separator_index + 1..];
+            result_return_unless!(is_valid_mount_name(mount_name), results::lib::fs::ResultInvalidMountName);
+            Ok((mount_name, inner_path))
+        },
+        None => Err(results::lib::fs::ResultInvalidMountName::make())
+    }
+}
+
+// Normalizes an absolute path: repeated separators and "." components are dropped, and ".." components go up one level (never above the root)
+pub fn normalize(path: &str) -> Result<String> {
+    result_return_unless!(path.starts_with(PATH_SEPARATOR), results::fs::ResultInvalidPathFormat);
+
+    let mut components: Vec<&str> = Vec::new();
+    for component in path.split(PATH_SEPARATOR) {
+        match component {
+            "" | "." => {},
+            ".." => {
+                components.pop();
+            },
+            _ => {
+                result_return_if!(component.contains(&INVALID_PATH_CHARACTERS[..]), results::fs::ResultInvalidCharacter);
+                components.push(component);
+            }
+        };
+    }
+
+    let mut normalized_path = String::new();
+    for component in components {
+        normalized_path.push(PATH_SEPARATOR);
+        normalized_path.push_str(component);
+    }
+    if normalized_path.is_empty() {
+        normalized_path.push(PATH_SEPARATOR);
+    }
+
+    result_return_if!(normalized_path.len() > MAX_PATH_LENGTH, results::fs::ResultTooLongPath);
+    Ok(normalized_path)
+}
+
+// Parses "<mount-name>:/<path>" into the mount name and the normalized path inside the mount
+pub fn parse(path: &str) -> Result<(&str, String)> {
+    let (mount_name, inner_path) = split_mount_name(path)?;
+    let normalized_path = normalize(inner_path)?;
+    Ok((mount_name, normalized_path))
+}
+
+pub fn join(base: &str, name: &str) -> String {
+    let mut path = String::from(base);
+    if !path.ends_with(PATH_SEPARATOR) {
+        path.push(PATH_SEPARATOR);
+    }
+    path.push_str(name.trim_start_matches(PATH_SEPARATOR));
+    path
+}
+
+pub fn get_file_name(path: &str) -> &str {
+    match path.rfind(PATH_SEPARATOR) {
+        Some(separator_index) => &path[separator_index + 1..],
+        None => path
+    }
+}
+
+pub fn get_parent(path: &str) -> &str {
+    match path.rfind(PATH_SEPARATOR) {
+        Some(0) => &path[..1],
+        Some(separator_index) => &path[..separator_index],
+        None => ""
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn normalize_collapses_components() {
+        assert_eq!(normalize("/").unwrap(), "/");
+        assert_eq!(normalize("/a/b/c").unwrap(), "/a/b/c");
+        assert_eq!(normalize("//a///b//").unwrap(), "/a/b");
+        assert_eq!(normalize("/a/./b/.").unwrap(), "/a/b");
+        assert_eq!(normalize("/a/b/../c").unwrap(), "/a/c");
+        assert_eq!(normalize("/a/b/../..").unwrap(), "/");
+    }
+
+    #[test]
+    fn normalize_stays_at_root() {
+        assert_eq!(normalize("/..").unwrap(), "/");
+        assert_eq!(normalize("/../../a").unwrap(), "/a");
+        assert_eq!(normalize("/a/../../b/..").unwrap(), "/");
+    }
+
+    #[test]
+    fn normalize_rejects_invalid_paths() {
+        assert!(results::fs::ResultInvalidPathFormat::matches(normalize("").unwrap_err()));
+        assert!(results::fs::ResultInvalidPathFormat::matches(normalize("a/b").unwrap_err()));
+        assert!(results::fs::ResultInvalidCharacter::matches(normalize("/a/b*c").unwrap_err()));
+        assert!(results::fs::ResultInvalidCharacter::matches(normalize("/a:/b").unwrap_err()));
+
+        let long_path = "/a".repeat(MAX_PATH_LENGTH / 2 + 1);
+        assert!(results::fs::ResultTooLongPath::matches(normalize(&long_path).unwrap_err()));
+        // The limit applies to the normalized path
+        let long_dotted_path = "/.".repeat(MAX_PATH_LENGTH) + "/a";
+        assert_eq!(normalize(&long_dotted_path).unwrap(), "/a");
+    }
+
+    #[test]
+    fn parse_splits_mount_name() {
+        let (mount_name, path) = parse("sdmc:/a//b/../c").unwrap();
+        assert_eq!(mount_name, "sdmc");
+        assert_eq!(path, "/a/c");
+
+        let (mount_name, path) = parse("sdmc:/").unwrap();
+        assert_eq!(mount_name, "sdmc");
+        assert_eq!(path, "/");
+    }
+
+    #[test]
+    fn parse_rejects_invalid_mount_names() {
+        // Missing mount name
+        assert!(results::lib::fs::ResultInvalidMountName::matches(parse(":/a").unwrap_err()));
+        assert!(results::lib::fs::ResultInvalidMountName::matches(parse("/a/b").unwrap_err()));
+        assert!(results::lib::fs::ResultInvalidMountName::matches(parse("a/b:/c").unwrap_err()));
+        assert!(results::lib::fs::ResultInvalidMountName::matches(parse("0123456789abcdef:/").unwrap_err()));
+        assert!(parse("0123456789abcde:/").is_ok());
+    }
+
+    #[test]
+    fn parse_rejects_missing_path() {
+        // Trailing ':' (the path inside the mount has to be absolute)
+        assert!(results::fs::ResultInvalidPathFormat::matches(parse("sdmc:").unwrap_err()));
+        assert!(results::fs::ResultInvalidPathFormat::matches(parse("sdmc:a").unwrap_err()));
+        // Only the first ':' separates the mount name
+        assert!(results::fs::ResultInvalidCharacter::matches(parse("sdmc:/a:").unwrap_err()));
+        assert!(results::fs::ResultInvalidPathFormat::matches(parse("sdmc::/a").unwrap_err()));
+    }
+
+    #[test]
+    fn join_and_split_paths() {
+        assert_eq!(join("/", "a"), "/a");
+        assert_eq!(join("/a", "b"), "/a/b");
+        assert_eq!(join("/a/", "/b"), "/a/b");
+
+        assert_eq!(get_file_name("/a/b.txt"), "b.txt");
+        assert_eq!(get_file_name("/"), "");
+        assert_eq!(get_file_name("b.txt"), "b.txt");
+
+        assert_eq!(get_parent("/a/b"), "/a");
+        assert_eq!(get_parent("/a"), "/");
+        assert_eq!(get_parent("a"), "");
+    }
+}
\ No newline at end of file
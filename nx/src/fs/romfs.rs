@@ -0,0 +1,607 @@
+use crate::result::*;
+use crate::results;
+use crate::ipc::sf;
+use crate::mem;
+use crate::service::fspsrv;
+use crate::service::fspsrv::IStorage;
+use crate::service::fspsrv::IFileSystemProxy;
+use super::path;
+use super::Read;
+use super::Seek;
+use super::SeekFrom;
+use super::DirectoryEntryType;
+use alloc::vec::Vec;
+use alloc::string::String;
+
+pub const EMPTY_ENTRY: u32 = 0xFFFFFFFF;
+pub const ROOT_DIRECTORY_OFFSET: u32 = 0;
+
+const HEADER_SIZE: usize = 0x50;
+const DIRECTORY_ENTRY_HEADER_SIZE: usize = 0x18;
+const FILE_ENTRY_HEADER_SIZE: usize = 0x20;
+
+// Any byte source a RomFs image can be read from (the storage from fsp-srv, an in-memory image...)
+
+pub trait StorageRead {
+    fn read(&mut self, offset: usize, buf: &mut [u8]) -> Result<()>;
+    fn get_size(&mut self) -> Result<usize>;
+}
+
+impl StorageRead for mem::Shared<fspsrv::Storage> {
+    fn read(&mut self, offset: usize, buf: &mut [u8]) -> Result<()> {
+        self.get().read(offset, buf.len(), sf::Buffer::from_mut(buf.as_mut_ptr(), buf.len()))
+    }
+
+    fn get_size(&mut self) -> Result<usize> {
+        self.get().get_size()
+    }
+}
+
+pub struct MemoryStorage<'a> {
+    data: &'a [u8]
+}
+
+impl<'a> MemoryStorage<'a> {
+    pub const fn new(data: &'a [u8]) -> Self {
+        Self { data: data }
+    }
+}
+
+impl<'a> StorageRead for MemoryStorage<'a> {
+    fn read(&mut self, offset: usize, buf: &mut [u8]) -> Result<()> {
+        result_return_if!((offset > self.data.len()) || (buf.len() > (self.data.len() - offset)), results::fs::ResultOutOfRange);
+        buf.copy_from_slice(&self.data[offset..offset + buf.len()]);
+        Ok(())
+    }
+
+    fn get_size(&mut self) -> Result<usize> {
+        Ok(self.data.len())
+    }
+}
+
+#[derive(Copy, Clone, Default)]
+pub struct Header {
+    pub header_size: u64,
+    pub dir_hash_table_offset: u64,
+    pub dir_hash_table_size: u64,
+    pub dir_table_offset: u64,
+    pub dir_table_size: u64,
+    pub file_hash_table_offset: u64,
+    pub file_hash_table_size: u64,
+    pub file_table_offset: u64,
+    pub file_table_size: u64,
+    pub file_data_offset: u64
+}
+
+impl Header {
+    pub fn parse(data: &[u8]) -> Result<Self> {
+        result_return_if!(data.len() < HEADER_SIZE, results::lib::fs::ResultInvalidRomFsHeader);
+        let header = Self {
+            header_size: read_u64(data, 0x0)?,
+            dir_hash_table_offset: read_u64(data, 0x8)?,
+            dir_hash_table_size: read_u64(data, 0x10)?,
+            dir_table_offset: read_u64(data, 0x18)?,
+            dir_table_size: read_u64(data, 0x20)?,
+            file_hash_table_offset: read_u64(data, 0x28)?,
+            file_hash_table_size: read_u64(data, 0x30)?,
+            file_table_offset: read_u64(data, 0x38)?,
+            file_table_size: read_u64(data, 0x40)?,
+            file_data_offset: read_u64(data, 0x48)?
+        };
+        result_return_unless!(header.header_size == HEADER_SIZE as u64, results::lib::fs::ResultInvalidRomFsHeader);
+        Ok(header)
+    }
+}
+
+#[derive(Clone)]
+pub struct DirectoryEntry {
+    pub parent: u32,
+    pub sibling: u32,
+    pub child_dir: u32,
+    pub child_file: u32,
+    pub next_hash: u32,
+    pub name: String
+}
+
+#[derive(Clone)]
+pub struct FileEntry {
+    pub parent: u32,
+    pub sibling: u32,
+    pub data_offset: u64,
+    pub data_size: u64,
+    pub next_hash: u32,
+    pub name: String
+}
+
+fn read_u32(data: &[u8], offset: usize) -> Result<u32> {
+    result_return_if!((offset + 4) > data.len(), results::lib::fs::ResultInvalidRomFsEntry);
+    let mut bytes: [u8; 4] = [0; 4];
+    bytes.copy_from_slice(&data[offset..offset + 4]);
+    Ok(u32::from_le_bytes(bytes))
+}
+
+fn read_u64(data: &[u8], offset: usize) -> Result<u64> {
+    result_return_if!((offset + 8) > data.len(), results::lib::fs::ResultInvalidRomFsEntry);
+    let mut bytes: [u8; 8] = [0; 8];
+    bytes.copy_from_slice(&data[offset..offset + 8]);
+    Ok(u64::from_le_bytes(bytes))
+}
+
+fn read_name(data: &[u8], offset: usize, name_len: usize) -> Result<String> {
+    result_return_if!((offset + name_len) > data.len(), results::lib::fs::ResultInvalidRomFsEntry);
+    match core::str::from_utf8(&data[offset..offset + name_len]) {
+        Ok(name) => Ok(String::from(name)),
+        Err(_) => Err(results::lib::util::ResultInvalidConversion::make())
+    }
+}
+
+fn parse_hash_table(data: &[u8]) -> Vec<u32> {
+    data.chunks_exact(4).map(|bytes| u32::from_le_bytes([bytes[0], bytes[1], bytes[2], bytes[3]])).collect()
+}
+
+pub fn calculate_path_hash(parent: u32, name: &[u8]) -> u32 {
+    let mut hash = parent ^ 123456789;
+    for c in name {
+        hash = hash.rotate_right(5);
+        hash ^= *c as u32;
+    }
+    hash
+}
+
+pub struct RomFs<S: StorageRead> {
+    storage: S,
+    base_offset: usize,
+    header: Header,
+    dir_hash_table: Vec<u32>,
+    dir_table: Vec<u8>,
+    file_hash_table: Vec<u32>,
+    file_table: Vec<u8>
+}
+
+impl<S: StorageRead> RomFs<S> {
+    pub fn new(storage: S) -> Result<Self> {
+        Self::new_at(storage, 0)
+    }
+
+    pub fn new_at(mut storage: S, base_offset: usize) -> Result<Self> {
+        let mut header_data: [u8; HEADER_SIZE] = [0; HEADER_SIZE];
+        storage.read(base_offset, &mut header_data)?;
+        let header = Header::parse(&header_data)?;
+
+        let storage_size = storage.get_size()?;
+        let mut romfs = Self { storage: storage, base_offset: base_offset, header: header, dir_hash_table: Vec::new(), dir_table: Vec::new(), file_hash_table: Vec::new(), file_table: Vec::new() };
+        // The metadata tables are small, so we keep them cached and only go through the storage for actual file data
+        let dir_hash_table_data = romfs.read_table(header.dir_hash_table_offset, header.dir_hash_table_size, storage_size)?;
+        romfs.dir_hash_table = parse_hash_table(&dir_hash_table_data);
+        romfs.dir_table = romfs.read_table(header.dir_table_offset, header.dir_table_size, storage_size)?;
+        let file_hash_table_data = romfs.read_table(header.file_hash_table_offset, header.file_hash_table_size, storage_size)?;
+        romfs.file_hash_table = parse_hash_table(&file_hash_table_data);
+        romfs.file_table = romfs.read_table(header.file_table_offset, header.file_table_size, storage_size)?;
+        Ok(romfs)
+    }
+
+    fn read_table(&mut self, offset: u64, size: u64, storage_size: usize) -> Result<Vec<u8>> {
+        // The header can't be trusted, so the table must be within the storage before allocating anything for it
+        let table_end = (self.base_offset as u64).checked_add(offset).and_then(|table_offset| table_offset.checked_add(size));
+        let table_fits = match table_end {
+            Some(table_end) => table_end <= storage_size as u64,
+            None => false
+        };
+        result_return_unless!(table_fits, results::lib::fs::ResultInvalidRomFsHeader);
+
+        let mut table = vec![0u8; size as usize];
+        self.storage.read(self.base_offset + offset as usize, &mut table)?;
+        Ok(table)
+    }
+
+    pub fn get_header(&self) -> Header {
+        self.header
+    }
+
+    // Every entry takes at least its header's size, which bounds how many entries a table can have
+    fn get_max_directory_count(&self) -> usize {
+        self.dir_table.len() / DIRECTORY_ENTRY_HEADER_SIZE
+    }
+
+    fn get_max_file_count(&self) -> usize {
+        self.file_table.len() / FILE_ENTRY_HEADER_SIZE
+    }
+
+    pub fn get_directory_entry(&self, dir_offset: u32) -> Result<DirectoryEntry> {
+        let offset = dir_offset as usize;
+        let name_len = read_u32(&self.dir_table, offset + 0x14)? as usize;
+        Ok(DirectoryEntry {
+            parent: read_u32(&self.dir_table, offset)?,
+            sibling: read_u32(&self.dir_table, offset + 0x4)?,
+            child_dir: read_u32(&self.dir_table, offset + 0x8)?,
+            child_file: read_u32(&self.dir_table, offset + 0xC)?,
+            next_hash: read_u32(&self.dir_table, offset + 0x10)?,
+            name: read_name(&self.dir_table, offset + DIRECTORY_ENTRY_HEADER_SIZE, name_len)?
+        })
+    }
+
+    pub fn get_file_entry(&self, file_offset: u32) -> Result<FileEntry> {
+        let offset = file_offset as usize;
+        let name_len = read_u32(&self.file_table, offset + 0x1C)? as usize;
+        Ok(FileEntry {
+            parent: read_u32(&self.file_table, offset)?,
+            sibling: read_u32(&self.file_table, offset + 0x4)?,
+            data_offset: read_u64(&self.file_table, offset + 0x8)?,
+            data_size: read_u64(&self.file_table, offset + 0x10)?,
+            next_hash: read_u32(&self.file_table, offset + 0x18)?,
+            name: read_name(&self.file_table, offset + FILE_ENTRY_HEADER_SIZE, name_len)?
+        })
+    }
+
+    fn find_child_directory(&self, parent: u32, name: &str) -> Result<u32> {
+        result_return_if!(self.dir_hash_table.is_empty(), results::fs::ResultPathNotFound);
+        let hash = calculate_path_hash(parent, name.as_bytes());
+        let mut cur_offset = self.dir_hash_table[hash as usize % self.dir_hash_table.len()];
+        // Chains can't be longer than the table's entry count, unless the image makes them loop
+        let mut remaining_entries = self.get_max_directory_count();
+        while cur_offset != EMPTY_ENTRY {
+            result_return_if!(remaining_entries == 0, results::lib::fs::ResultInvalidRomFsEntry);
+            remaining_entries -= 1;
+            let entry = self.get_directory_entry(cur_offset)?;
+            if (entry.parent == parent) && (entry.name == name) {
+                return Ok(cur_offset);
+            }
+            cur_offset = entry.next_hash;
+        }
+        Err(results::fs::ResultPathNotFound::make())
+    }
+
+    fn find_child_file(&self, parent: u32, name: &str) -> Result<u32> {
+        result_return_if!(self.file_hash_table.is_empty(), results::fs::ResultPathNotFound);
+        let hash = calculate_path_hash(parent, name.as_bytes());
+        let mut cur_offset = self.file_hash_table[hash as usize % self.file_hash_table.len()];
+        let mut remaining_entries = self.get_max_file_count();
+        while cur_offset != EMPTY_ENTRY {
+            result_return_if!(remaining_entries == 0, results::lib::fs::ResultInvalidRomFsEntry);
+            remaining_entries -= 1;
+            let entry = self.get_file_entry(cur_offset)?;
+            if (entry.parent == parent) && (entry.name == name) {
+                return Ok(cur_offset);
+            }
+            cur_offset = entry.next_hash;
+        }
+        Err(results::fs::ResultPathNotFound::make())
+    }
+
+    pub fn find_directory(&self, dir_path: &str) -> Result<u32> {
+        let normalized_path = path::normalize(dir_path)?;
+        let mut cur_dir = ROOT_DIRECTORY_OFFSET;
+        for component in normalized_path.split(path::PATH_SEPARATOR).filter(|component| !component.is_empty()) {
+            cur_dir = self.find_child_directory(cur_dir, component)?;
+        }
+        Ok(cur_dir)
+    }
+
+    pub fn find_file(&self, file_path: &str) -> Result<u32> {
+        let normalized_path = path::normalize(file_path)?;
+        let parent_dir = self.find_directory(path::get_parent(&normalized_path))?;
+        self.find_child_file(parent_dir, path::get_file_name(&normalized_path))
+    }
+
+    pub fn get_entry_type(&self, entry_path: &str) -> Result<DirectoryEntryType> {
+        if self.find_file(entry_path).is_ok() {
+            Ok(DirectoryEntryType::File)
+        }
+        else {
+            self.find_directory(entry_path)?;
+            Ok(DirectoryEntryType::Directory)
+        }
+    }
+
+    pub fn read_file_data(&mut self, file: &FileEntry, offset: usize, buf: &mut [u8]) -> Result<usize> {
+        let file_size = file.data_size as usize;
+        util_return_if!(offset >= file_size, Ok(0));
+
+        let read_size = core::cmp::min(buf.len(), file_size - offset);
+        let data_offset = (self.base_offset as u64).checked_add(self.header.file_data_offset).and_then(|data_offset| data_offset.checked_add(file.data_offset)).and_then(|data_offset| data_offset.checked_add(offset as u64));
+        let data_offset = match data_offset {
+            Some(data_offset) => data_offset as usize,
+            None => return Err(results::lib::fs::ResultInvalidRomFsEntry::make())
+        };
+        self.storage.read(data_offset, &mut buf[..read_size])?;
+        Ok(read_size)
+    }
+
+    pub fn open_file(&mut self, file_path: &str) -> Result<File<'_, S>> {
+        let file_offset = self.find_file(file_path)?;
+        let entry = self.get_file_entry(file_offset)?;
+        Ok(File { romfs: self, entry: entry, offset: 0 })
+    }
+
+    pub fn open_directory(&self, dir_path: &str) -> Result<Dir<'_, S>> {
+        let dir_offset = self.find_directory(dir_path)?;
+        let entry = self.get_directory_entry(dir_offset)?;
+        Ok(Dir { romfs: self, next_dir: entry.child_dir, next_file: entry.child_file, remaining_entries: self.get_max_directory_count() + self.get_max_file_count() })
+    }
+}
+
+pub fn open_current_process() -> Result<RomFs<mem::Shared<fspsrv::Storage>>> {
+    let storage = super::get_fspsrv_session()?.get().open_data_storage_by_current_process()?;
+    RomFs::new(storage)
+}
+
+pub struct File<'a, S: StorageRead> {
+    romfs: &'a mut RomFs<S>,
+    entry: FileEntry,
+    offset: usize
+}
+
+impl<'a, S: StorageRead> File<'a, S> {
+    pub fn get_size(&self) -> usize {
+        self.entry.data_size as usize
+    }
+
+    pub fn get_entry(&self) -> &FileEntry {
+        &self.entry
+    }
+}
+
+impl<'a, S: StorageRead> Read for File<'a, S> {
+    fn read(&mut self, buf: &mut [u8]) -> Result<usize> {
+        let read_size = self.romfs.read_file_data(&self.entry, self.offset, buf)?;
+        self.offset += read_size;
+        Ok(read_size)
+    }
+}
+
+impl<'a, S: StorageRead> Seek for File<'a, S> {
+    fn seek(&mut self, pos: SeekFrom) -> Result<usize> {
+        let (base, offset) = match pos {
+            SeekFrom::Start(offset) => (0, offset as isize),
+            SeekFrom::Current(offset) => (self.offset, offset),
+            SeekFrom::End(offset) => (self.get_size(), offset)
+        };
+        let new_offset = base as isize + offset;
+        result_return_if!(new_offset < 0, results::fs::ResultInvalidOffset);
+
+        self.offset = new_offset as usize;
+        Ok(self.offset)
+    }
+}
+
+#[derive(Clone)]
+pub struct Entry {
+    pub name: String,
+    pub entry_type: DirectoryEntryType,
+    pub size: usize
+}
+
+// Directories are listed first (like fsp-srv does), then files
+
+pub struct Dir<'a, S: StorageRead> {
+    romfs: &'a RomFs<S>,
+    next_dir: u32,
+    next_file: u32,
+    // Sibling chains are bounded like hash chains
+    remaining_entries: usize
+}
+
+impl<'a, S: StorageRead> Iterator for Dir<'a, S> {
+    type Item = Result<Entry>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if (self.next_dir != EMPTY_ENTRY) || (self.next_file != EMPTY_ENTRY) {
+            if self.remaining_entries == 0 {
+                self.next_dir = EMPTY_ENTRY;
+                self.next_file = EMPTY_ENTRY;
+                return Some(Err(results::lib::fs::ResultInvalidRomFsEntry::make()));
+            }
+            self.remaining_entries -= 1;
+        }
+
+        if self.next_dir != EMPTY_ENTRY {
+            match self.romfs.get_directory_entry(self.next_dir) {
+                Ok(entry) => {
+                    self.next_dir = entry.sibling;
+                    Some(Ok(Entry { name: entry.name, entry_type: DirectoryEntryType::Directory, size: 0 }))
+                },
+                Err(rc) => {
+                    self.next_dir = EMPTY_ENTRY;
+                    Some(Err(rc))
+                }
+            }
+        }
+        else if self.next_file != EMPTY_ENTRY {
+            match self.romfs.get_file_entry(self.next_file) {
+                Ok(entry) => {
+                    self.next_file = entry.sibling;
+                    Some(Ok(Entry { name: entry.name, entry_type: DirectoryEntryType::File, size: entry.data_size as usize }))
+                },
+                Err(rc) => {
+                    self.next_file = EMPTY_ENTRY;
+                    Some(Err(rc))
+                }
+            }
+        }
+        else {
+            None
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Generated by test-data/romfs/make_test_romfs.py:
+    // /a.txt, /b.txt, /empty.bin, /data/numbers.bin, /data/nested/deep.txt and the empty /docs directory
+    static TEST_ROMFS: &[u8] = include_bytes!("../../test-data/romfs/test.romfs");
+
+    fn open_test_romfs() -> RomFs<MemoryStorage<'static>> {
+        RomFs::new(MemoryStorage::new(TEST_ROMFS)).unwrap()
+    }
+
+    fn read_file(romfs: &mut RomFs<MemoryStorage<'static>>, file_path: &str) -> Vec<u8> {
+        let mut data: Vec<u8> = Vec::new();
+        romfs.open_file(file_path).unwrap().read_to_end(&mut data).unwrap();
+        data
+    }
+
+    fn list_directory(romfs: &RomFs<MemoryStorage<'static>>, dir_path: &str) -> Vec<(String, DirectoryEntryType, usize)> {
+        romfs.open_directory(dir_path).unwrap().map(|entry| {
+            let entry = entry.unwrap();
+            (entry.name, entry.entry_type, entry.size)
+        }).collect()
+    }
+
+    #[test]
+    fn path_hash() {
+        assert_eq!(calculate_path_hash(0, b""), 123456789);
+        assert_eq!(calculate_path_hash(0, b"a"), 123456789u32.rotate_right(5) ^ b'a' as u32);
+    }
+
+    #[test]
+    fn find_entries() {
+        let mut romfs = open_test_romfs();
+        assert_eq!(read_file(&mut romfs, "/a.txt"), b"Hello from the RomFs!\n");
+        // Found further down its hash bucket's chain
+        assert_eq!(read_file(&mut romfs, "/b.txt"), b"bbbb");
+        assert_eq!(read_file(&mut romfs, "/data/nested/deep.txt"), b"deep");
+        assert_eq!(read_file(&mut romfs, "//data/./nested/../../a.txt"), b"Hello from the RomFs!\n");
+
+        assert_eq!(romfs.get_entry_type("/data/nested").unwrap(), DirectoryEntryType::Directory);
+        assert_eq!(romfs.get_entry_type("/docs").unwrap(), DirectoryEntryType::Directory);
+        assert_eq!(romfs.get_entry_type("/").unwrap(), DirectoryEntryType::Directory);
+        assert_eq!(romfs.get_entry_type("/empty.bin").unwrap(), DirectoryEntryType::File);
+        assert_eq!(romfs.find_directory("/").unwrap(), ROOT_DIRECTORY_OFFSET);
+    }
+
+    #[test]
+    fn missing_entries() {
+        let romfs = open_test_romfs();
+        assert!(results::fs::ResultPathNotFound::matches(romfs.find_file("/c.txt").err().unwrap()));
+        assert!(results::fs::ResultPathNotFound::matches(romfs.find_file("/data/a.txt").err().unwrap()));
+        assert!(results::fs::ResultPathNotFound::matches(romfs.find_file("/a.txt/x").err().unwrap()));
+        // Files aren't directories and vice versa
+        assert!(results::fs::ResultPathNotFound::matches(romfs.find_directory("/a.txt").err().unwrap()));
+        assert!(results::fs::ResultPathNotFound::matches(romfs.find_file("/data/nested").err().unwrap()));
+        assert!(results::fs::ResultPathNotFound::matches(romfs.get_entry_type("/nested").err().unwrap()));
+    }
+
+    #[test]
+    fn iterate_directories() {
+        let romfs = open_test_romfs();
+        assert_eq!(list_directory(&romfs, "/"), vec![
+            (String::from("data"), DirectoryEntryType::Directory, 0),
+            (String::from("docs"), DirectoryEntryType::Directory, 0),
+            (String::from("a.txt"), DirectoryEntryType::File, 22),
+            (String::from("b.txt"), DirectoryEntryType::File, 4),
+            (String::from("empty.bin"), DirectoryEntryType::File, 0)
+        ]);
+        assert_eq!(list_directory(&romfs, "/data"), vec![
+            (String::from("nested"), DirectoryEntryType::Directory, 0),
+            (String::from("numbers.bin"), DirectoryEntryType::File, 300)
+        ]);
+        assert!(list_directory(&romfs, "/docs").is_empty());
+    }
+
+    #[test]
+    fn read_past_end_of_file() {
+        let mut romfs = open_test_romfs();
+        let mut buf: [u8; 0x100] = [0; 0x100];
+        {
+            let mut file = romfs.open_file("/data/numbers.bin").unwrap();
+            assert_eq!(file.get_size(), 300);
+            assert_eq!(file.read(&mut buf).unwrap(), 0x100);
+            assert_eq!(buf[0xFF], 0xFF);
+            assert_eq!(file.read(&mut buf).unwrap(), 300 - 0x100);
+            assert_eq!(buf[..300 - 0x100], (0..(300 - 0x100) as u8).collect::<Vec<u8>>()[..]);
+            assert_eq!(file.read(&mut buf).unwrap(), 0);
+
+            // Seeking past the end is fine, reading there just doesn't read anything
+            assert_eq!(file.seek(SeekFrom::End(10)).unwrap(), 310);
+            assert_eq!(file.read(&mut buf).unwrap(), 0);
+            assert_eq!(file.seek(SeekFrom::Current(-20)).unwrap(), 290);
+            assert_eq!(file.read(&mut buf).unwrap(), 10);
+            assert!(results::fs::ResultInvalidOffset::matches(file.seek(SeekFrom::Current(-1000)).err().unwrap()));
+
+            file.rewind().unwrap();
+            let mut small_buf: [u8; 400] = [0; 400];
+            assert!(results::fs::ResultOutOfRange::matches(file.read_exact(&mut small_buf).err().unwrap()));
+        }
+
+        let mut file = romfs.open_file("/empty.bin").unwrap();
+        assert_eq!(file.read(&mut buf).unwrap(), 0);
+    }
+
+    #[test]
+    fn image_at_offset() {
+        let mut image = vec![0xFFu8; 0x123];
+        image.extend_from_slice(TEST_ROMFS);
+        let mut romfs = RomFs::new_at(MemoryStorage::new(&image), 0x123).unwrap();
+
+        let mut data: Vec<u8> = Vec::new();
+        romfs.open_file("/data/nested/deep.txt").unwrap().read_to_end(&mut data).unwrap();
+        assert_eq!(data, b"deep");
+    }
+
+    #[test]
+    fn invalid_images() {
+        // Truncated
+        let rc = RomFs::new(MemoryStorage::new(&TEST_ROMFS[..TEST_ROMFS.len() - 1])).err().unwrap();
+        assert!(results::lib::fs::ResultInvalidRomFsHeader::matches(rc));
+        let rc = RomFs::new(MemoryStorage::new(&TEST_ROMFS[..0x10])).err().unwrap();
+        assert!(results::fs::ResultOutOfRange::matches(rc));
+
+        let mut image = TEST_ROMFS.to_vec();
+        image[0] = 0x40;
+        let rc = RomFs::new(MemoryStorage::new(&image)).err().unwrap();
+        assert!(results::lib::fs::ResultInvalidRomFsHeader::matches(rc));
+
+        // Tables past the end of the storage, with and without overflowing
+        for table_size in [0x1000_0000, u64::MAX] {
+            let mut image = TEST_ROMFS.to_vec();
+            patch_u64(&mut image, 0x20, table_size);
+            let rc = RomFs::new(MemoryStorage::new(&image)).err().unwrap();
+            assert!(results::lib::fs::ResultInvalidRomFsHeader::matches(rc));
+        }
+    }
+
+    fn patch_u32(image: &mut [u8], offset: usize, value: u32) {
+        image[offset..offset + 4].copy_from_slice(&value.to_le_bytes());
+    }
+
+    fn patch_u64(image: &mut [u8], offset: usize, value: u64) {
+        image[offset..offset + 8].copy_from_slice(&value.to_le_bytes());
+    }
+
+    #[test]
+    fn looping_chains() {
+        let header = open_test_romfs().get_header();
+        let dir_table_offset = header.dir_table_offset as usize;
+
+        // Every bucket points to the root, whose hash chain points back to itself
+        let mut image = TEST_ROMFS.to_vec();
+        for i in 0..(header.dir_hash_table_size as usize / 4) {
+            patch_u32(&mut image, header.dir_hash_table_offset as usize + i * 4, ROOT_DIRECTORY_OFFSET);
+        }
+        patch_u32(&mut image, dir_table_offset + ROOT_DIRECTORY_OFFSET as usize + 0x10, ROOT_DIRECTORY_OFFSET);
+        let romfs = RomFs::new(MemoryStorage::new(&image)).unwrap();
+        let rc = romfs.find_directory("/missing").err().unwrap();
+        assert!(results::lib::fs::ResultInvalidRomFsEntry::matches(rc));
+
+        // The root's first child directory is its own sibling
+        let mut image = TEST_ROMFS.to_vec();
+        let child_dir = open_test_romfs().get_directory_entry(ROOT_DIRECTORY_OFFSET).unwrap().child_dir;
+        patch_u32(&mut image, dir_table_offset + child_dir as usize + 0x4, child_dir);
+        let romfs = RomFs::new(MemoryStorage::new(&image)).unwrap();
+        let entries: Vec<Result<Entry>> = romfs.open_directory("/").unwrap().collect();
+        assert!(results::lib::fs::ResultInvalidRomFsEntry::matches(*entries.last().unwrap().as_ref().err().unwrap()));
+    }
+
+    #[test]
+    fn overflowing_data_offset() {
+        let mut image = TEST_ROMFS.to_vec();
+        patch_u64(&mut image, 0x48, u64::MAX);
+        let mut romfs = RomFs::new(MemoryStorage::new(&image)).unwrap();
+
+        let mut buf: [u8; 0x10] = [0; 0x10];
+        let rc = romfs.open_file("/b.txt").unwrap().read(&mut buf).err().unwrap();
+        assert!(results::lib::fs::ResultInvalidRomFsEntry::matches(rc));
+    }
+}
\ No newline at end of file
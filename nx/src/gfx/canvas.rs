@@ -0,0 +1,529 @@
+use crate::result::*;
+use crate::results;
+use crate::gpu;
+use alloc::vec::Vec;
+use core::mem;
+use core::slice;
+
+// Laid out like A8B8G8R8 pixels in memory, so raw images in that format can be used as they are
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+#[repr(C)]
+pub struct RGBA8 {
+    pub r: u8,
+    pub g: u8,
+    pub b: u8,
+    pub a: u8,
+}
+
+impl RGBA8 {
+    pub const fn new_rgba(r: u8, g: u8, b: u8, a: u8) -> Self {
+        Self { r: r, g: g, b: b, a: a }
+    }
+
+    pub const fn new_rgb(r: u8, g: u8, b: u8) -> Self {
+        Self { r: r, g: g, b: b, a: 0xFF }
+    }
+
+    const fn decode(raw: u32) -> (u8, u8, u8, u8) {
+        let a = (raw & 0xFF) as u8;
+        let b = ((raw >> 8) & 0xFF) as u8;
+        let c = ((raw >> 16) & 0xFF) as u8;
+        let d = ((raw >> 24) & 0xFF) as u8;
+        (a, b, c, d)
+    }
+
+    pub const fn from_rgba(raw: u32) -> Self {
+        let (a, b, g, r) = Self::decode(raw);
+        Self::new_rgba(r, g, b, a)
+    }
+
+    pub const fn from_abgr(raw: u32) -> Self {
+        let (r, g, b, a) = Self::decode(raw);
+        Self::new_rgba(r, g, b, a)
+    }
+
+    const fn encode(a: u8, b: u8, c: u8, d: u8) -> u32 {
+        (a as u32 & 0xFF) | ((b as u32 & 0xFF) << 8) | ((c as u32 & 0xFF) << 16) | ((d as u32 & 0xFF) << 24)
+    }
+
+    pub const fn encode_rgba(&self) -> u32 {
+        Self::encode(self.a, self.b, self.g, self.r)
+    }
+
+    pub const fn encode_abgr(&self) -> u32 {
+        Self::encode(self.r, self.g, self.b, self.a)
+    }
+
+    const fn blend_color_impl(src: u32, dst: u32, alpha: u32) -> u8 {
+        let one_minus_a = 0xFF - alpha;
+        ((dst * alpha + src * one_minus_a) / 0xFF) as u8
+    }
+
+    // Draws this color over the other one, the result being opaque
+    pub const fn blend_with(&self, other: Self) -> Self {
+        let r = Self::blend_color_impl(other.r as u32, self.r as u32, self.a as u32);
+        let g = Self::blend_color_impl(other.g as u32, self.g as u32, self.a as u32);
+        let b = Self::blend_color_impl(other.b as u32, self.b as u32, self.a as u32);
+        Self::new_rgb(r, g, b)
+    }
+}
+
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub struct Rect {
+    pub x: i32,
+    pub y: i32,
+    pub width: i32,
+    pub height: i32
+}
+
+impl Rect {
+    pub const fn new(x: i32, y: i32, width: i32, height: i32) -> Self {
+        Self { x: x, y: y, width: width, height: height }
+    }
+
+    pub const fn contains(&self, x: i32, y: i32) -> bool {
+        (x >= self.x) && (x < (self.x + self.width)) && (y >= self.y) && (y < (self.y + self.height))
+    }
+
+    pub fn intersect(&self, other: Rect) -> Rect {
+        let x0 = core::cmp::max(self.x, other.x);
+        let y0 = core::cmp::max(self.y, other.y);
+        let x1 = core::cmp::min(self.x + self.width, other.x + other.width);
+        let y1 = core::cmp::min(self.y + self.height, other.y + other.height);
+        Rect::new(x0, y0, core::cmp::max(x1 - x0, 0), core::cmp::max(y1 - y0, 0))
+    }
+}
+
+const BYTES_PER_PIXEL: u32 = mem::size_of::<u32>() as u32;
+
+// Software renderer over an in-memory (pitch-linear) A8B8G8R8 buffer, later rendered to the actual framebuffer
+pub struct Canvas {
+    buf: Vec<u32>,
+    width: u32,
+    height: u32,
+    stride: u32,
+    clip: Rect
+}
+
+impl Canvas {
+    pub fn new(width: u32, height: u32) -> Self {
+        let stride = gpu::swizzle::get_pitch_stride(width, BYTES_PER_PIXEL);
+        let buf = vec![0; (stride * height / BYTES_PER_PIXEL) as usize];
+        Self { buf: buf, width: width, height: height, stride: stride, clip: Rect::new(0, 0, width as i32, height as i32) }
+    }
+
+    pub fn get_width(&self) -> u32 {
+        self.width
+    }
+
+    pub fn get_height(&self) -> u32 {
+        self.height
+    }
+
+    // In bytes
+    pub fn get_stride(&self) -> u32 {
+        self.stride
+    }
+
+    pub fn get_buffer(&self) -> &[u32] {
+        &self.buf
+    }
+
+    pub fn get_buffer_mut(&mut self) -> &mut [u32] {
+        &mut self.buf
+    }
+
+    fn get_bounds(&self) -> Rect {
+        Rect::new(0, 0, self.width as i32, self.height as i32)
+    }
+
+    pub fn get_clip(&self) -> Rect {
+        self.clip
+    }
+
+    // Nothing is drawn outside the clip rectangle (besides clearing)
+    pub fn set_clip(&mut self, clip: Rect) {
+        self.clip = clip.intersect(self.get_bounds());
+    }
+
+    pub fn reset_clip(&mut self) {
+        self.clip = self.get_bounds();
+    }
+
+    fn get_pixel_index(&self, x: i32, y: i32) -> usize {
+        (y as u32 * (self.stride / BYTES_PER_PIXEL) + x as u32) as usize
+    }
+
+    pub fn get_pixel(&self, x: i32, y: i32) -> Option<RGBA8> {
+        match self.get_bounds().contains(x, y) {
+            true => Some(RGBA8::from_abgr(self.buf[self.get_pixel_index(x, y)])),
+            false => None
+        }
+    }
+
+    fn blend_pixel_impl(&mut self, index: usize, color: RGBA8) {
+        self.buf[index] = match color.a {
+            0xFF => color.encode_abgr(),
+            _ => color.blend_with(RGBA8::from_abgr(self.buf[index])).encode_abgr()
+        };
+    }
+
+    pub fn clear(&mut self, color: RGBA8) {
+        let raw_color = color.encode_abgr();
+        for pixel in self.buf.iter_mut() {
+            *pixel = raw_color;
+        }
+    }
+
+    pub fn draw_pixel(&mut self, x: i32, y: i32, color: RGBA8) {
+        if self.clip.contains(x, y) {
+            let index = self.get_pixel_index(x, y);
+            self.blend_pixel_impl(index, color);
+        }
+    }
+
+    pub fn fill_rect(&mut self, x: i32, y: i32, width: i32, height: i32, color: RGBA8) {
+        let rect = Rect::new(x, y, width, height).intersect(self.clip);
+        for y in rect.y..(rect.y + rect.height) {
+            let row_index = self.get_pixel_index(rect.x, y);
+            for index in row_index..(row_index + rect.width as usize) {
+                self.blend_pixel_impl(index, color);
+            }
+        }
+    }
+
+    pub fn draw_rect(&mut self, x: i32, y: i32, width: i32, height: i32, color: RGBA8) {
+        if (width <= 0) || (height <= 0) {
+            return;
+        }
+
+        self.fill_rect(x, y, width, 1, color);
+        if height > 1 {
+            self.fill_rect(x, y + height - 1, width, 1, color);
+        }
+        if height > 2 {
+            self.fill_rect(x, y + 1, 1, height - 2, color);
+            if width > 1 {
+                self.fill_rect(x + width - 1, y + 1, 1, height - 2, color);
+            }
+        }
+    }
+
+    // Bresenham's algorithm, both ends included
+    pub fn draw_line(&mut self, x0: i32, y0: i32, x1: i32, y1: i32, color: RGBA8) {
+        let dx = (x1 - x0).abs();
+        let dy = -(y1 - y0).abs();
+        let step_x = if x0 < x1 { 1 } else { -1 };
+        let step_y = if y0 < y1 { 1 } else { -1 };
+        let mut error = dx + dy;
+        let mut x = x0;
+        let mut y = y0;
+        loop {
+            self.draw_pixel(x, y, color);
+            if (x == x1) && (y == y1) {
+                break;
+            }
+            let error_2 = 2 * error;
+            if error_2 >= dy {
+                error += dy;
+                x += step_x;
+            }
+            if error_2 <= dx {
+                error += dx;
+                y += step_y;
+            }
+        }
+    }
+
+    // Midpoint algorithm, calling the given function for each octant step (every point is mirrored by it)
+    fn for_each_circle_step(radius: i32, mut f: impl FnMut(i32, i32)) {
+        let mut x = radius;
+        let mut y = 0;
+        let mut error = 1 - radius;
+        while x >= y {
+            f(x, y);
+            y += 1;
+            if error < 0 {
+                error += 2 * y + 1;
+            }
+            else {
+                x -= 1;
+                error += 2 * (y - x) + 1;
+            }
+        }
+    }
+
+    pub fn draw_circle(&mut self, center_x: i32, center_y: i32, radius: i32, color: RGBA8) {
+        if radius < 0 {
+            return;
+        }
+
+        if radius == 0 {
+            self.draw_pixel(center_x, center_y, color);
+            return;
+        }
+
+        Self::for_each_circle_step(radius, |x, y| {
+            // Octants meet at the axes and diagonals, whose points mustn't be blended twice
+            if y == 0 {
+                for &(px, py) in &[(x, 0), (-x, 0), (0, x), (0, -x)] {
+                    self.draw_pixel(center_x + px, center_y + py, color);
+                }
+            }
+            else if x == y {
+                for &(px, py) in &[(x, y), (-x, y), (x, -y), (-x, -y)] {
+                    self.draw_pixel(center_x + px, center_y + py, color);
+                }
+            }
+            else {
+                for &(px, py) in &[(x, y), (y, x), (-y, x), (-x, y), (-x, -y), (-y, -x), (y, -x), (x, -y)] {
+                    self.draw_pixel(center_x + px, center_y + py, color);
+                }
+            }
+        });
+    }
+
+    pub fn fill_circle(&mut self, center_x: i32, center_y: i32, radius: i32, color: RGBA8) {
+        if radius < 0 {
+            return;
+        }
+
+        // Half-width of the span at each row offset, so that every row is filled once
+        let mut spans = vec![0i32; radius as usize + 1];
+        Self::for_each_circle_step(radius, |x, y| {
+            spans[y as usize] = core::cmp::max(spans[y as usize], x);
+            spans[x as usize] = core::cmp::max(spans[x as usize], y);
+        });
+        for (y, half_width) in spans.iter().enumerate() {
+            let y = y as i32;
+            self.fill_rect(center_x - half_width, center_y + y, 2 * half_width + 1, 1, color);
+            if y != 0 {
+                self.fill_rect(center_x - half_width, center_y - y, 2 * half_width + 1, 1, color);
+            }
+        }
+    }
+
+    // The image is made of width * height pixels, row by row
+    pub fn blit(&mut self, x: i32, y: i32, image: &[RGBA8], width: i32, height: i32) -> Result<()> {
+        result_return_unless!((width >= 0) && (height >= 0) && (image.len() >= (width * height) as usize), results::lib::gpu::ResultInvalidBufferSize);
+
+        let rect = Rect::new(x, y, width, height).intersect(self.clip);
+        for dst_y in rect.y..(rect.y + rect.height) {
+            let src_row = ((dst_y - y) * width) as usize;
+            for dst_x in rect.x..(rect.x + rect.width) {
+                let index = self.get_pixel_index(dst_x, dst_y);
+                self.blend_pixel_impl(index, image[src_row + (dst_x - x) as usize]);
+            }
+        }
+        Ok(())
+    }
+
+    fn get_buffer_bytes(&self) -> &[u8] {
+        unsafe { slice::from_raw_parts(self.buf.as_ptr() as *const u8, self.buf.len() * mem::size_of::<u32>()) }
+    }
+
+    // Renders the canvas to a surface buffer with the given layout, which must be in the same (A8B8G8R8) format since pixels are copied as they are
+    pub fn render_to(&self, buf: &mut [u8], color_fmt: gpu::ColorFormat, layout: gpu::Layout) -> Result<()> {
+        result_return_unless!(color_fmt == gpu::ColorFormat::A8B8G8R8, results::lib::gpu::ResultUnsupportedColorFormat);
+        gpu::swizzle::convert_linear_to_layout(buf, self.get_buffer_bytes(), self.width, self.height, self.stride, color_fmt, layout)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const BLACK: RGBA8 = RGBA8::new_rgb(0, 0, 0);
+    const WHITE: RGBA8 = RGBA8::new_rgb(0xFF, 0xFF, 0xFF);
+
+    fn new_canvas(width: u32, height: u32) -> Canvas {
+        let mut canvas = Canvas::new(width, height);
+        canvas.clear(BLACK);
+        canvas
+    }
+
+    // Every pixel which isn't black anymore, row by row
+    fn get_drawn_pixels(canvas: &Canvas) -> Vec<(i32, i32)> {
+        let mut pixels = Vec::new();
+        for y in 0..canvas.get_height() as i32 {
+            for x in 0..canvas.get_width() as i32 {
+                if canvas.get_pixel(x, y) != Some(BLACK) {
+                    pixels.push((x, y));
+                }
+            }
+        }
+        pixels
+    }
+
+    fn sorted(mut pixels: Vec<(i32, i32)>) -> Vec<(i32, i32)> {
+        pixels.sort_by_key(|&(x, y)| (y, x));
+        pixels
+    }
+
+    #[test]
+    fn colors() {
+        let color = RGBA8::new_rgba(0x11, 0x22, 0x33, 0x44);
+        assert_eq!(color.encode_abgr(), 0x44332211);
+        assert_eq!(color.encode_rgba(), 0x11223344);
+        assert_eq!(RGBA8::from_abgr(0x44332211), color);
+        assert_eq!(RGBA8::from_rgba(0x11223344), color);
+
+        assert_eq!(RGBA8::new_rgba(0xFF, 0, 0, 0x80).blend_with(RGBA8::new_rgb(0, 0, 0xFF)), RGBA8::new_rgb(0x80, 0, 0x7F));
+        assert_eq!(RGBA8::new_rgba(0xFF, 0, 0, 0).blend_with(WHITE), WHITE);
+    }
+
+    #[test]
+    fn rects() {
+        let rect = Rect::new(2, 3, 4, 5);
+        assert!(rect.contains(2, 3));
+        assert!(rect.contains(5, 7));
+        assert!(!rect.contains(6, 7));
+        assert!(!rect.contains(5, 8));
+        assert!(!rect.contains(1, 3));
+
+        assert_eq!(rect.intersect(Rect::new(0, 0, 4, 4)), Rect::new(2, 3, 2, 1));
+        assert_eq!(rect.intersect(Rect::new(10, 10, 4, 4)), Rect::new(10, 10, 0, 0));
+    }
+
+    #[test]
+    fn clipping() {
+        let mut canvas = new_canvas(8, 6);
+        // Rows are padded, but only the actual pixels are reachable
+        assert!(canvas.get_stride() >= 8 * 4);
+        assert_eq!(canvas.get_pixel(8, 0), None);
+        assert_eq!(canvas.get_pixel(0, -1), None);
+
+        canvas.set_clip(Rect::new(-2, 4, 20, 20));
+        assert_eq!(canvas.get_clip(), Rect::new(0, 4, 8, 2));
+
+        canvas.fill_rect(-1, 3, 3, 2, WHITE);
+        canvas.draw_pixel(7, 5, WHITE);
+        canvas.draw_pixel(7, 3, WHITE);
+        canvas.draw_pixel(8, 5, WHITE);
+        canvas.draw_pixel(-1, 5, WHITE);
+        assert_eq!(get_drawn_pixels(&canvas), [(0, 4), (1, 4), (7, 5)]);
+
+        canvas.reset_clip();
+        assert_eq!(canvas.get_clip(), Rect::new(0, 0, 8, 6));
+        canvas.fill_rect(6, -3, 10, 4, WHITE);
+        assert_eq!(get_drawn_pixels(&canvas), [(6, 0), (7, 0), (0, 4), (1, 4), (7, 5)]);
+
+        // Clearing ignores the clip rectangle
+        canvas.set_clip(Rect::new(0, 0, 1, 1));
+        canvas.clear(WHITE);
+        assert_eq!(get_drawn_pixels(&canvas).len(), 8 * 6);
+    }
+
+    #[test]
+    fn rect_outlines() {
+        let mut canvas = new_canvas(8, 6);
+        canvas.draw_rect(1, 1, 4, 3, WHITE);
+        assert_eq!(get_drawn_pixels(&canvas), [(1, 1), (2, 1), (3, 1), (4, 1), (1, 2), (4, 2), (1, 3), (2, 3), (3, 3), (4, 3)]);
+
+        let mut canvas = new_canvas(8, 6);
+        canvas.draw_rect(2, 2, 1, 1, WHITE);
+        canvas.draw_rect(5, 0, 0, 3, WHITE);
+        assert_eq!(get_drawn_pixels(&canvas), [(2, 2)]);
+    }
+
+    #[test]
+    fn lines() {
+        let mut canvas = new_canvas(8, 6);
+        canvas.draw_line(0, 0, 5, 2, WHITE);
+        assert_eq!(get_drawn_pixels(&canvas), [(0, 0), (1, 0), (2, 1), (3, 1), (4, 2), (5, 2)]);
+
+        // Steep lines step once per row, both ends included whatever the direction is
+        let mut canvas = new_canvas(8, 6);
+        canvas.draw_line(3, 5, 1, 0, WHITE);
+        let pixels = get_drawn_pixels(&canvas);
+        assert_eq!(pixels.len(), 6);
+        assert!(pixels.contains(&(3, 5)) && pixels.contains(&(1, 0)));
+        for (i, &(_, y)) in pixels.iter().enumerate() {
+            assert_eq!(y, i as i32);
+        }
+
+        let mut canvas = new_canvas(8, 6);
+        canvas.draw_line(4, 4, 4, 4, WHITE);
+        assert_eq!(get_drawn_pixels(&canvas), [(4, 4)]);
+
+        // Only the part inside the canvas is drawn
+        let mut canvas = new_canvas(8, 6);
+        canvas.draw_line(-3, -3, 2, 2, WHITE);
+        assert_eq!(get_drawn_pixels(&canvas), [(0, 0), (1, 1), (2, 2)]);
+    }
+
+    #[test]
+    fn circles() {
+        let mut canvas = new_canvas(8, 8);
+        canvas.draw_circle(3, 3, 2, WHITE);
+        let expected: Vec<(i32, i32)> = [(2, 0), (-2, 0), (0, 2), (0, -2), (2, 1), (1, 2), (-1, 2), (-2, 1), (-2, -1), (-1, -2), (1, -2), (2, -1)].iter().map(|&(x, y)| (3 + x, 3 + y)).collect();
+        assert_eq!(get_drawn_pixels(&canvas), sorted(expected));
+
+        let mut canvas = new_canvas(8, 8);
+        canvas.fill_circle(3, 3, 2, WHITE);
+        let mut expected = Vec::new();
+        for &(y, half_width) in &[(-2, 1), (-1, 2), (0, 2), (1, 2), (2, 1)] {
+            for x in -half_width..=half_width {
+                expected.push((3 + x, 3 + y));
+            }
+        }
+        assert_eq!(get_drawn_pixels(&canvas), expected);
+
+        let mut canvas = new_canvas(8, 8);
+        canvas.draw_circle(3, 3, 0, WHITE);
+        canvas.draw_circle(5, 5, -1, WHITE);
+        canvas.fill_circle(5, 5, -1, WHITE);
+        assert_eq!(get_drawn_pixels(&canvas), [(3, 3)]);
+    }
+
+    #[test]
+    fn circles_blend_each_pixel_once() {
+        let half_white = RGBA8::new_rgba(0xFF, 0xFF, 0xFF, 0x80);
+        let blended = half_white.blend_with(BLACK);
+        for radius in 1..6 {
+            let mut outline_canvas = new_canvas(16, 16);
+            outline_canvas.draw_circle(7, 7, radius, half_white);
+            let mut filled_canvas = new_canvas(16, 16);
+            filled_canvas.fill_circle(7, 7, radius, half_white);
+            for canvas in &[outline_canvas, filled_canvas] {
+                for (x, y) in get_drawn_pixels(canvas) {
+                    assert_eq!(canvas.get_pixel(x, y), Some(blended), "radius {} at ({}, {})", radius, x, y);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn blits() {
+        let red = RGBA8::new_rgb(0xFF, 0, 0);
+        let transparent = RGBA8::new_rgba(0, 0xFF, 0, 0);
+        let image = [red, transparent, red, red, red, transparent];
+
+        let mut canvas = new_canvas(8, 6);
+        canvas.blit(6, 4, &image, 3, 2).unwrap();
+        assert_eq!(get_drawn_pixels(&canvas), [(6, 4), (6, 5), (7, 5)]);
+        assert_eq!(canvas.get_pixel(6, 4), Some(red));
+
+        let mut canvas = new_canvas(8, 6);
+        canvas.set_clip(Rect::new(0, 0, 1, 6));
+        canvas.blit(-2, 0, &image, 3, 2).unwrap();
+        assert_eq!(get_drawn_pixels(&canvas), [(0, 0)]);
+
+        assert!(results::lib::gpu::ResultInvalidBufferSize::matches(canvas.blit(0, 0, &image, 4, 2).unwrap_err()));
+        assert!(results::lib::gpu::ResultInvalidBufferSize::matches(canvas.blit(0, 0, &image, -1, 2).unwrap_err()));
+    }
+
+    #[test]
+    fn rendering() {
+        let mut canvas = new_canvas(8, 6);
+        canvas.draw_pixel(1, 2, WHITE);
+
+        let stride = canvas.get_stride() as usize;
+        let mut buf = vec![0u8; stride * 6];
+        canvas.render_to(&mut buf, gpu::ColorFormat::A8B8G8R8, gpu::Layout::Pitch).unwrap();
+        assert_eq!(buf[2 * stride + 4..2 * stride + 8], [0xFF; 4]);
+        assert_eq!(buf[..4], BLACK.encode_abgr().to_le_bytes());
+
+        assert!(results::lib::gpu::ResultUnsupportedColorFormat::matches(canvas.render_to(&mut buf, gpu::ColorFormat::R5G6B5, gpu::Layout::Pitch).unwrap_err()));
+    }
+}
\ No newline at end of file
@@ -0,0 +1,62 @@
+use crate::result::*;
+use crate::results;
+use crate::gpu;
+use crate::gpu::surface;
+use crate::service::nv;
+use core::mem;
+use core::slice;
+
+pub mod canvas;
+pub use canvas::*;
+
+// Canvas rendered to a surface each frame, between start() and end()
+pub struct SurfaceCanvas<NS: nv::INvDrvService + 'static> {
+    canvas: Canvas,
+    surface: surface::Surface<NS>,
+    buf: *mut u8,
+    buf_size: usize,
+    slot: i32,
+    fences: gpu::MultiFence,
+    started: bool
+}
+
+impl<NS: nv::INvDrvService + 'static> SurfaceCanvas<NS> {
+    pub fn new(surface: surface::Surface<NS>) -> Result<Self> {
+        result_return_unless!(surface.get_color_format() == gpu::ColorFormat::A8B8G8R8, results::lib::gpu::ResultUnsupportedColorFormat);
+        result_return_if!(surface.get_layout() == gpu::Layout::Tiled, results::lib::gpu::ResultUnsupportedLayout);
+
+        let canvas = Canvas::new(surface.get_width(), surface.get_height());
+        Ok(Self { canvas: canvas, surface: surface, buf: core::ptr::null_mut(), buf_size: 0, slot: 0, fences: unsafe { mem::zeroed() }, started: false })
+    }
+
+    pub fn start(&mut self) -> Result<()> {
+        result_return_if!(self.started, results::lib::gpu::ResultCanvasAlreadyStarted);
+
+        let (buf, buf_size, slot, _has_fences, fences) = self.surface.dequeue_buffer(true)?;
+        self.buf = buf;
+        self.buf_size = buf_size;
+        self.slot = slot;
+        self.fences = fences;
+        self.started = true;
+        self.surface.wait_fences(fences, -1)
+    }
+
+    pub fn end(&mut self) -> Result<()> {
+        // Without a dequeued buffer there's nothing to render to
+        result_return_unless!(self.started, results::lib::gpu::ResultCanvasNotStarted);
+
+        let buf = unsafe { slice::from_raw_parts_mut(self.buf, self.buf_size) };
+        self.canvas.render_to(buf, self.surface.get_color_format(), self.surface.get_layout())?;
+        self.surface.queue_buffer(self.slot, self.fences)?;
+        self.started = false;
+        self.surface.wait_vsync_event(-1)
+    }
+
+    pub fn get_canvas(&mut self) -> &mut Canvas {
+        &mut self.canvas
+    }
+
+    pub fn get_surface(&mut self) -> &mut surface::Surface<NS> {
+        &mut self.surface
+    }
+}
\ No newline at end of file
@@ -0,0 +1,463 @@
+use super::*;
+use crate::results;
+use alloc::vec::Vec;
+
+// Maxwell method streams are made of 32-bit words: a method header followed by its arguments (if any)
+// Methods are given here as byte offsets in the engine's register space, like in the hardware docs
+
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+#[repr(u32)]
+pub enum SecondaryOpcode {
+    IncreasingMethod = 1,
+    NonIncreasingMethod = 3,
+    ImmediateData = 4,
+    IncreaseOnceMethod = 5
+}
+
+// The usual subchannel assignment (the engine bound to each one is set with bind_subchannel)
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+#[repr(u32)]
+pub enum Subchannel {
+    ThreeD = 0,
+    Compute = 1,
+    InlineToMemory = 2,
+    TwoD = 3,
+    Copy = 4
+}
+
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+#[repr(u32)]
+pub enum EngineClass {
+    Fermi2D = 0x902D,
+    KeplerInlineToMemoryB = 0xA140,
+    MaxwellDmaCopyB = 0xB0B5,
+    Maxwell3DB = 0xB197,
+    MaxwellComputeB = 0xB1C0
+}
+
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+#[repr(u32)]
+pub enum SurfaceFormat {
+    A2B10G10R10Unorm = 0xD1,
+    A8B8G8R8Unorm = 0xD5,
+    A8R8G8B8Unorm = 0xCF,
+    R5G6B5Unorm = 0xE8,
+    A1R5G5B5Unorm = 0xE9,
+    R8Unorm = 0xF3
+}
+
+bit_enum! {
+    ClearBuffers (u32) {
+        Depth = 0x1,
+        Stencil = 0x2,
+        Red = 0x4,
+        Green = 0x8,
+        Blue = 0x10,
+        Alpha = 0x20,
+        Color = 0x3C
+    }
+}
+
+pub const MAX_METHOD_COUNT: u32 = 0x1FFF;
+pub const MAX_IMMEDIATE_VALUE: u32 = 0x1FFF;
+
+pub const fn make_method_header(opcode: SecondaryOpcode, subchannel: Subchannel, method: u32, count_or_value: u32) -> u32 {
+    ((opcode as u32) << 29) | ((count_or_value & 0x1FFF) << 16) | ((subchannel as u32) << 13) | ((method >> 2) & 0x1FFF)
+}
+
+const SET_OBJECT: u32 = 0x0;
+
+const TWOD_DST_FORMAT: u32 = 0x200;
+const TWOD_SRC_FORMAT: u32 = 0x230;
+const TWOD_CLIP_ENABLE: u32 = 0x290;
+const TWOD_OPERATION: u32 = 0x2AC;
+const TWOD_DRAW_SHAPE: u32 = 0x580;
+const TWOD_DRAW_POINT32_X_0: u32 = 0x600;
+const TWOD_BLIT_CONTROL: u32 = 0x888;
+const TWOD_BLIT_DST_X: u32 = 0x8B0;
+
+const TWOD_OPERATION_SRCCOPY: u32 = 3;
+const TWOD_DRAW_SHAPE_RECTANGLES: u32 = 4;
+
+const THREED_RT_0: u32 = 0x800;
+const THREED_RT_SIZE: u32 = 0x40;
+const THREED_CLEAR_COLOR: u32 = 0xD80;
+const THREED_CLEAR_DEPTH: u32 = 0xD90;
+const THREED_CLEAR_STENCIL: u32 = 0xDA0;
+const THREED_SCREEN_SCISSOR_HORIZONTAL: u32 = 0xFF4;
+const THREED_RT_CONTROL: u32 = 0x121C;
+const THREED_CLEAR_BUFFERS: u32 = 0x19D0;
+
+const THREED_RT_TILE_MODE_LINEAR: u32 = 0x1000;
+
+pub const MAX_RENDER_TARGETS: u32 = 8;
+
+// A GPU-mapped image, as seen by the 2D and 3D engines
+#[derive(Copy, Clone)]
+pub struct Image {
+    pub gpu_address: u64,
+    pub width: u32,
+    pub height: u32,
+    // Row size in bytes, only used by pitch images
+    pub pitch: u32,
+    pub format: SurfaceFormat,
+    pub layout: Layout,
+    pub block_height_log2: u32
+}
+
+impl Image {
+    pub const fn new(gpu_address: u64, width: u32, height: u32, pitch: u32, format: SurfaceFormat, layout: Layout) -> Self {
+        Self { gpu_address: gpu_address, width: width, height: height, pitch: pitch, format: format, layout: layout, block_height_log2: BLOCK_HEIGHT_LOG2 }
+    }
+}
+
+#[derive(Copy, Clone)]
+#[repr(C)]
+pub struct GpfifoEntry {
+    pub desc: u64
+}
+
+bit_enum! {
+    GpfifoEntryFlags (u64) {
+        None = 0,
+        NotMain = 0x20000000000,
+        NoPrefetch = 0x8000000000000000
+    }
+}
+
+// Entries point to the GPU address (40 bits, word-aligned) and size (in words) of a command list
+pub const GPFIFO_ENTRY_MAX_WORD_COUNT: u32 = 0x1FFFFF;
+
+impl GpfifoEntry {
+    pub const fn new(gpu_address: u64, word_count: u32, flags: GpfifoEntryFlags) -> Self {
+        Self { desc: (gpu_address & 0xFFFFFFFFFC) | (((word_count & GPFIFO_ENTRY_MAX_WORD_COUNT) as u64) << 42) | flags.get() }
+    }
+
+    pub const fn get_gpu_address(&self) -> u64 {
+        self.desc & 0xFFFFFFFFFC
+    }
+
+    pub const fn get_word_count(&self) -> u32 {
+        ((self.desc >> 42) as u32) & GPFIFO_ENTRY_MAX_WORD_COUNT
+    }
+
+    pub const fn get_flags(&self) -> GpfifoEntryFlags {
+        GpfifoEntryFlags::from(self.desc & (GpfifoEntryFlags::NotMain() | GpfifoEntryFlags::NoPrefetch()).get())
+    }
+}
+
+pub struct CommandList {
+    words: Vec<u32>
+}
+
+impl CommandList {
+    pub const fn new() -> Self {
+        Self { words: Vec::new() }
+    }
+
+    pub fn with_capacity(word_count: usize) -> Self {
+        Self { words: Vec::with_capacity(word_count) }
+    }
+
+    pub fn get_words(&self) -> &[u32] {
+        &self.words
+    }
+
+    pub fn get_word_count(&self) -> usize {
+        self.words.len()
+    }
+
+    pub fn get_size(&self) -> usize {
+        self.words.len() * core::mem::size_of::<u32>()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.words.is_empty()
+    }
+
+    pub fn clear(&mut self) {
+        self.words.clear();
+    }
+
+    // The entry for this list once its words are copied to the given GPU address
+    pub fn make_gpfifo_entry(&self, gpu_address: u64, flags: GpfifoEntryFlags) -> Result<GpfifoEntry> {
+        result_return_unless!(self.words.len() <= GPFIFO_ENTRY_MAX_WORD_COUNT as usize, results::lib::gpu::ResultInvalidBufferSize);
+        Ok(GpfifoEntry::new(gpu_address, self.words.len() as u32, flags))
+    }
+
+    pub fn push_raw(&mut self, word: u32) {
+        self.words.push(word);
+    }
+
+    fn push_method_args(&mut self, opcode: SecondaryOpcode, subchannel: Subchannel, method: u32, args: &[u32]) {
+        // Longer argument lists are split in several methods, continuing where the previous one ended
+        let mut cur_opcode = opcode;
+        let mut cur_method = method;
+        for chunk in args.chunks(MAX_METHOD_COUNT as usize) {
+            self.words.push(make_method_header(cur_opcode, subchannel, cur_method, chunk.len() as u32));
+            self.words.extend_from_slice(chunk);
+            match cur_opcode {
+                SecondaryOpcode::IncreasingMethod => cur_method += core::mem::size_of_val(chunk) as u32,
+                SecondaryOpcode::IncreaseOnceMethod => {
+                    cur_opcode = SecondaryOpcode::NonIncreasingMethod;
+                    cur_method += core::mem::size_of::<u32>() as u32;
+                },
+                _ => {}
+            };
+        }
+    }
+
+    // Each argument goes to the next method
+    pub fn push_increasing(&mut self, subchannel: Subchannel, method: u32, args: &[u32]) {
+        self.push_method_args(SecondaryOpcode::IncreasingMethod, subchannel, method, args);
+    }
+
+    // Every argument goes to the same method
+    pub fn push_non_increasing(&mut self, subchannel: Subchannel, method: u32, args: &[u32]) {
+        self.push_method_args(SecondaryOpcode::NonIncreasingMethod, subchannel, method, args);
+    }
+
+    // The first argument goes to the method, the rest of them go to the next one
+    pub fn push_increase_once(&mut self, subchannel: Subchannel, method: u32, args: &[u32]) {
+        self.push_method_args(SecondaryOpcode::IncreaseOnceMethod, subchannel, method, args);
+    }
+
+    // The value is encoded in the header itself, so it must fit in 13 bits
+    pub fn push_immediate(&mut self, subchannel: Subchannel, method: u32, value: u32) -> Result<()> {
+        result_return_unless!(value <= MAX_IMMEDIATE_VALUE, results::lib::gpu::ResultInvalidCommandArgument);
+        self.words.push(make_method_header(SecondaryOpcode::ImmediateData, subchannel, method, value));
+        Ok(())
+    }
+
+    // Sets a single method, as an immediate one if the value is small enough
+    pub fn push_inline(&mut self, subchannel: Subchannel, method: u32, value: u32) {
+        match value <= MAX_IMMEDIATE_VALUE {
+            true => self.words.push(make_method_header(SecondaryOpcode::ImmediateData, subchannel, method, value)),
+            false => self.push_increasing(subchannel, method, &[value])
+        };
+    }
+
+    pub fn bind_subchannel(&mut self, subchannel: Subchannel, class: EngineClass) {
+        self.push_increasing(subchannel, SET_OBJECT, &[class as u32]);
+    }
+
+    // 2D engine
+
+    fn set_2d_image(&mut self, base_method: u32, image: &Image) -> Result<()> {
+        // Format, linear, block dimensions, depth, layer, pitch, width, height, address (high, low)
+        let (linear, block_dimensions, pitch) = match image.layout {
+            Layout::Pitch => (1, 0, image.pitch),
+            Layout::BlockLinear => (0, image.block_height_log2 << 4, 0),
+            Layout::Tiled => return Err(results::lib::gpu::ResultUnsupportedLayout::make())
+        };
+        self.push_increasing(Subchannel::TwoD, base_method, &[image.format as u32, linear, block_dimensions, 1, 0, pitch, image.width, image.height, (image.gpu_address >> 32) as u32, image.gpu_address as u32]);
+        Ok(())
+    }
+
+    pub fn init_2d(&mut self) {
+        self.bind_subchannel(Subchannel::TwoD, EngineClass::Fermi2D);
+        self.push_inline(Subchannel::TwoD, TWOD_CLIP_ENABLE, 0);
+        self.push_inline(Subchannel::TwoD, TWOD_OPERATION, TWOD_OPERATION_SRCCOPY);
+    }
+
+    pub fn set_2d_destination(&mut self, image: &Image) -> Result<()> {
+        self.set_2d_image(TWOD_DST_FORMAT, image)
+    }
+
+    pub fn set_2d_source(&mut self, image: &Image) -> Result<()> {
+        self.set_2d_image(TWOD_SRC_FORMAT, image)
+    }
+
+    // Copies a rectangle of the source image to the destination one, with no scaling
+    pub fn copy_2d(&mut self, dst: &Image, src: &Image, dst_x: u32, dst_y: u32, src_x: u32, src_y: u32, width: u32, height: u32) -> Result<()> {
+        self.set_2d_destination(dst)?;
+        self.set_2d_source(src)?;
+        self.push_inline(Subchannel::TwoD, TWOD_BLIT_CONTROL, 0);
+        // Destination rectangle, source steps (fractional and integer parts, 1:1) and source position (fractional and integer parts)
+        // The last method (source Y) is the one triggering the blit
+        self.push_increasing(Subchannel::TwoD, TWOD_BLIT_DST_X, &[dst_x, dst_y, width, height, 0, 1, 0, 1, 0, src_x, 0, src_y]);
+        Ok(())
+    }
+
+    // The color is given in the specified format (which may differ from the image's one)
+    pub fn fill_2d(&mut self, dst: &Image, x: u32, y: u32, width: u32, height: u32, color: u32, color_format: SurfaceFormat) -> Result<()> {
+        self.set_2d_destination(dst)?;
+        self.push_increasing(Subchannel::TwoD, TWOD_DRAW_SHAPE, &[TWOD_DRAW_SHAPE_RECTANGLES, color_format as u32, color]);
+        self.push_increasing(Subchannel::TwoD, TWOD_DRAW_POINT32_X_0, &[x, y, x + width, y + height]);
+        Ok(())
+    }
+
+    // 3D engine
+
+    pub fn init_3d(&mut self) {
+        self.bind_subchannel(Subchannel::ThreeD, EngineClass::Maxwell3DB);
+    }
+
+    pub fn set_render_target(&mut self, index: u32, image: &Image) -> Result<()> {
+        result_return_unless!(index < MAX_RENDER_TARGETS, results::lib::gpu::ResultInvalidCommandArgument);
+        // Address (high, low), horizontal and vertical sizes, format, tile mode, array mode, layer stride and base layer
+        let (horizontal, tile_mode) = match image.layout {
+            Layout::Pitch => (image.pitch, THREED_RT_TILE_MODE_LINEAR),
+            Layout::BlockLinear => (image.width, image.block_height_log2 << 4),
+            Layout::Tiled => return Err(results::lib::gpu::ResultUnsupportedLayout::make())
+        };
+        self.push_increasing(Subchannel::ThreeD, THREED_RT_0 + index * THREED_RT_SIZE, &[(image.gpu_address >> 32) as u32, image.gpu_address as u32, horizontal, image.height, image.format as u32, tile_mode, 1, 0, 0]);
+        Ok(())
+    }
+
+    // Enables the first render targets, each one mapped to itself
+    pub fn set_render_target_count(&mut self, count: u32) -> Result<()> {
+        result_return_unless!(count <= MAX_RENDER_TARGETS, results::lib::gpu::ResultInvalidCommandArgument);
+        let mut control = count;
+        for i in 0..count {
+            control |= i << (4 + i * 3);
+        }
+        self.push_inline(Subchannel::ThreeD, THREED_RT_CONTROL, control);
+        Ok(())
+    }
+
+    pub fn set_screen_scissor(&mut self, x: u32, y: u32, width: u32, height: u32) {
+        self.push_increasing(Subchannel::ThreeD, THREED_SCREEN_SCISSOR_HORIZONTAL, &[x | (width << 16), y | (height << 16)]);
+    }
+
+    pub fn set_clear_color(&mut self, r: f32, g: f32, b: f32, a: f32) {
+        self.push_increasing(Subchannel::ThreeD, THREED_CLEAR_COLOR, &[r.to_bits(), g.to_bits(), b.to_bits(), a.to_bits()]);
+    }
+
+    pub fn set_clear_depth(&mut self, depth: f32) {
+        self.push_increasing(Subchannel::ThreeD, THREED_CLEAR_DEPTH, &[depth.to_bits()]);
+    }
+
+    pub fn set_clear_stencil(&mut self, stencil: u8) {
+        self.push_inline(Subchannel::ThreeD, THREED_CLEAR_STENCIL, stencil as u32);
+    }
+
+    // Clears the given layer of a render target (or the depth/stencil one) with the values set above
+    pub fn clear_buffers(&mut self, buffers: ClearBuffers, render_target: u32, layer: u32) {
+        self.push_inline(Subchannel::ThreeD, THREED_CLEAR_BUFFERS, buffers.get() | ((render_target & 0xF) << 6) | ((layer & 0x7FF) << 10));
+    }
+
+    pub fn clear_render_target(&mut self, render_target: u32, r: f32, g: f32, b: f32, a: f32) {
+        self.set_clear_color(r, g, b, a);
+        self.clear_buffers(ClearBuffers::Color(), render_target, 0);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn method_headers() {
+        assert_eq!(make_method_header(SecondaryOpcode::IncreasingMethod, Subchannel::ThreeD, 0x800, 9), 0x20090200);
+        assert_eq!(make_method_header(SecondaryOpcode::NonIncreasingMethod, Subchannel::Compute, 0x1234, 2), 0x6002248D);
+        assert_eq!(make_method_header(SecondaryOpcode::ImmediateData, Subchannel::Copy, 0x100, 0x1FFF), 0x9FFF8040);
+        assert_eq!(make_method_header(SecondaryOpcode::IncreaseOnceMethod, Subchannel::InlineToMemory, 0x1B0, 4), 0xA004406C);
+        // Out of range counts/values and methods don't spill into the other fields
+        assert_eq!(make_method_header(SecondaryOpcode::IncreasingMethod, Subchannel::ThreeD, 0x8000, 0x2000), 0x20000000);
+    }
+
+    #[test]
+    fn increasing_methods_are_split() {
+        let args: Vec<u32> = (0..MAX_METHOD_COUNT + 2).collect();
+        let mut list = CommandList::new();
+        list.push_increasing(Subchannel::ThreeD, 0, &args);
+
+        let words = list.get_words();
+        assert_eq!(words.len(), args.len() + 2);
+        assert_eq!(words[0], make_method_header(SecondaryOpcode::IncreasingMethod, Subchannel::ThreeD, 0, MAX_METHOD_COUNT));
+        assert_eq!(words[1..=MAX_METHOD_COUNT as usize], args[..MAX_METHOD_COUNT as usize]);
+        // The second method continues right after the last one set by the first
+        let second_header = MAX_METHOD_COUNT as usize + 1;
+        assert_eq!(words[second_header], 0x20021FFF);
+        assert_eq!(words[second_header + 1..], args[MAX_METHOD_COUNT as usize..]);
+
+        // No split when it fits exactly
+        let mut list = CommandList::new();
+        list.push_increasing(Subchannel::ThreeD, 0, &args[..MAX_METHOD_COUNT as usize]);
+        assert_eq!(list.get_word_count(), MAX_METHOD_COUNT as usize + 1);
+
+        let mut list = CommandList::new();
+        list.push_increasing(Subchannel::ThreeD, 0x800, &[]);
+        assert!(list.is_empty());
+    }
+
+    #[test]
+    fn non_increasing_methods_are_split() {
+        let args: Vec<u32> = (0..MAX_METHOD_COUNT + 1).collect();
+        let mut list = CommandList::new();
+        list.push_non_increasing(Subchannel::InlineToMemory, 0x1B4, &args);
+
+        let words = list.get_words();
+        assert_eq!(words.len(), args.len() + 2);
+        assert_eq!(words[0], 0x7FFF406D);
+        // Same method again
+        assert_eq!(words[MAX_METHOD_COUNT as usize + 1], 0x6001406D);
+        assert_eq!(words[MAX_METHOD_COUNT as usize + 2], MAX_METHOD_COUNT);
+    }
+
+    #[test]
+    fn increase_once_methods_are_split() {
+        let args: Vec<u32> = (0..2 * MAX_METHOD_COUNT + 1).collect();
+        let mut list = CommandList::new();
+        list.push_increase_once(Subchannel::ThreeD, 0x100, &args);
+
+        let words = list.get_words();
+        assert_eq!(words.len(), args.len() + 3);
+        assert_eq!(words[0], 0xBFFF0040);
+        // The continuation only targets the second method, so it's a non-increasing one from there on
+        let second_header = MAX_METHOD_COUNT as usize + 1;
+        assert_eq!(words[second_header], 0x7FFF0041);
+        assert_eq!(words[second_header + 1], MAX_METHOD_COUNT);
+        let third_header = 2 * (MAX_METHOD_COUNT as usize + 1);
+        assert_eq!(words[third_header], 0x60010041);
+        assert_eq!(words[third_header + 1], 2 * MAX_METHOD_COUNT);
+    }
+
+    #[test]
+    fn immediate_and_inline_methods() {
+        let mut list = CommandList::new();
+        list.push_immediate(Subchannel::TwoD, 0x290, 0).unwrap();
+        assert!(results::lib::gpu::ResultInvalidCommandArgument::matches(list.push_immediate(Subchannel::TwoD, 0x290, MAX_IMMEDIATE_VALUE + 1).unwrap_err()));
+        list.push_inline(Subchannel::TwoD, 0x2AC, 3);
+        list.push_inline(Subchannel::TwoD, 0x2AC, MAX_IMMEDIATE_VALUE + 1);
+        assert_eq!(list.get_words(), [0x800060A4, 0x800360AB, 0x200160AB, 0x2000]);
+        assert_eq!(list.get_size(), 4 * 4);
+    }
+
+    #[test]
+    fn engine_commands() {
+        let mut list = CommandList::new();
+        list.init_2d();
+        assert_eq!(list.get_words(), [0x20016000, 0x902D, 0x800060A4, 0x800360AB]);
+
+        list.clear();
+        list.set_render_target_count(3).unwrap();
+        list.clear_buffers(ClearBuffers::Color(), 2, 5);
+        assert_eq!(list.get_words(), [0x88830487, 0x94BC0674]);
+        assert!(results::lib::gpu::ResultInvalidCommandArgument::matches(list.set_render_target_count(MAX_RENDER_TARGETS + 1).unwrap_err()));
+    }
+
+    #[test]
+    fn gpfifo_entries() {
+        let entry = GpfifoEntry::new(0x12_3456_7890, 0x1234, GpfifoEntryFlags::NoPrefetch());
+        assert_eq!(entry.desc, 0x8048D01234567890);
+        assert_eq!(entry.get_gpu_address(), 0x12_3456_7890);
+        assert_eq!(entry.get_word_count(), 0x1234);
+        assert_eq!(entry.get_flags().get(), GpfifoEntryFlags::NoPrefetch().get());
+
+        // Addresses are 40-bit and word-aligned
+        let entry = GpfifoEntry::new(0xFF_FF12_3456_7893, GPFIFO_ENTRY_MAX_WORD_COUNT, GpfifoEntryFlags::NotMain());
+        assert_eq!(entry.desc, 0x7FFFFE1234567890);
+        assert_eq!(entry.get_word_count(), GPFIFO_ENTRY_MAX_WORD_COUNT);
+        assert_eq!(entry.get_flags().get(), GpfifoEntryFlags::NotMain().get());
+
+        let mut list = CommandList::new();
+        list.init_3d();
+        let entry = list.make_gpfifo_entry(0x10000, GpfifoEntryFlags::None()).unwrap();
+        assert_eq!(entry.desc, (2 << 42) | 0x10000);
+
+        let list = CommandList { words: vec![0; GPFIFO_ENTRY_MAX_WORD_COUNT as usize + 1] };
+        assert!(results::lib::gpu::ResultInvalidBufferSize::matches(list.make_gpfifo_entry(0x10000, GpfifoEntryFlags::None()).err().unwrap()));
+    }
+}
\ No newline at end of file
@@ -1,17 +1,29 @@
 use crate::service::nv;
+use crate::results;
 use super::*;
 
+// Defined along with command lists, which are what GPFIFO entries point to
+pub use super::cmdlist::GpfifoEntry;
+pub use super::cmdlist::GpfifoEntryFlags;
+pub use super::cmdlist::GPFIFO_ENTRY_MAX_WORD_COUNT;
+
 #[derive(Copy, Clone, PartialEq, Eq, Debug)]
 #[repr(u8)]
 pub enum IoctlFd {
     NvHost,
     NvMap,
     NvHostCtrl,
+    NvHostGpu,
 }
 
-pub trait Ioctl {
+pub trait Ioctl: Sized {
     fn get_id() -> nv::IoctlId;
     fn get_fd() -> IoctlFd;
+
+    // Ioctls with variable-length inline data override this, the actual ID being sized accordingly
+    fn get_size(&self) -> usize {
+        core::mem::size_of::<Self>()
+    }
 }
 
 #[derive(Copy, Clone)]
@@ -94,6 +106,56 @@ impl Ioctl for NvMapGetId {
     }
 }
 
+#[derive(Copy, Clone)]
+#[repr(C)]
+pub struct NvMapFree {
+    pub handle: u32,
+    pub pad: u32,
+    pub address: *mut u8,
+    pub size: u32,
+    // 1 if the memory isn't freed yet (still in use by someone else)
+    pub flags: u32,
+}
+
+impl Ioctl for NvMapFree {
+    fn get_id() -> nv::IoctlId {
+        nv::IoctlId::NvMapFree
+    }
+
+    fn get_fd() -> IoctlFd {
+        IoctlFd::NvMap
+    }
+}
+
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+#[repr(u32)]
+pub enum NvMapParameter {
+    Size = 1,
+    Alignment = 2,
+    Base = 3,
+    Heap = 4,
+    Kind = 5,
+    Compr = 6,
+}
+
+#[derive(Copy, Clone)]
+#[repr(C)]
+pub struct NvMapParam {
+    pub handle: u32,
+    pub param: NvMapParameter,
+    pub result: u32,
+}
+
+impl Ioctl for NvMapParam {
+    fn get_id() -> nv::IoctlId {
+        nv::IoctlId::NvMapParam
+    }
+
+    fn get_fd() -> IoctlFd {
+        IoctlFd::NvMap
+    }
+}
+
 #[derive(Copy, Clone)]
 #[repr(C)]
 pub struct NvHostCtrlSyncptWait {
@@ -109,4 +171,324 @@ impl Ioctl for NvHostCtrlSyncptWait {
     fn get_fd() -> IoctlFd {
         IoctlFd::NvHostCtrl
     }
+}
+
+#[derive(Copy, Clone)]
+#[repr(C)]
+pub struct NvHostCtrlEventSignal {
+    pub event_id: u32
+}
+
+impl Ioctl for NvHostCtrlEventSignal {
+    fn get_id() -> nv::IoctlId {
+        nv::IoctlId::NvHostCtrlEventSignal
+    }
+
+    fn get_fd() -> IoctlFd {
+        IoctlFd::NvHostCtrl
+    }
+}
+
+// The value is the event ID to wait on (async waits) as input, and the syncpoint value as output
+#[derive(Copy, Clone)]
+#[repr(C)]
+pub struct NvHostCtrlEventWait {
+    pub fence: Fence,
+    pub timeout: i32,
+    pub value: u32
+}
+
+impl Ioctl for NvHostCtrlEventWait {
+    fn get_id() -> nv::IoctlId {
+        nv::IoctlId::NvHostCtrlEventWait
+    }
+
+    fn get_fd() -> IoctlFd {
+        IoctlFd::NvHostCtrl
+    }
+}
+
+#[derive(Copy, Clone)]
+#[repr(C)]
+pub struct NvHostCtrlEventWaitAsync {
+    pub fence: Fence,
+    pub timeout: i32,
+    pub value: u32
+}
+
+impl Ioctl for NvHostCtrlEventWaitAsync {
+    fn get_id() -> nv::IoctlId {
+        nv::IoctlId::NvHostCtrlEventWaitAsync
+    }
+
+    fn get_fd() -> IoctlFd {
+        IoctlFd::NvHostCtrl
+    }
+}
+
+#[derive(Copy, Clone)]
+#[repr(C)]
+pub struct NvHostCtrlEventRegister {
+    pub event_id: u32
+}
+
+impl Ioctl for NvHostCtrlEventRegister {
+    fn get_id() -> nv::IoctlId {
+        nv::IoctlId::NvHostCtrlEventRegister
+    }
+
+    fn get_fd() -> IoctlFd {
+        IoctlFd::NvHostCtrl
+    }
+}
+
+#[derive(Copy, Clone)]
+#[repr(C)]
+pub struct NvHostCtrlEventUnregister {
+    pub event_id: u32
+}
+
+impl Ioctl for NvHostCtrlEventUnregister {
+    fn get_id() -> nv::IoctlId {
+        nv::IoctlId::NvHostCtrlEventUnregister
+    }
+
+    fn get_fd() -> IoctlFd {
+        IoctlFd::NvHostCtrl
+    }
+}
+
+#[derive(Copy, Clone)]
+#[repr(C)]
+pub struct NvHostCtrlEventKill {
+    pub event_mask: u64
+}
+
+impl Ioctl for NvHostCtrlEventKill {
+    fn get_id() -> nv::IoctlId {
+        nv::IoctlId::NvHostCtrlEventKill
+    }
+
+    fn get_fd() -> IoctlFd {
+        IoctlFd::NvHostCtrl
+    }
+}
+
+// Address space (nvhost-as-gpu) ioctls
+
+#[derive(Copy, Clone)]
+#[repr(C)]
+pub struct NvHostAsGpuBindChannel {
+    pub fd: nv::Fd
+}
+
+impl Ioctl for NvHostAsGpuBindChannel {
+    fn get_id() -> nv::IoctlId {
+        nv::IoctlId::NvHostAsGpuBindChannel
+    }
+
+    fn get_fd() -> IoctlFd {
+        IoctlFd::NvHost
+    }
+}
+
+bit_enum! {
+    AllocSpaceFlags (u32) {
+        FixedOffset = 0x1,
+        Sparse = 0x2
+    }
+}
+
+#[derive(Copy, Clone)]
+#[repr(C)]
+pub struct NvHostAsGpuAllocSpace {
+    pub pages: u32,
+    pub page_size: u32,
+    pub flags: AllocSpaceFlags,
+    pub pad: u32,
+    // The alignment as input (or the fixed offset, with FixedOffset), the allocated offset as output
+    pub offset: u64
+}
+
+impl Ioctl for NvHostAsGpuAllocSpace {
+    fn get_id() -> nv::IoctlId {
+        nv::IoctlId::NvHostAsGpuAllocSpace
+    }
+
+    fn get_fd() -> IoctlFd {
+        IoctlFd::NvHost
+    }
+}
+
+#[derive(Copy, Clone)]
+#[repr(C)]
+pub struct NvHostAsGpuUnmapBuffer {
+    pub offset: u64
+}
+
+impl Ioctl for NvHostAsGpuUnmapBuffer {
+    fn get_id() -> nv::IoctlId {
+        nv::IoctlId::NvHostAsGpuUnmapBuffer
+    }
+
+    fn get_fd() -> IoctlFd {
+        IoctlFd::NvHost
+    }
+}
+
+bit_enum! {
+    MapBufferFlags (u32) {
+        FixedOffset = 0x1,
+        IsCacheable = 0x4,
+        Modify = 0x100
+    }
+}
+
+#[derive(Copy, Clone)]
+#[repr(C)]
+pub struct NvHostAsGpuMapBufferEx {
+    pub flags: MapBufferFlags,
+    pub kind: Kind,
+    pub nvmap_handle: u32,
+    pub page_size: u32,
+    pub buffer_offset: u64,
+    pub mapping_size: u64,
+    // The GPU virtual address (input with FixedOffset, output otherwise)
+    pub offset: u64
+}
+
+impl Ioctl for NvHostAsGpuMapBufferEx {
+    fn get_id() -> nv::IoctlId {
+        nv::IoctlId::NvHostAsGpuMapBufferEx
+    }
+
+    fn get_fd() -> IoctlFd {
+        IoctlFd::NvHost
+    }
+}
+
+// Channel (nvhost-gpu) ioctls
+
+#[derive(Copy, Clone)]
+#[repr(C)]
+pub struct NvHostGpuSetNvmapFd {
+    pub fd: nv::Fd
+}
+
+impl Ioctl for NvHostGpuSetNvmapFd {
+    fn get_id() -> nv::IoctlId {
+        nv::IoctlId::NvHostGpuSetNvmapFd
+    }
+
+    fn get_fd() -> IoctlFd {
+        IoctlFd::NvHostGpu
+    }
+}
+
+bit_enum! {
+    SubmitGpfifoFlags (u32) {
+        FenceWait = 0x1,
+        FenceGet = 0x2,
+        HwFormat = 0x4,
+        SyncFence = 0x8,
+        SuppressWfi = 0x10,
+        SkipBufferRefcounting = 0x20
+    }
+}
+
+// The entries are sent inline, only the first num_entries ones (up to this amount) being sent
+pub const MAX_SUBMIT_GPFIFO_ENTRIES: usize = 16;
+
+#[derive(Copy, Clone)]
+#[repr(C)]
+pub struct NvHostGpuSubmitGpfifo {
+    pub gpfifo: u64,
+    pub num_entries: u32,
+    pub flags: SubmitGpfifoFlags,
+    pub fence: Fence,
+    pub entries: [GpfifoEntry; MAX_SUBMIT_GPFIFO_ENTRIES]
+}
+
+impl NvHostGpuSubmitGpfifo {
+    pub fn new(gpfifo: u64, entries: &[GpfifoEntry], flags: SubmitGpfifoFlags, fence: Fence) -> Result<Self> {
+        result_return_unless!(entries.len() <= MAX_SUBMIT_GPFIFO_ENTRIES, results::lib::gpu::ResultInvalidCommandArgument);
+
+        let mut submit = Self { gpfifo: gpfifo, num_entries: entries.len() as u32, flags: flags, fence: fence, entries: [GpfifoEntry { desc: 0 }; MAX_SUBMIT_GPFIFO_ENTRIES] };
+        submit.entries[..entries.len()].copy_from_slice(entries);
+        Ok(submit)
+    }
+}
+
+impl Ioctl for NvHostGpuSubmitGpfifo {
+    fn get_id() -> nv::IoctlId {
+        nv::IoctlId::NvHostGpuSubmitGpfifo
+    }
+
+    fn get_fd() -> IoctlFd {
+        IoctlFd::NvHostGpu
+    }
+
+    fn get_size(&self) -> usize {
+        let entry_count = core::cmp::min(self.num_entries as usize, MAX_SUBMIT_GPFIFO_ENTRIES);
+        core::mem::size_of::<Self>() - (MAX_SUBMIT_GPFIFO_ENTRIES - entry_count) * core::mem::size_of::<GpfifoEntry>()
+    }
+}
+
+#[derive(Copy, Clone)]
+#[repr(C)]
+pub struct NvHostGpuAllocObjCtx {
+    pub class_num: u32,
+    pub flags: u32,
+    pub obj_id: u64
+}
+
+impl Ioctl for NvHostGpuAllocObjCtx {
+    fn get_id() -> nv::IoctlId {
+        nv::IoctlId::NvHostGpuAllocObjCtx
+    }
+
+    fn get_fd() -> IoctlFd {
+        IoctlFd::NvHostGpu
+    }
+}
+
+#[derive(Copy, Clone)]
+#[repr(C)]
+pub struct NvHostGpuSetErrorNotifier {
+    pub offset: u64,
+    pub size: u64,
+    pub mem: u32,
+    pub pad: u32
+}
+
+impl Ioctl for NvHostGpuSetErrorNotifier {
+    fn get_id() -> nv::IoctlId {
+        nv::IoctlId::NvHostGpuSetErrorNotifier
+    }
+
+    fn get_fd() -> IoctlFd {
+        IoctlFd::NvHostGpu
+    }
+}
+
+#[derive(Copy, Clone)]
+#[repr(C)]
+pub struct NvHostGpuAllocGpfifoEx2 {
+    pub num_entries: u32,
+    pub flags: u32,
+    pub unk_0: u32,
+    pub fence: Fence,
+    pub unk_1: u32,
+    pub unk_2: u32,
+    pub unk_3: u32
+}
+
+impl Ioctl for NvHostGpuAllocGpfifoEx2 {
+    fn get_id() -> nv::IoctlId {
+        nv::IoctlId::NvHostGpuAllocGpfifoEx2
+    }
+
+    fn get_fd() -> IoctlFd {
+        IoctlFd::NvHostGpu
+    }
 }
\ No newline at end of file
@@ -23,6 +23,12 @@ pub mod ioctl;
 
 pub mod surface;
 
+pub mod swizzle;
+
+pub mod swapchain;
+
+pub mod cmdlist;
+
 #[derive(Copy, Clone, PartialEq, Eq, Debug)]
 #[repr(u32)]
 pub enum Layout {
@@ -659,8 +665,8 @@ pub const GRAPHIC_BUFFER_MAGIC: u32 = 0xDAFFCAFF;
 #[derive(Copy, Clone)]
 #[repr(C)]
 pub struct Fence {
-    id: u32,
-    value: u32
+    pub id: u32,
+    pub value: u32
 }
 
 #[derive(Copy, Clone)]
@@ -722,6 +728,7 @@ pub const fn align_height(height: u32) -> u32 {
 const NVHOST_PATH: &str = nul!("/dev/nvhost-as-gpu");
 const NVMAP_PATH: &str = nul!("/dev/nvmap");
 const NVHOSTCTRL_PATH: &str = nul!("/dev/nvhost-ctrl");
+const NVHOSTGPU_PATH: &str = nul!("/dev/nvhost-gpu");
 
 const SIZE_FACTOR: f32 = 1.5; // 1920x1080 / 1280x720
 
@@ -745,6 +752,8 @@ pub struct GpuContext<VS: IRootService + service::IService + 'static, NS: INvDrv
     nvhost_fd: u32,
     nvmap_fd: u32,
     nvhostctrl_fd: u32,
+    // Only opened when first needed (see do_ioctl), since most contexts never submit commands to the GPU
+    nvhostgpu_fd: Option<u32>
 }
 
 impl<VS: IRootService + service::IService + 'static, NS: INvDrvService + service::IService + 'static> GpuContext<VS, NS> {
@@ -765,9 +774,9 @@ impl<VS: IRootService + service::IService + 'static, NS: INvDrvService + service
         let (nvhostctrl_fd, nvhostctrl_err) = nvdrv_srv.get().open(sf::Buffer::from_const(NVHOSTCTRL_PATH.as_ptr(), NVHOSTCTRL_PATH.len()))?;
         nv::convert_error_code(nvhostctrl_err)?;
         
-        let application_display_srv = vi_srv.get().get_display_service(vi::DisplayServiceMode::Privileged)?.to::<vi::ApplicationDisplayService>();
-        let hos_binder_drv = application_display_srv.get().get_relay_service()?.to::<dispdrv::HOSBinderDriver>();
-        Ok(Self { vi_service: vi_srv, nvdrv_service: nvdrv_srv, application_display_service: application_display_srv, hos_binder_driver: hos_binder_drv, transfer_mem: transfer_mem, transfer_mem_alloc_layout: transfer_mem_alloc_layout, transfer_mem_handle: transfer_mem_handle, nvhost_fd: nvhost_fd, nvmap_fd: nvmap_fd, nvhostctrl_fd: nvhostctrl_fd })
+        let application_display_srv = vi_srv.get().get_display_service(vi::DisplayServiceMode::Privileged)?;
+        let hos_binder_drv = application_display_srv.get().get_relay_service()?;
+        Ok(Self { vi_service: vi_srv, nvdrv_service: nvdrv_srv, application_display_service: application_display_srv, hos_binder_driver: hos_binder_drv, transfer_mem: transfer_mem, transfer_mem_alloc_layout: transfer_mem_alloc_layout, transfer_mem_handle: transfer_mem_handle, nvhost_fd: nvhost_fd, nvmap_fd: nvmap_fd, nvhostctrl_fd: nvhostctrl_fd, nvhostgpu_fd: None })
     }
 
     pub fn get_vi_service(&self) -> mem::Shared<VS> {
@@ -786,12 +795,37 @@ impl<VS: IRootService + service::IService + 'static, NS: INvDrvService + service
         self.hos_binder_driver.clone()
     }
 
+    pub fn get_nvhostgpu_fd(&mut self) -> Result<nv::Fd> {
+        match self.nvhostgpu_fd {
+            Some(fd) => Ok(fd),
+            None => {
+                let (fd, err) = self.nvdrv_service.get().open(sf::Buffer::from_const(NVHOSTGPU_PATH.as_ptr(), NVHOSTGPU_PATH.len()))?;
+                nv::convert_error_code(err)?;
+                self.nvhostgpu_fd = Some(fd);
+                Ok(fd)
+            }
+        }
+    }
+
+    pub fn do_ioctl<I: ioctl::Ioctl>(&mut self, i: &mut I) -> Result<()> {
+        let fd = match I::get_fd() {
+            ioctl::IoctlFd::NvHost => self.nvhost_fd,
+            ioctl::IoctlFd::NvMap => self.nvmap_fd,
+            ioctl::IoctlFd::NvHostCtrl => self.nvhostctrl_fd,
+            ioctl::IoctlFd::NvHostGpu => self.get_nvhostgpu_fd()?,
+        };
+
+        let size = i.get_size();
+        let err = self.nvdrv_service.get().ioctl(fd, I::get_id().with_size(size), sf::Buffer::from_const(i as *const I, size), sf::Buffer::from_mut(i as *mut I, size))?;
+        nv::convert_error_code(err)
+    }
+
     fn stray_layer_destroy(layer_id: vi::LayerId, application_display_service: mem::Shared<vi::ApplicationDisplayService>) -> Result<()> {
         application_display_service.get().destroy_stray_layer(layer_id)
     }
 
     fn managed_layer_destroy(layer_id: vi::LayerId, application_display_service: mem::Shared<vi::ApplicationDisplayService>) -> Result<()> {
-        let manager_display_service = application_display_service.get().get_manager_display_service()?.to::<vi::ManagerDisplayService>();
+        let manager_display_service = application_display_service.get().get_manager_display_service()?;
         manager_display_service.get().destroy_managed_layer(layer_id)
     }
 
@@ -831,8 +865,8 @@ impl<VS: IRootService + service::IService + 'static, NS: INvDrvService + service
     pub fn create_managed_layer_surface(&mut self, display_name: &str, aruid: applet::AppletResourceUserId, layer_flags: vi::LayerFlags, x: f32, y: f32, width: u32, height: u32, z: LayerZ, buffer_count: u32, color_fmt: ColorFormat, pixel_fmt: PixelFormat, layout: Layout) -> Result<surface::Surface<NS>> {
         let display_name_v = vi::DisplayName::from(display_name)?;
         let display_id = self.application_display_service.get().open_display(display_name_v)?;
-        let system_display_service = self.application_display_service.get().get_system_display_service()?.to::<vi::SystemDisplayService>();
-        let manager_display_service = self.application_display_service.get().get_manager_display_service()?.to::<vi::ManagerDisplayService>();
+        let system_display_service = self.application_display_service.get().get_system_display_service()?;
+        let manager_display_service = self.application_display_service.get().get_manager_display_service()?;
         let native_window = parcel::ParcelPayload::new();
 
         let layer_id = manager_display_service.get().create_managed_layer(layer_flags, display_id, aruid)?;
@@ -850,6 +884,9 @@ impl<VS: IRootService + service::IService + 'static, NS: INvDrvService + service
         let _ = self.nvdrv_service.get().close(self.nvhost_fd);
         let _ = self.nvdrv_service.get().close(self.nvmap_fd);
         let _ = self.nvdrv_service.get().close(self.nvhostctrl_fd);
+        if let Some(nvhostgpu_fd) = self.nvhostgpu_fd {
+            let _ = self.nvdrv_service.get().close(nvhostgpu_fd);
+        }
 
         unsafe { alloc::alloc::dealloc(self.transfer_mem, self.transfer_mem_alloc_layout); }
         let _ = svc::close_handle(self.transfer_mem_handle);
@@ -8,6 +8,7 @@ use crate::service::nv;
 use crate::service::vi;
 use crate::service::dispdrv;
 use crate::mem;
+use crate::os;
 use core::mem as cmem;
 use core::ptr;
 
@@ -32,14 +33,15 @@ pub struct Surface<NS: nv::INvDrvService + 'static> {
     color_fmt: ColorFormat,
     pixel_fmt: PixelFormat,
     layout: Layout,
+    swap_interval: u32,
     display_id: vi::DisplayId,
     layer_id: vi::LayerId,
     layer_destroy_fn: LayerDestroyFn,
     nvhost_fd: nv::Fd,
     nvmap_fd: nv::Fd,
     nvhostctrl_fd: nv::Fd,
-    vsync_event_handle: svc::Handle,
-    buffer_event_handle: svc::Handle
+    vsync_event: os::SystemEvent,
+    buffer_event: os::SystemEvent
 }
 
 impl<NS: nv::INvDrvService> Surface<NS> {
@@ -49,7 +51,7 @@ impl<NS: nv::INvDrvService> Surface<NS> {
         let _ = binder.connect(ConnectionApi::Cpu, false)?;
         let vsync_event_handle = application_display_service.get().get_display_vsync_event(display_id)?;
         let buffer_event_handle = binder.get_native_handle(dispdrv::NativeHandleType::BufferEvent)?;
-        let mut surface = Self { binder: binder, nvdrv_srv: nvdrv_srv, application_display_service: application_display_service, width: width, height: height, buffer_data: ptr::null_mut(), buffer_alloc_layout: alloc::alloc::Layout::new::<u8>(), single_buffer_size: 0, buffer_count: buffer_count, slot_has_requested: [false; MAX_BUFFERS], graphic_buf: unsafe { cmem::zeroed() }, color_fmt: color_fmt, pixel_fmt: pixel_fmt, layout: layout, display_id: display_id, layer_id: layer_id, layer_destroy_fn: layer_destroy_fn, nvhost_fd: nvhost_fd, nvmap_fd: nvmap_fd, nvhostctrl_fd: nvhostctrl_fd, vsync_event_handle: vsync_event_handle.handle, buffer_event_handle: buffer_event_handle.handle };
+        let mut surface = Self { binder: binder, nvdrv_srv: nvdrv_srv, application_display_service: application_display_service, width: width, height: height, buffer_data: ptr::null_mut(), buffer_alloc_layout: alloc::alloc::Layout::new::<u8>(), single_buffer_size: 0, buffer_count: buffer_count, slot_has_requested: [false; MAX_BUFFERS], graphic_buf: unsafe { cmem::zeroed() }, color_fmt: color_fmt, pixel_fmt: pixel_fmt, layout: layout, swap_interval: 1, display_id: display_id, layer_id: layer_id, layer_destroy_fn: layer_destroy_fn, nvhost_fd: nvhost_fd, nvmap_fd: nvmap_fd, nvhostctrl_fd: nvhostctrl_fd, vsync_event: os::SystemEvent::from_copy_handle(vsync_event_handle, true), buffer_event: os::SystemEvent::from_copy_handle(buffer_event_handle, true) };
         surface.initialize()?;
         Ok(surface)
     }
@@ -59,9 +61,12 @@ impl<NS: nv::INvDrvService> Surface<NS> {
             ioctl::IoctlFd::NvHost => self.nvhost_fd,
             ioctl::IoctlFd::NvMap => self.nvmap_fd,
             ioctl::IoctlFd::NvHostCtrl => self.nvhostctrl_fd,
+            // Surfaces don't own a GPU channel
+            ioctl::IoctlFd::NvHostGpu => return Err(results::lib::gpu::ResultUnsupportedIoctlFd::make())
         };
 
-        let err = self.nvdrv_srv.get().ioctl(fd, I::get_id(), sf::Buffer::from_var(i), sf::Buffer::from_var(i))?;
+        let size = i.get_size();
+        let err = self.nvdrv_srv.get().ioctl(fd, I::get_id().with_size(size), sf::Buffer::from_const(i as *const I, size), sf::Buffer::from_mut(i as *mut I, size))?;
         nv::convert_error_code(err)
     }
 
@@ -146,10 +151,7 @@ impl<NS: nv::INvDrvService> Surface<NS> {
         unsafe { alloc::alloc::dealloc(self.buffer_data, self.buffer_alloc_layout); }
         (self.layer_destroy_fn)(self.layer_id, self.application_display_service.clone())?;
 
-        self.application_display_service.get().close_display(self.display_id)?;
-
-        svc::close_handle(self.buffer_event_handle)?;
-        svc::close_handle(self.vsync_event_handle)
+        self.application_display_service.get().close_display(self.display_id)
     }
 
     pub fn dequeue_buffer(&mut self, is_async: bool) -> Result<(*mut u8, usize, i32, bool, MultiFence)> {
@@ -193,7 +195,7 @@ impl<NS: nv::INvDrvService> Surface<NS> {
 
     pub fn queue_buffer(&mut self, slot: i32, fences: MultiFence) -> Result<()> {
         let mut qbi: QueueBufferInput = unsafe { cmem::zeroed() };
-        qbi.swap_interval = 1;
+        qbi.swap_interval = self.swap_interval;
         qbi.fences = fences;
 
         mem::flush_data_cache(self.buffer_data, self.single_buffer_size * self.buffer_count as usize);
@@ -217,18 +219,16 @@ impl<NS: nv::INvDrvService> Surface<NS> {
     }
 
     pub fn set_visible(&mut self, visible: bool) -> Result<()> {
-        let system_display_service = self.application_display_service.get().get_system_display_service()?.to::<vi::SystemDisplayService>();
+        let system_display_service = self.application_display_service.get().get_system_display_service()?;
         system_display_service.get().set_layer_visibility(visible, self.layer_id)
     }
 
     pub fn wait_buffer_event(&mut self, timeout: i64) -> Result<()> {
-        svc::wait_synchronization(&self.buffer_event_handle, 1, timeout)?;
-        svc::reset_signal(self.buffer_event_handle)
+        self.buffer_event.timed_wait(timeout)
     }
 
     pub fn wait_vsync_event(&mut self, timeout: i64) -> Result<()> {
-        svc::wait_synchronization(&self.vsync_event_handle, 1, timeout)?;
-        svc::reset_signal(self.vsync_event_handle)
+        self.vsync_event.timed_wait(timeout)
     }
 
     pub fn get_width(&self) -> u32 {
@@ -243,6 +243,19 @@ impl<NS: nv::INvDrvService> Surface<NS> {
         self.color_fmt
     }
 
+    pub fn get_layout(&self) -> Layout {
+        self.layout
+    }
+
+    pub fn get_swap_interval(&self) -> u32 {
+        self.swap_interval
+    }
+
+    // Number of vsyncs each queued buffer is shown for (0 presents it as soon as possible)
+    pub fn set_swap_interval(&mut self, swap_interval: u32) {
+        self.swap_interval = swap_interval;
+    }
+
     pub fn compute_stride(&self) -> u32 {
         let bpp = calculate_bpp(self.color_fmt);
         align_width(bpp, self.width) * bpp
@@ -0,0 +1,208 @@
+use super::*;
+use crate::results;
+use crate::arm;
+use crate::gpu::surface;
+use crate::service::nv;
+use alloc::vec::Vec;
+use core::mem as cmem;
+use core::slice;
+
+// Linear view of a frame's pixels, whatever the layout of the surface is
+pub struct Frame<'a, T: Copy> {
+    pixels: &'a mut [T],
+    width: u32,
+    height: u32,
+    stride: u32
+}
+
+impl<'a, T: Copy> Frame<'a, T> {
+    pub fn get_width(&self) -> u32 {
+        self.width
+    }
+
+    pub fn get_height(&self) -> u32 {
+        self.height
+    }
+
+    // In pixels
+    pub fn get_stride(&self) -> u32 {
+        self.stride
+    }
+
+    // Rows are get_stride() pixels long, only the first get_width() ones being visible
+    pub fn get_pixels(&mut self) -> &mut [T] {
+        &mut self.pixels[..]
+    }
+
+    pub fn get_row(&mut self, y: u32) -> Option<&mut [T]> {
+        if y < self.height {
+            let start = (y * self.stride) as usize;
+            Some(&mut self.pixels[start..start + self.width as usize])
+        }
+        else {
+            None
+        }
+    }
+
+    pub fn set_pixel(&mut self, x: u32, y: u32, pixel: T) {
+        if (x < self.width) && (y < self.height) {
+            self.pixels[(y * self.stride + x) as usize] = pixel;
+        }
+    }
+
+    pub fn fill(&mut self, pixel: T) {
+        for cur_pixel in self.pixels.iter_mut() {
+            *cur_pixel = pixel;
+        }
+    }
+}
+
+#[derive(Copy, Clone, Debug)]
+pub struct PresentStatistics {
+    pub frame_count: u64,
+    // Times between consecutive presents (the first frame isn't measured)
+    pub last_frame_time_ns: u64,
+    pub min_frame_time_ns: u64,
+    pub max_frame_time_ns: u64,
+    pub total_frame_time_ns: u64
+}
+
+impl PresentStatistics {
+    pub const fn new() -> Self {
+        Self { frame_count: 0, last_frame_time_ns: 0, min_frame_time_ns: 0, max_frame_time_ns: 0, total_frame_time_ns: 0 }
+    }
+
+    pub const fn get_average_frame_time_ns(&self) -> u64 {
+        match self.frame_count {
+            0 | 1 => 0,
+            frame_count => self.total_frame_time_ns / (frame_count - 1)
+        }
+    }
+
+    pub const fn get_average_fps(&self) -> u64 {
+        match self.get_average_frame_time_ns() {
+            0 => 0,
+            average_frame_time_ns => 1_000_000_000 / average_frame_time_ns
+        }
+    }
+
+    fn push_frame(&mut self, frame_time_ns: Option<u64>) {
+        self.frame_count += 1;
+        if let Some(frame_time_ns) = frame_time_ns {
+            self.last_frame_time_ns = frame_time_ns;
+            self.total_frame_time_ns += frame_time_ns;
+            if (self.min_frame_time_ns == 0) || (frame_time_ns < self.min_frame_time_ns) {
+                self.min_frame_time_ns = frame_time_ns;
+            }
+            if frame_time_ns > self.max_frame_time_ns {
+                self.max_frame_time_ns = frame_time_ns;
+            }
+        }
+    }
+}
+
+// Owns the surface's buffer slots: each frame dequeues a buffer, lets the caller draw it and queues it back
+// Pitch surfaces are drawn directly, other layouts are drawn in a linear buffer and converted when presenting
+pub struct Swapchain<NS: nv::INvDrvService + 'static, T: Copy> {
+    surface: surface::Surface<NS>,
+    linear_buf: Vec<T>,
+    stride: u32,
+    last_present_tick: Option<u64>,
+    stats: PresentStatistics
+}
+
+impl<NS: nv::INvDrvService + 'static, T: Copy + Default> Swapchain<NS, T> {
+    // Each pixel (T) must be exactly as big as the surface's color format pixels
+    pub fn new(surface: surface::Surface<NS>, swap_interval: u32) -> Result<Self> {
+        let bpp = calculate_bpp(surface.get_color_format());
+        result_return_unless!(cmem::size_of::<T>() == bpp as usize, results::lib::gpu::ResultUnsupportedColorFormat);
+        result_return_unless!(swizzle::is_bpp_supported(bpp), results::lib::gpu::ResultUnsupportedColorFormat);
+        result_return_if!(surface.get_layout() == Layout::Tiled, results::lib::gpu::ResultUnsupportedLayout);
+
+        let stride = swizzle::get_pitch_stride(surface.get_width(), bpp) / bpp;
+        let linear_buf = match surface.get_layout() {
+            Layout::Pitch => Vec::new(),
+            _ => vec![T::default(); (stride * surface.get_height()) as usize]
+        };
+        let mut swapchain = Self { surface: surface, linear_buf: linear_buf, stride: stride, last_present_tick: None, stats: PresentStatistics::new() };
+        swapchain.set_swap_interval(swap_interval);
+        Ok(swapchain)
+    }
+
+    pub fn get_surface(&mut self) -> &mut surface::Surface<NS> {
+        &mut self.surface
+    }
+
+    pub fn get_swap_interval(&self) -> u32 {
+        self.surface.get_swap_interval()
+    }
+
+    // Can be changed between frames, 0 disables vsync pacing
+    pub fn set_swap_interval(&mut self, swap_interval: u32) {
+        self.surface.set_swap_interval(swap_interval);
+    }
+
+    pub fn get_statistics(&self) -> PresentStatistics {
+        self.stats
+    }
+
+    pub fn reset_statistics(&mut self) {
+        self.stats = PresentStatistics::new();
+        self.last_present_tick = None;
+    }
+
+    fn present(&mut self, slot: i32, fences: MultiFence) -> Result<()> {
+        self.surface.queue_buffer(slot, fences)?;
+        for _ in 0..self.surface.get_swap_interval() {
+            self.surface.wait_vsync_event(-1)?;
+        }
+
+        let tick = arm::get_system_tick();
+        let frame_time_ns = self.last_present_tick.map(|last_tick| arm::ticks_to_nanoseconds(tick - last_tick));
+        self.stats.push_frame(frame_time_ns);
+        self.last_present_tick = Some(tick);
+        Ok(())
+    }
+
+    pub fn render_frame<F: FnOnce(&mut Frame<T>) -> Result<()>>(&mut self, render_fn: F) -> Result<()> {
+        let (buf, buf_size, slot, _has_fences, fences) = self.surface.dequeue_buffer(true)?;
+        self.surface.wait_fences(fences, -1)?;
+
+        let width = self.surface.get_width();
+        let height = self.surface.get_height();
+        let rc = match self.surface.get_layout() {
+            Layout::Pitch => {
+                let pixels = unsafe { slice::from_raw_parts_mut(buf as *mut T, buf_size / cmem::size_of::<T>()) };
+                render_fn(&mut Frame { pixels: pixels, width: width, height: height, stride: self.stride })
+            },
+            layout => {
+                match render_fn(&mut Frame { pixels: &mut self.linear_buf, width: width, height: height, stride: self.stride }) {
+                    Ok(()) => {
+                        let out_buf = unsafe { slice::from_raw_parts_mut(buf, buf_size) };
+                        let in_buf = unsafe { slice::from_raw_parts(self.linear_buf.as_ptr() as *const u8, self.linear_buf.len() * cmem::size_of::<T>()) };
+                        swizzle::convert_linear_to_layout(out_buf, in_buf, width, height, self.stride * cmem::size_of::<T>() as u32, self.surface.get_color_format(), layout)
+                    },
+                    Err(rc) => Err(rc)
+                }
+            }
+        };
+
+        // The buffer is queued even if rendering failed, so that its slot doesn't get lost
+        self.present(slot, fences)?;
+        rc
+    }
+
+    // Renders frames until the given function returns false (or fails)
+    pub fn run<F: FnMut(&mut Frame<T>) -> Result<bool>>(&mut self, mut render_fn: F) -> Result<()> {
+        loop {
+            let mut keep_running = true;
+            self.render_frame(|frame| {
+                keep_running = render_fn(frame)?;
+                Ok(())
+            })?;
+            if !keep_running {
+                return Ok(());
+            }
+        }
+    }
+}
\ No newline at end of file
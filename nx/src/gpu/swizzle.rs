@@ -0,0 +1,238 @@
+use super::*;
+use crate::results;
+
+// Block-linear memory is made of 512-byte GOBs (64 bytes by 8 rows), stacked vertically in blocks of (1 << block_height_log2) GOBs
+// Inside a GOB, memory is split in 16-byte chunks of two rows each, which makes them the unit copied here for any bpp
+
+pub const GOB_WIDTH: u32 = 64;
+pub const GOB_HEIGHT: u32 = 8;
+pub const GOB_SIZE: u32 = GOB_WIDTH * GOB_HEIGHT;
+
+const CHUNK_SIZE: u32 = 16;
+
+pub const fn is_bpp_supported(bpp: u32) -> bool {
+    matches!(bpp, 1 | 2 | 4 | 8 | 16)
+}
+
+pub const fn get_block_linear_size(width: u32, height: u32, bpp: u32, block_height_log2: u32) -> usize {
+    let block_height_px = GOB_HEIGHT << block_height_log2;
+    let width_gobs = (width * bpp + GOB_WIDTH - 1) / GOB_WIDTH;
+    let height_blocks = (height + block_height_px - 1) / block_height_px;
+    (width_gobs * height_blocks * (GOB_SIZE << block_height_log2)) as usize
+}
+
+// Offset of the row's first byte, and of the chunk (at the given byte offset of any row) from there
+const fn get_block_linear_row_offset(y: u32, width_gobs: u32, block_height_log2: u32) -> u32 {
+    let block_height_px = GOB_HEIGHT << block_height_log2;
+    let block_y = y / block_height_px;
+    let gob_y = (y / GOB_HEIGHT) & ((1 << block_height_log2) - 1);
+    let gob_row = y % GOB_HEIGHT;
+    (block_y * width_gobs * (GOB_SIZE << block_height_log2)) + (gob_y * GOB_SIZE) + ((gob_row >> 1) * 64) + ((gob_row & 1) * CHUNK_SIZE)
+}
+
+const fn get_block_linear_chunk_offset(x_bytes: u32, block_height_log2: u32) -> u32 {
+    let block_x = x_bytes / GOB_WIDTH;
+    let gob_x = x_bytes % GOB_WIDTH;
+    (block_x * (GOB_SIZE << block_height_log2)) + ((gob_x >> 5) * 256) + (((gob_x >> 4) & 1) * 32)
+}
+
+// Copies in_buf to out_buf chunk by chunk, out_buf being the block-linear buffer when converting to block-linear (and in_buf otherwise)
+fn convert_block_linear_impl(out_buf: *mut u8, in_buf: *const u8, width: u32, height: u32, stride: u32, bpp: u32, block_height_log2: u32, to_block_linear: bool) {
+    let row_size = width * bpp;
+    let width_gobs = (row_size + GOB_WIDTH - 1) / GOB_WIDTH;
+    for y in 0..height {
+        let row_offset = get_block_linear_row_offset(y, width_gobs, block_height_log2);
+        let mut x_bytes: u32 = 0;
+        while x_bytes < row_size {
+            // The last chunk of a row may be a partial one
+            let copy_size = core::cmp::min(CHUNK_SIZE, row_size - x_bytes) as usize;
+            let block_linear_offset = (row_offset + get_block_linear_chunk_offset(x_bytes, block_height_log2)) as usize;
+            let linear_offset = (y * stride + x_bytes) as usize;
+            let (out_offset, in_offset) = match to_block_linear {
+                true => (block_linear_offset, linear_offset),
+                false => (linear_offset, block_linear_offset)
+            };
+            unsafe {
+                core::ptr::copy_nonoverlapping(in_buf.add(in_offset), out_buf.add(out_offset), copy_size);
+            }
+            x_bytes += CHUNK_SIZE;
+        }
+    }
+}
+
+fn check_buffer_sizes(block_linear_buf: &[u8], linear_buf: &[u8], width: u32, height: u32, stride: u32, bpp: u32, block_height_log2: u32) -> Result<()> {
+    result_return_unless!(is_bpp_supported(bpp), results::lib::gpu::ResultUnsupportedColorFormat);
+    result_return_unless!(stride >= (width * bpp), results::lib::gpu::ResultInvalidBufferSize);
+    if height > 0 {
+        let linear_size = ((height - 1) * stride + width * bpp) as usize;
+        result_return_unless!(linear_buf.len() >= linear_size, results::lib::gpu::ResultInvalidBufferSize);
+    }
+    result_return_unless!(block_linear_buf.len() >= get_block_linear_size(width, height, bpp, block_height_log2), results::lib::gpu::ResultInvalidBufferSize);
+    Ok(())
+}
+
+// The stride is the size of each linear row, in bytes
+pub fn convert_linear_to_block_linear(out_buf: &mut [u8], in_buf: &[u8], width: u32, height: u32, stride: u32, bpp: u32, block_height_log2: u32) -> Result<()> {
+    check_buffer_sizes(out_buf, in_buf, width, height, stride, bpp, block_height_log2)?;
+    convert_block_linear_impl(out_buf.as_mut_ptr(), in_buf.as_ptr(), width, height, stride, bpp, block_height_log2, true);
+    Ok(())
+}
+
+pub fn convert_block_linear_to_linear(out_buf: &mut [u8], in_buf: &[u8], width: u32, height: u32, stride: u32, bpp: u32, block_height_log2: u32) -> Result<()> {
+    check_buffer_sizes(in_buf, out_buf, width, height, stride, bpp, block_height_log2)?;
+    convert_block_linear_impl(out_buf.as_mut_ptr(), in_buf.as_ptr(), width, height, stride, bpp, block_height_log2, false);
+    Ok(())
+}
+
+fn convert_pitch_impl(out_buf: &mut [u8], out_stride: u32, in_buf: &[u8], in_stride: u32, row_size: u32, height: u32) -> Result<()> {
+    for y in 0..height {
+        let out_offset = (y * out_stride) as usize;
+        let in_offset = (y * in_stride) as usize;
+        result_return_unless!((out_offset + row_size as usize) <= out_buf.len(), results::lib::gpu::ResultInvalidBufferSize);
+        result_return_unless!((in_offset + row_size as usize) <= in_buf.len(), results::lib::gpu::ResultInvalidBufferSize);
+        out_buf[out_offset..out_offset + row_size as usize].copy_from_slice(&in_buf[in_offset..in_offset + row_size as usize]);
+    }
+    Ok(())
+}
+
+// Pitch surfaces (as allocated by us) use rows aligned like block-linear ones
+pub const fn get_pitch_stride(width: u32, bpp: u32) -> u32 {
+    align_width(bpp, width) * bpp
+}
+
+// Converts a linear image to the layout of a surface with the given color format (using the default block height)
+pub fn convert_linear_to_layout(out_buf: &mut [u8], in_buf: &[u8], width: u32, height: u32, stride: u32, color_fmt: ColorFormat, layout: Layout) -> Result<()> {
+    let bpp = calculate_bpp(color_fmt);
+    result_return_unless!(is_bpp_supported(bpp), results::lib::gpu::ResultUnsupportedColorFormat);
+    match layout {
+        Layout::Pitch => convert_pitch_impl(out_buf, get_pitch_stride(width, bpp), in_buf, stride, width * bpp, height),
+        Layout::BlockLinear => convert_linear_to_block_linear(out_buf, in_buf, width, height, stride, bpp, BLOCK_HEIGHT_LOG2),
+        Layout::Tiled => Err(results::lib::gpu::ResultUnsupportedLayout::make())
+    }
+}
+
+pub fn convert_layout_to_linear(out_buf: &mut [u8], in_buf: &[u8], width: u32, height: u32, stride: u32, color_fmt: ColorFormat, layout: Layout) -> Result<()> {
+    let bpp = calculate_bpp(color_fmt);
+    result_return_unless!(is_bpp_supported(bpp), results::lib::gpu::ResultUnsupportedColorFormat);
+    match layout {
+        Layout::Pitch => convert_pitch_impl(out_buf, stride, in_buf, get_pitch_stride(width, bpp), width * bpp, height),
+        Layout::BlockLinear => convert_block_linear_to_linear(out_buf, in_buf, width, height, stride, bpp, BLOCK_HEIGHT_LOG2),
+        Layout::Tiled => Err(results::lib::gpu::ResultUnsupportedLayout::make())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloc::vec::Vec;
+
+    // Byte offset in a block-linear surface, straight from the GOB layout description (as opposed to the chunk-based one above)
+    fn get_reference_offset(x_bytes: u32, y: u32, width_gobs: u32, block_height_log2: u32) -> usize {
+        let block_height = 1 << block_height_log2;
+        let gob_offset = ((y / (GOB_HEIGHT * block_height)) * GOB_SIZE * block_height * width_gobs) + ((x_bytes / GOB_WIDTH) * GOB_SIZE * block_height) + (((y % (GOB_HEIGHT * block_height)) / GOB_HEIGHT) * GOB_SIZE);
+        let x = x_bytes % GOB_WIDTH;
+        let y = y % GOB_HEIGHT;
+        (gob_offset + ((x / 32) * 256) + ((y / 2) * 64) + (((x % 32) / 16) * 32) + ((y % 2) * 16) + (x % 16)) as usize
+    }
+
+    fn make_linear_image(stride: u32, height: u32) -> Vec<u8> {
+        // Non-repeating enough for misplaced bytes to show up
+        (0..(stride * height)).map(|i| ((i * 7) ^ (i >> 8)) as u8).collect()
+    }
+
+    fn check_round_trip(width: u32, height: u32, bpp: u32, block_height_log2: u32) {
+        let row_size = width * bpp;
+        let stride = row_size + 3;
+        let width_gobs = (row_size + GOB_WIDTH - 1) / GOB_WIDTH;
+        let linear = make_linear_image(stride, height);
+
+        // Padding of partial GOBs/blocks is left untouched
+        let mut block_linear = vec![0xCDu8; get_block_linear_size(width, height, bpp, block_height_log2)];
+        convert_linear_to_block_linear(&mut block_linear, &linear, width, height, stride, bpp, block_height_log2).unwrap();
+
+        let mut expected_block_linear = vec![0xCDu8; block_linear.len()];
+        for y in 0..height {
+            for x_bytes in 0..row_size {
+                expected_block_linear[get_reference_offset(x_bytes, y, width_gobs, block_height_log2)] = linear[(y * stride + x_bytes) as usize];
+            }
+        }
+        assert!(block_linear == expected_block_linear, "{}x{} image, bpp {}, block height {}", width, height, bpp, 1 << block_height_log2);
+
+        let mut linear_2 = vec![0u8; linear.len()];
+        convert_block_linear_to_linear(&mut linear_2, &block_linear, width, height, stride, bpp, block_height_log2).unwrap();
+        for y in 0..height {
+            let row = (y * stride) as usize..(y * stride + row_size) as usize;
+            assert_eq!(linear_2[row.clone()], linear[row]);
+            // Stride padding is left untouched too
+            assert_eq!(linear_2[(y * stride + row_size) as usize..((y + 1) * stride) as usize], [0; 3]);
+        }
+    }
+
+    #[test]
+    fn gob_layout() {
+        // A single 64x8 GOB (16 pixels of 4 bytes per row)
+        let linear = make_linear_image(64, 8);
+        let mut block_linear = vec![0u8; GOB_SIZE as usize];
+        convert_linear_to_block_linear(&mut block_linear, &linear, 16, 8, 64, 4, 0).unwrap();
+
+        // 16-byte chunks of two rows each: (row 0, bytes 0-15), (row 1, bytes 0-15), (row 0, bytes 16-31), (row 1, bytes 16-31), then rows 2 and 3...
+        assert_eq!(block_linear[0x0..0x10], linear[0..16]);
+        assert_eq!(block_linear[0x10..0x20], linear[64..80]);
+        assert_eq!(block_linear[0x20..0x30], linear[16..32]);
+        assert_eq!(block_linear[0x30..0x40], linear[80..96]);
+        assert_eq!(block_linear[0x40..0x50], linear[128..144]);
+        assert_eq!(block_linear[0xC0..0xD0], linear[384..400]);
+        // ...and the right half of the GOB goes after the whole left half
+        assert_eq!(block_linear[0x100..0x110], linear[32..48]);
+        assert_eq!(block_linear[0x130..0x140], linear[112..128]);
+        assert_eq!(block_linear[0x1F0..0x200], linear[496..512]);
+    }
+
+    #[test]
+    fn block_layout() {
+        // Two GOBs wide, two blocks of two GOBs high
+        let width_gobs = 2;
+        let block_linear_size = get_block_linear_size(32, 32, 4, 1);
+        assert_eq!(block_linear_size, 2 * 2 * 2 * GOB_SIZE as usize);
+        // GOBs go down a block first, then right, then to the next row of blocks
+        assert_eq!(get_block_linear_row_offset(8, width_gobs, 1), GOB_SIZE);
+        assert_eq!(get_block_linear_chunk_offset(64, 1), 2 * GOB_SIZE);
+        assert_eq!(get_block_linear_row_offset(16, width_gobs, 1), 4 * GOB_SIZE);
+        assert_eq!(get_reference_offset(64, 24, width_gobs, 1), 7 * GOB_SIZE as usize);
+    }
+
+    #[test]
+    fn round_trips() {
+        for &bpp in &[1, 2, 4, 8, 16] {
+            for block_height_log2 in 0..=4 {
+                // Single GOBs, exact GOBs, and rows ending in a partial 16-byte chunk
+                for &(width_bytes, height) in &[(16, 8), (64, 8), (128, 16), (48, 3), (80, 130), (112, 47)] {
+                    let width = core::cmp::max(width_bytes / bpp, 1);
+                    check_round_trip(width, height, bpp, block_height_log2);
+                }
+            }
+        }
+        // Partial chunks for every bpp
+        check_round_trip(37, 9, 1, 0);
+        check_round_trip(21, 17, 2, 1);
+        check_round_trip(7, 33, 4, 2);
+        check_round_trip(3, 20, 8, 3);
+        check_round_trip(5, 70, 16, 4);
+    }
+
+    #[test]
+    fn buffer_sizes_are_checked() {
+        let linear = vec![0u8; 64 * 8];
+        let mut block_linear = vec![0u8; GOB_SIZE as usize];
+        assert!(results::lib::gpu::ResultUnsupportedColorFormat::matches(convert_linear_to_block_linear(&mut block_linear, &linear, 16, 8, 64, 3, 0).unwrap_err()));
+        // Stride below the row size
+        assert!(results::lib::gpu::ResultInvalidBufferSize::matches(convert_linear_to_block_linear(&mut block_linear, &linear, 16, 8, 32, 4, 0).unwrap_err()));
+        // Linear buffer too small
+        assert!(results::lib::gpu::ResultInvalidBufferSize::matches(convert_linear_to_block_linear(&mut block_linear, &linear[..64 * 8 - 1], 16, 8, 64, 4, 0).unwrap_err()));
+        // Block-linear buffer too small (a block of two GOBs is needed)
+        assert!(results::lib::gpu::ResultInvalidBufferSize::matches(convert_linear_to_block_linear(&mut block_linear, &linear, 16, 8, 64, 4, 1).unwrap_err()));
+        assert!(results::lib::gpu::ResultInvalidBufferSize::matches(convert_block_linear_to_linear(&mut vec![0u8; 64 * 8], &block_linear[..GOB_SIZE as usize - 1], 16, 8, 64, 4, 0).unwrap_err()));
+        // The last row doesn't need the whole stride
+        convert_linear_to_block_linear(&mut block_linear, &linear[..7 * 64 + 32], 8, 8, 64, 4, 0).unwrap();
+    }
+}
\ No newline at end of file
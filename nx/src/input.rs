@@ -243,7 +243,7 @@ impl InputContext {
     pub fn new(aruid: applet::AppletResourceUserId, supported_tags: hid::NpadStyleTag, controllers: &[hid::ControllerId]) -> Result<Self> {
         let hid_srv = service::new_service_object::<hid::HidServer>()?;
         let hid_process_id = sf::ProcessId::from(aruid);
-        let applet_res = hid_srv.get().create_applet_resource(hid_process_id)?.to::<hid::AppletResource>();
+        let applet_res = hid_srv.get().create_applet_resource(hid_process_id)?;
         let shmem_handle = applet_res.get().get_shared_memory_handle()?;
         let shmem_size = cmem::size_of::<SharedMemoryData>();
         let shmem_address = vmem::allocate(shmem_size)?;
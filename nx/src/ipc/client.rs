@@ -6,73 +6,90 @@ use crate::mem;
 use core::mem as cmem;
 
 #[inline(always)]
-pub fn write_command_on_ipc_buffer(ctx: &mut CommandContext, command_type: CommandType, data_size: u32) {
+pub fn write_command_on_buffer(buf: &mut [u8], ctx: &mut CommandContext, command_type: CommandType, data_size: u32) -> Result<()> {
+    write_raw_command_on_buffer(buf, ctx, command_type as u32, data_size)
+}
+
+#[inline(always)]
+pub fn write_raw_command_on_buffer(buf: &mut [u8], ctx: &mut CommandContext, raw_command_type: u32, data_size: u32) -> Result<()> {
     unsafe {
-        let mut ipc_buf = get_ipc_buffer();
+        let mut msg_buf = buf.as_mut_ptr();
     
         let has_special_header = ctx.in_params.send_process_id || ctx.in_params.copy_handles.len() > 0 || ctx.in_params.move_handles.len() > 0;
         let data_word_count = (data_size + 3) / 4;
-        let command_header = ipc_buf as *mut CommandHeader;
-        *command_header = CommandHeader::new(command_type, ctx.send_statics.len() as u32, ctx.send_buffers.len() as u32, ctx.receive_buffers.len() as u32, ctx.exchange_buffers.len() as u32, data_word_count, ctx.receive_statics.len() as u32, has_special_header);
-        ipc_buf = command_header.offset(1) as *mut u8;
+        let command_header = CommandHeader::new_raw(raw_command_type, ctx.send_statics.len() as u32, ctx.send_buffers.len() as u32, ctx.receive_buffers.len() as u32, ctx.exchange_buffers.len() as u32, data_word_count, ctx.receive_statics.len() as u32, has_special_header);
+        let special_header = CommandSpecialHeader::new(ctx.in_params.send_process_id, ctx.in_params.copy_handles.len() as u32, ctx.in_params.move_handles.len() as u32);
+        check_message_size(buf, get_message_size(&command_header, &special_header))?;
+
+        *(msg_buf as *mut CommandHeader) = command_header;
+        msg_buf = msg_buf.offset(cmem::size_of::<CommandHeader>() as isize);
 
         if has_special_header {
-            let special_header = ipc_buf as *mut CommandSpecialHeader;
-            *special_header = CommandSpecialHeader::new(ctx.in_params.send_process_id, ctx.in_params.copy_handles.len() as u32, ctx.in_params.move_handles.len() as u32);
-            ipc_buf = special_header.offset(1) as *mut u8;
+            *(msg_buf as *mut CommandSpecialHeader) = special_header;
+            msg_buf = msg_buf.offset(cmem::size_of::<CommandSpecialHeader>() as isize);
 
             if ctx.in_params.send_process_id {
-                ipc_buf = ipc_buf.offset(cmem::size_of::<u64>() as isize);
+                msg_buf = msg_buf.offset(cmem::size_of::<u64>() as isize);
             }
 
-            ipc_buf = write_array_to_buffer(ipc_buf, ctx.in_params.copy_handles.len() as u32, &ctx.in_params.copy_handles);
-            ipc_buf = write_array_to_buffer(ipc_buf, ctx.in_params.move_handles.len() as u32, &ctx.in_params.move_handles);
+            msg_buf = write_array_to_buffer(msg_buf, ctx.in_params.copy_handles.len() as u32, &ctx.in_params.copy_handles);
+            msg_buf = write_array_to_buffer(msg_buf, ctx.in_params.move_handles.len() as u32, &ctx.in_params.move_handles);
         }
 
-        ipc_buf = write_array_to_buffer(ipc_buf, ctx.send_statics.len() as u32, &ctx.send_statics);
-        ipc_buf = write_array_to_buffer(ipc_buf, ctx.send_buffers.len() as u32, &ctx.send_buffers);
-        ipc_buf = write_array_to_buffer(ipc_buf, ctx.receive_buffers.len() as u32, &ctx.receive_buffers);
-        ipc_buf = write_array_to_buffer(ipc_buf, ctx.exchange_buffers.len() as u32, &ctx.exchange_buffers);
-        ctx.in_params.data_words_offset = ipc_buf;
-        ipc_buf = ipc_buf.offset((cmem::size_of::<u32>() * data_word_count as usize) as isize);
-        /* ipc_buf = */ write_array_to_buffer(ipc_buf, ctx.receive_statics.len() as u32, &ctx.receive_statics);
+        msg_buf = write_array_to_buffer(msg_buf, ctx.send_statics.len() as u32, &ctx.send_statics);
+        msg_buf = write_array_to_buffer(msg_buf, ctx.send_buffers.len() as u32, &ctx.send_buffers);
+        msg_buf = write_array_to_buffer(msg_buf, ctx.receive_buffers.len() as u32, &ctx.receive_buffers);
+        msg_buf = write_array_to_buffer(msg_buf, ctx.exchange_buffers.len() as u32, &ctx.exchange_buffers);
+        ctx.in_params.data_words_offset = msg_buf;
+        msg_buf = msg_buf.offset((cmem::size_of::<u32>() * data_word_count as usize) as isize);
+        /* msg_buf = */ write_array_to_buffer(msg_buf, ctx.receive_statics.len() as u32, &ctx.receive_statics);
+        Ok(())
     }
 }
 
 #[inline(always)]
-pub fn read_command_response_from_ipc_buffer(ctx: &mut CommandContext) {
+pub fn read_command_response_from_buffer(buf: &mut [u8], ctx: &mut CommandContext) -> Result<()> {
     unsafe {
-        let mut ipc_buf = get_ipc_buffer();
-
-        let command_header = ipc_buf as *mut CommandHeader;
-        ipc_buf = command_header.offset(1) as *mut u8;
-
-        let mut copy_handle_count: u32 = 0;
-        let mut move_handle_count: u32 = 0;
-        if (*command_header).get_has_special_header() {
-            let special_header = ipc_buf as *mut CommandSpecialHeader;
-            copy_handle_count = (*special_header).get_copy_handle_count();
-            move_handle_count = (*special_header).get_move_handle_count();
-            ipc_buf = special_header.offset(1) as *mut u8;
-            if (*special_header).get_send_process_id() {
-                ctx.out_params.process_id = *(ipc_buf as *mut u64);
-                ipc_buf = ipc_buf.offset(cmem::size_of::<u64>() as isize);
-            }
+        let mut msg_buf = buf.as_mut_ptr();
+
+        check_message_size(buf, cmem::size_of::<CommandHeader>())?;
+        let command_header = *(msg_buf as *const CommandHeader);
+        msg_buf = msg_buf.offset(cmem::size_of::<CommandHeader>() as isize);
+
+        let mut special_header = CommandSpecialHeader::empty();
+        if command_header.get_has_special_header() {
+            check_message_size(buf, cmem::size_of::<CommandHeader>() + cmem::size_of::<CommandSpecialHeader>())?;
+            special_header = *(msg_buf as *const CommandSpecialHeader);
+            msg_buf = msg_buf.offset(cmem::size_of::<CommandSpecialHeader>() as isize);
         }
+        check_message_size(buf, get_message_size(&command_header, &special_header))?;
 
-        ipc_buf = read_array_from_buffer(ipc_buf, copy_handle_count, &mut ctx.out_params.copy_handles);
-        ipc_buf = read_array_from_buffer(ipc_buf, move_handle_count, &mut ctx.out_params.move_handles);
+        if special_header.get_send_process_id() {
+            // Placed right after the special header, thus only 4-byte aligned
+            ctx.out_params.process_id = ptr::read_unaligned(msg_buf as *const u64);
+            msg_buf = msg_buf.offset(cmem::size_of::<u64>() as isize);
+        }
 
-        ipc_buf = ipc_buf.offset((cmem::size_of::<SendStaticDescriptor>() * (*command_header).get_send_static_count() as usize) as isize);
-        ctx.out_params.data_words_offset = ipc_buf;
+        msg_buf = read_array_from_buffer(msg_buf, special_header.get_copy_handle_count(), &mut ctx.out_params.copy_handles);
+        msg_buf = read_array_from_buffer(msg_buf, special_header.get_move_handle_count(), &mut ctx.out_params.move_handles);
+
+        msg_buf = msg_buf.offset((cmem::size_of::<SendStaticDescriptor>() * command_header.get_send_static_count() as usize) as isize);
+        ctx.out_params.data_words_offset = msg_buf;
+        Ok(())
     }
 }
 
 #[inline(always)]
-pub fn write_request_command_on_ipc_buffer(ctx: &mut CommandContext, request_id: Option<u32>, domain_command_type: DomainCommandType) {
-    unsafe {
-        let ipc_buf = get_ipc_buffer();
+pub fn write_request_command_on_buffer(buf: &mut [u8], ctx: &mut CommandContext, request_id: Option<u32>, domain_command_type: DomainCommandType) -> Result<()> {
+    if ctx.object_info.uses_tipc_protocol() {
+        return match request_id {
+            Some(request_id) => write_tipc_request_command_on_buffer(buf, ctx, request_id),
+            // No domains (hence no domain close requests) in TIPC
+            None => Err(results::hipc::ResultUnsupportedOperation::make())
+        };
+    }
 
+    unsafe {
         let has_data_header = request_id.is_some();
         let mut data_size = DATA_PADDING + ctx.in_params.data_size;
         if has_data_header {
@@ -87,8 +104,8 @@ pub fn write_request_command_on_ipc_buffer(ctx: &mut CommandContext, request_id:
         let out_pointer_sizes_offset = data_size;
         data_size += (cmem::size_of::<u16>() * ctx.in_params.out_pointer_sizes.len()) as u32;
 
-        write_command_on_ipc_buffer(ctx, CommandType::Request, data_size);
-        let mut data_offset = get_aligned_data_offset(ctx.in_params.data_words_offset, ipc_buf);
+        write_command_on_buffer(buf, ctx, CommandType::Request, data_size)?;
+        let mut data_offset = get_aligned_data_offset(ctx.in_params.data_words_offset, buf.as_mut_ptr());
 
         let out_pointer_sizes = ctx.in_params.data_words_offset.offset(out_pointer_sizes_offset as isize);
         write_array_to_buffer(out_pointer_sizes, ctx.in_params.out_pointer_sizes.len() as u32, &ctx.in_params.out_pointer_sizes);
@@ -110,26 +127,33 @@ pub fn write_request_command_on_ipc_buffer(ctx: &mut CommandContext, request_id:
         }
 
         ctx.in_params.data_offset = data_offset;
+        Ok(())
     }
 }
 
 #[inline(always)]
-pub fn read_request_command_response_from_ipc_buffer(ctx: &mut CommandContext) -> Result<()> {
+pub fn read_request_command_response_from_buffer(buf: &mut [u8], ctx: &mut CommandContext) -> Result<()> {
+    if ctx.object_info.uses_tipc_protocol() {
+        return read_tipc_request_command_response_from_buffer(buf, ctx);
+    }
+
     unsafe {
-        let ipc_buf = get_ipc_buffer();
-        read_command_response_from_ipc_buffer(ctx);
+        read_command_response_from_buffer(buf, ctx)?;
 
-        let mut data_offset = get_aligned_data_offset(ctx.out_params.data_words_offset, ipc_buf);
+        let mut data_offset = get_aligned_data_offset(ctx.out_params.data_words_offset, buf.as_mut_ptr());
         let mut data_header = data_offset as *mut DataHeader;
         if ctx.object_info.is_domain() {
+            check_buffer_range(buf, data_offset, cmem::size_of::<DomainOutDataHeader>() + cmem::size_of::<DataHeader>())?;
             let domain_header = data_offset as *mut DomainOutDataHeader;
             data_offset = data_offset.offset(cmem::size_of::<DomainOutDataHeader>() as isize);
             let objects_offset = data_offset.offset((cmem::size_of::<DataHeader>() + ctx.out_params.data_size as usize) as isize);
             let object_count = (*domain_header).out_object_count;
+            check_buffer_range(buf, objects_offset, cmem::size_of::<DomainObjectId>() * object_count as usize)?;
             read_array_from_buffer(objects_offset, object_count, &mut ctx.out_params.objects);
             data_header = data_offset as *mut DataHeader;
         }
 
+        check_buffer_range(buf, data_header as *mut u8, cmem::size_of::<DataHeader>())?;
         data_offset = data_offset.offset(cmem::size_of::<DataHeader>() as isize);
         result_return_unless!((*data_header).magic == OUT_DATA_HEADER_MAGIC, results::cmif::ResultInvalidOutputHeader);
         result_try!(ResultCode::new((*data_header).value));
@@ -140,30 +164,32 @@ pub fn read_request_command_response_from_ipc_buffer(ctx: &mut CommandContext) -
 }
 
 #[inline(always)]
-pub fn write_control_command_on_ipc_buffer(ctx: &mut CommandContext, request_id: ControlRequestId) {
+pub fn write_control_command_on_buffer(buf: &mut [u8], ctx: &mut CommandContext, request_id: ControlRequestId) -> Result<()> {
+    // Control commands are CMIF-only
+    result_return_if!(ctx.object_info.uses_tipc_protocol(), results::hipc::ResultUnsupportedOperation);
+
     unsafe {
-        let ipc_buf = get_ipc_buffer();
         let data_size = DATA_PADDING + cmem::size_of::<DataHeader>() as u32 + ctx.in_params.data_size;
 
-        write_command_on_ipc_buffer(ctx, CommandType::Control, data_size);
-        let mut data_offset = get_aligned_data_offset(ctx.in_params.data_words_offset, ipc_buf);
+        write_command_on_buffer(buf, ctx, CommandType::Control, data_size)?;
+        let mut data_offset = get_aligned_data_offset(ctx.in_params.data_words_offset, buf.as_mut_ptr());
 
         let data_header = data_offset as *mut DataHeader;
         *data_header = DataHeader::new(IN_DATA_HEADER_MAGIC, 0, request_id as u32, 0);
 
         data_offset = data_offset.offset(cmem::size_of::<DataHeader>() as isize);
         ctx.in_params.data_offset = data_offset;
+        Ok(())
     }
 }
 
 #[inline(always)]
-pub fn read_control_command_response_from_ipc_buffer(ctx: &mut CommandContext) -> Result<()> {
+pub fn read_control_command_response_from_buffer(buf: &mut [u8], ctx: &mut CommandContext) -> Result<()> {
     unsafe {
-        let ipc_buf = get_ipc_buffer();
-
-        read_command_response_from_ipc_buffer(ctx);
-        let mut data_offset = get_aligned_data_offset(ctx.out_params.data_words_offset, ipc_buf);
+        read_command_response_from_buffer(buf, ctx)?;
+        let mut data_offset = get_aligned_data_offset(ctx.out_params.data_words_offset, buf.as_mut_ptr());
 
+        check_buffer_range(buf, data_offset, cmem::size_of::<DataHeader>())?;
         let data_header = data_offset as *mut DataHeader;
         
         data_offset = data_offset.offset(cmem::size_of::<DataHeader>() as isize);
@@ -175,14 +201,91 @@ pub fn read_control_command_response_from_ipc_buffer(ctx: &mut CommandContext) -
     }
 }
 
+// TIPC requests have no data header (nor padding): the request ID is encoded in the command type, and the data goes right after the message header
+
+#[inline(always)]
+pub fn write_tipc_request_command_on_buffer(buf: &mut [u8], ctx: &mut CommandContext, request_id: u32) -> Result<()> {
+    write_raw_command_on_buffer(buf, ctx, TIPC_REQUEST_COMMAND_TYPE_BASE + request_id, ctx.in_params.data_size)?;
+    ctx.in_params.data_offset = ctx.in_params.data_words_offset;
+    Ok(())
+}
+
+// Responses start with the result value, followed by the output data
+#[inline(always)]
+pub fn read_tipc_request_command_response_from_buffer(buf: &mut [u8], ctx: &mut CommandContext) -> Result<()> {
+    unsafe {
+        read_command_response_from_buffer(buf, ctx)?;
+
+        let data_offset = ctx.out_params.data_words_offset;
+        check_buffer_range(buf, data_offset, cmem::size_of::<u32>())?;
+        result_try!(ResultCode::new(*(data_offset as *const u32)));
+
+        ctx.out_params.data_offset = data_offset.offset(cmem::size_of::<u32>() as isize);
+        Ok(())
+    }
+}
+
+#[inline(always)]
+pub fn write_close_command_on_buffer(buf: &mut [u8], ctx: &mut CommandContext) -> Result<()> {
+    let command_type = match ctx.object_info.uses_tipc_protocol() {
+        true => CommandType::TipcClose,
+        false => CommandType::Close
+    };
+    write_command_on_buffer(buf, ctx, command_type, 0)
+}
+
+// Shorthands for the above functions using the buffer the transport places the session's messages in (the current thread's IPC buffer for actual IPC requests)
+
+#[inline(always)]
+pub fn write_command_on_ipc_buffer(ctx: &mut CommandContext, command_type: CommandType, data_size: u32) -> Result<()> {
+    write_command_on_buffer(transport::get_message_buffer_slice(ctx.object_info.handle), ctx, command_type, data_size)
+}
+
+#[inline(always)]
+pub fn read_command_response_from_ipc_buffer(ctx: &mut CommandContext) -> Result<()> {
+    read_command_response_from_buffer(transport::get_message_buffer_slice(ctx.object_info.handle), ctx)
+}
+
+#[inline(always)]
+pub fn write_request_command_on_ipc_buffer(ctx: &mut CommandContext, request_id: Option<u32>, domain_command_type: DomainCommandType) -> Result<()> {
+    write_request_command_on_buffer(transport::get_message_buffer_slice(ctx.object_info.handle), ctx, request_id, domain_command_type)
+}
+
+#[inline(always)]
+pub fn read_request_command_response_from_ipc_buffer(ctx: &mut CommandContext) -> Result<()> {
+    read_request_command_response_from_buffer(transport::get_message_buffer_slice(ctx.object_info.handle), ctx)
+}
+
+#[inline(always)]
+pub fn write_control_command_on_ipc_buffer(ctx: &mut CommandContext, request_id: ControlRequestId) -> Result<()> {
+    write_control_command_on_buffer(transport::get_message_buffer_slice(ctx.object_info.handle), ctx, request_id)
+}
+
 #[inline(always)]
-pub fn write_close_command_on_ipc_buffer(ctx: &mut CommandContext) {
-    write_command_on_ipc_buffer(ctx, CommandType::Close, 0);
+pub fn read_control_command_response_from_ipc_buffer(ctx: &mut CommandContext) -> Result<()> {
+    read_control_command_response_from_buffer(transport::get_message_buffer_slice(ctx.object_info.handle), ctx)
+}
+
+#[inline(always)]
+pub fn write_tipc_request_command_on_ipc_buffer(ctx: &mut CommandContext, request_id: u32) -> Result<()> {
+    write_tipc_request_command_on_buffer(transport::get_message_buffer_slice(ctx.object_info.handle), ctx, request_id)
+}
+
+#[inline(always)]
+pub fn read_tipc_request_command_response_from_ipc_buffer(ctx: &mut CommandContext) -> Result<()> {
+    read_tipc_request_command_response_from_buffer(transport::get_message_buffer_slice(ctx.object_info.handle), ctx)
+}
+
+#[inline(always)]
+pub fn write_close_command_on_ipc_buffer(ctx: &mut CommandContext) -> Result<()> {
+    write_close_command_on_buffer(transport::get_message_buffer_slice(ctx.object_info.handle), ctx)
 }
 
 pub trait CommandParameter<O> {
     fn before_request_write(var: &Self, walker: &mut DataWalker, ctx: &mut CommandContext) -> Result<()>;
     fn before_send_sync_request(var: &Self, walker: &mut DataWalker, ctx: &mut CommandContext) -> Result<()>;
+    // Accounts the raw output data size, which tells where domain objects are placed in the response
+    fn before_response_read(walker: &mut DataWalker, ctx: &mut CommandContext) -> Result<()>;
     fn after_response_read(walker: &mut DataWalker, ctx: &mut CommandContext) -> Result<O>;
 }
 
@@ -197,6 +300,11 @@ impl<T: Copy> CommandParameter<T> for T {
         Ok(())
     }
 
+    default fn before_response_read(walker: &mut DataWalker, _ctx: &mut CommandContext) -> Result<()> {
+        walker.advance::<Self>();
+        Ok(())
+    }
+
     default fn after_response_read(walker: &mut DataWalker, _ctx: &mut CommandContext) -> Result<Self> {
         Ok(walker.advance_get())
     }
@@ -211,6 +319,10 @@ impl<const A: BufferAttribute> CommandParameter<sf::Buffer<A>> for sf::Buffer<A>
         Ok(())
     }
 
+    fn before_response_read(_walker: &mut DataWalker, _ctx: &mut CommandContext) -> Result<()> {
+        Ok(())
+    }
+
     fn after_response_read(_walker: &mut DataWalker, _ctx: &mut CommandContext) -> Result<Self> {
         // Buffers aren't returned as output variables - the buffer sent as input (with Out attribute) will contain the output data
         Err(results::hipc::ResultUnsupportedOperation::make())
@@ -226,6 +338,10 @@ impl<const M: HandleMode> CommandParameter<sf::Handle<M>> for sf::Handle<M> {
         Ok(())
     }
 
+    fn before_response_read(_walker: &mut DataWalker, _ctx: &mut CommandContext) -> Result<()> {
+        Ok(())
+    }
+
     fn after_response_read(_walker: &mut DataWalker, ctx: &mut CommandContext) -> Result<Self> {
         ctx.out_params.pop_handle()
     }
@@ -234,12 +350,21 @@ impl<const M: HandleMode> CommandParameter<sf::Handle<M>> for sf::Handle<M> {
 impl CommandParameter<sf::ProcessId> for sf::ProcessId {
     fn before_request_write(_process_id: &Self, walker: &mut DataWalker, ctx: &mut CommandContext) -> Result<()> {
         ctx.in_params.send_process_id = true;
-        walker.advance::<u64>();
+        // TIPC requests don't reserve space for the process ID in the data, only the kernel-written one is sent
+        if !ctx.object_info.uses_tipc_protocol() {
+            walker.advance::<u64>();
+        }
         Ok(())
     }
 
-    fn before_send_sync_request(process_id: &Self, walker: &mut DataWalker, _ctx: &mut CommandContext) -> Result<()> {
-        walker.advance_set(process_id.process_id);
+    fn before_send_sync_request(process_id: &Self, walker: &mut DataWalker, ctx: &mut CommandContext) -> Result<()> {
+        if !ctx.object_info.uses_tipc_protocol() {
+            walker.advance_set(process_id.process_id);
+        }
+        Ok(())
+    }
+
+    fn before_response_read(_walker: &mut DataWalker, _ctx: &mut CommandContext) -> Result<()> {
         Ok(())
     }
 
@@ -251,29 +376,37 @@ impl CommandParameter<sf::ProcessId> for sf::ProcessId {
 
 impl CommandParameter<mem::Shared<dyn sf::IObject>> for mem::Shared<dyn sf::IObject> {
     fn before_request_write(session: &Self, _walker: &mut DataWalker, ctx: &mut CommandContext) -> Result<()> {
-        ctx.in_params.add_object(session.get().get_info())
+        ctx.add_object(session.get().get_info())
     }
 
     fn before_send_sync_request(_session: &Self, _walker: &mut DataWalker, _ctx: &mut CommandContext) -> Result<()> {
         Ok(())
     }
 
+    fn before_response_read(_walker: &mut DataWalker, _ctx: &mut CommandContext) -> Result<()> {
+        Ok(())
+    }
+
     fn after_response_read(_walker: &mut DataWalker, _ctx: &mut CommandContext) -> Result<Self> {
         // Only supported when the IObject type is known (see the generic implementation below)
         Err(results::hipc::ResultUnsupportedOperation::make())
     }
 }
 
-impl<S: service::IClientObject + 'static> CommandParameter<mem::Shared<dyn sf::IObject>> for mem::Shared<S> {
+impl<S: service::IClientObject + 'static> CommandParameter<mem::Shared<S>> for mem::Shared<S> {
     fn before_request_write(session: &Self, _walker: &mut DataWalker, ctx: &mut CommandContext) -> Result<()> {
-        ctx.in_params.add_object(session.get().get_info())
+        ctx.add_object(session.get().get_info())
     }
 
     fn before_send_sync_request(_session: &Self, _walker: &mut DataWalker, _ctx: &mut CommandContext) -> Result<()> {
         Ok(())
     }
 
-    fn after_response_read(_walker: &mut DataWalker, ctx: &mut CommandContext) -> Result<mem::Shared<dyn sf::IObject>> {
+    fn before_response_read(_walker: &mut DataWalker, _ctx: &mut CommandContext) -> Result<()> {
+        Ok(())
+    }
+
+    fn after_response_read(_walker: &mut DataWalker, ctx: &mut CommandContext) -> Result<Self> {
         let object_info = ctx.pop_object()?;
         Ok(mem::Shared::new(S::new(sf::Session::from(object_info))))
     }
@@ -0,0 +1,286 @@
+use crate::result::*;
+use crate::results;
+use crate::svc;
+use crate::sync;
+use crate::mem;
+use crate::service;
+use crate::ipc;
+use crate::ipc::sf;
+use crate::ipc::server;
+use crate::ipc::transport;
+use core::cell::UnsafeCell;
+use core::sync::atomic::AtomicU32;
+use core::sync::atomic::Ordering;
+
+// In-process transport: requests sent to loopback sessions are dispatched right away by a ServerManager on the calling thread, without any kernel involvement
+// Loopback sessions place their messages in a buffer of their own instead of the thread's IPC buffer, so they work without a kernel TLS too (like on a host, in tests)
+// That buffer is shared by all loopback sessions, thus clients must be used from a single thread, and server commands can't make loopback requests themselves
+
+// Kernel handles never have bit 30 set, so loopback handles can't collide with actual ones
+const LOOPBACK_HANDLE_BASE: svc::Handle = 0x4000_0000;
+
+static G_NEXT_HANDLE_ID: AtomicU32 = AtomicU32::new(0);
+
+// Loopback handles aren't kernel objects, so this is the only trace of sessions which never get closed
+static G_OPEN_HANDLE_COUNT: AtomicU32 = AtomicU32::new(0);
+
+const MAX_SESSION_COUNT: usize = 0x40;
+
+struct LoopbackState {
+    manager: server::ServerManager<MAX_SESSION_COUNT, 0>
+}
+
+// The state is only ever accessed from the thread driving both the client and the server
+unsafe impl Send for LoopbackState {}
+
+static G_STATE: sync::Mutex<Option<LoopbackState>> = sync::Mutex::new(None);
+
+// Aligned like the IPC buffer in the TLS, since messages are read/written in place
+#[repr(C, align(0x10))]
+struct MessageBuffer(UnsafeCell<[u8; transport::MESSAGE_BUFFER_SIZE]>);
+
+// Only ever accessed from the thread using loopback sessions, like the state
+unsafe impl Sync for MessageBuffer {}
+
+static G_MESSAGE_BUFFER: MessageBuffer = MessageBuffer(UnsafeCell::new([0; transport::MESSAGE_BUFFER_SIZE]));
+
+// Server handles are even and client handles are odd, so each client handle maps to its server handle
+pub const fn is_loopback_handle(handle: svc::Handle) -> bool {
+    (handle & LOOPBACK_HANDLE_BASE) != 0
+}
+
+pub const fn is_client_handle(handle: svc::Handle) -> bool {
+    is_loopback_handle(handle) && ((handle & 1) != 0)
+}
+
+pub const fn get_server_handle(client_handle: svc::Handle) -> svc::Handle {
+    client_handle & !1
+}
+
+fn loopback_send_sync_request(handle: svc::Handle) -> Result<()> {
+    if !is_loopback_handle(handle) {
+        return svc::send_sync_request(handle);
+    }
+    result_return_unless!(is_client_handle(handle), results::os::ResultInvalidHandle);
+
+    // Fails (instead of deadlocking) if a server command is already being processed
+    match G_STATE.try_lock() {
+        Some(state) => match *state {
+            Some(ref state) => state.manager.process_loopback_request(get_server_handle(handle)),
+            None => Err(results::hipc::ResultSessionClosed::make())
+        },
+        None => Err(results::os::ResultInvalidState::make())
+    }
+}
+
+fn loopback_create_session() -> Result<(svc::Handle, svc::Handle)> {
+    let handle_id = G_NEXT_HANDLE_ID.fetch_add(1, Ordering::SeqCst);
+    let server_handle = LOOPBACK_HANDLE_BASE | ((handle_id << 1) & !LOOPBACK_HANDLE_BASE);
+    G_OPEN_HANDLE_COUNT.fetch_add(2, Ordering::SeqCst);
+    Ok((server_handle, server_handle | 1))
+}
+
+fn loopback_close_handle(handle: svc::Handle) -> Result<()> {
+    match is_loopback_handle(handle) {
+        true => {
+            G_OPEN_HANDLE_COUNT.fetch_sub(1, Ordering::SeqCst);
+            Ok(())
+        },
+        false => svc::close_handle(handle)
+    }
+}
+
+fn loopback_get_message_buffer(handle: svc::Handle) -> *mut u8 {
+    match is_loopback_handle(handle) {
+        true => G_MESSAGE_BUFFER.0.get() as *mut u8,
+        false => ipc::get_ipc_buffer()
+    }
+}
+
+pub static LOOPBACK_TRANSPORT: transport::Transport = transport::Transport { send_sync_request_fn: loopback_send_sync_request, create_session_fn: loopback_create_session, close_handle_fn: loopback_close_handle, get_message_buffer_fn: loopback_get_message_buffer };
+
+pub fn initialize() -> Result<()> {
+    *G_STATE.lock() = Some(LoopbackState { manager: server::ServerManager::new()? });
+    transport::set_transport(&LOOPBACK_TRANSPORT);
+    Ok(())
+}
+
+pub fn is_initialized() -> bool {
+    G_STATE.lock().is_some()
+}
+
+pub fn get_open_handle_count() -> u32 {
+    G_OPEN_HANDLE_COUNT.load(Ordering::SeqCst)
+}
+
+// Client objects obtained through connect() should be dropped before finalizing
+pub fn finalize() {
+    let state = G_STATE.lock().take();
+    // Server objects get dropped outside the lock, since closing their sessions goes through the transport again
+    drop(state);
+    transport::reset_transport();
+}
+
+// Creates a new session served by the given server object, and returns a client object for it
+pub fn connect<S: server::IServerObject + 'static, C: service::IClientObject + 'static>() -> Result<mem::Shared<C>> {
+    let client_handle = match *G_STATE.lock() {
+        Some(ref state) => {
+            let (server_handle, client_handle) = loopback_create_session()?;
+            // The server handle gets closed along with the server object if this fails
+            if let Err(rc) = state.manager.register_session::<S>(server_handle) {
+                let _ = loopback_close_handle(client_handle);
+                return Err(rc);
+            }
+            client_handle
+        },
+        None => return Err(results::hipc::ResultSessionClosed::make())
+    };
+    Ok(mem::Shared::new(C::new(sf::Session::from_handle(client_handle))))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ipc::sf::IObject;
+    use crate::service::IClientObject;
+
+    // Same interfaces as tests/server-ipc/simple-service, served and used in this process
+
+    #[sf::ipc_interface(client = "DemoSubInterface")]
+    pub trait IDemoSubInterface {
+        #[ipc_rid(246)]
+        fn sample_cmd_1(&mut self, input: u32) -> Result<u32>;
+    }
+
+    #[sf::ipc_interface(client = "DemoService")]
+    pub trait IDemoService {
+        #[ipc_rid(123)]
+        fn open_sub_interface(&mut self, value: u32, pid: sf::ProcessId) -> Result<mem::Shared<DemoSubInterface>>;
+    }
+
+    pub struct DemoSubInterfaceServer {
+        session: sf::Session,
+        value: u32
+    }
+
+    impl IDemoSubInterface for DemoSubInterfaceServer {
+        fn sample_cmd_1(&mut self, input: u32) -> Result<u32> {
+            Ok(input * self.value)
+        }
+    }
+
+    impl sf::IObject for DemoSubInterfaceServer {
+        fn get_session(&mut self) -> &mut sf::Session {
+            &mut self.session
+        }
+
+        fn get_command_table(&self) -> sf::CommandMetadataTable {
+            Self::make_command_table()
+        }
+    }
+
+    pub struct DemoServiceServer {
+        session: sf::Session
+    }
+
+    impl IDemoService for DemoServiceServer {
+        type DemoSubInterface = DemoSubInterfaceServer;
+
+        fn open_sub_interface(&mut self, value: u32, _pid: sf::ProcessId) -> Result<mem::Shared<DemoSubInterfaceServer>> {
+            result_return_if!(value == 0, results::hipc::ResultUnsupportedOperation);
+            Ok(mem::Shared::new(DemoSubInterfaceServer { session: sf::Session::new(), value: value }))
+        }
+    }
+
+    impl sf::IObject for DemoServiceServer {
+        fn get_session(&mut self) -> &mut sf::Session {
+            &mut self.session
+        }
+
+        fn get_command_table(&self) -> sf::CommandMetadataTable {
+            Self::make_command_table()
+        }
+    }
+
+    impl server::IServerObject for DemoServiceServer {
+        fn new(session: sf::Session) -> Self {
+            Self { session: session }
+        }
+    }
+
+    fn use_demo_service(as_domain: bool) -> Result<()> {
+        let demo_srv = connect::<DemoServiceServer, DemoService>()?;
+        if as_domain {
+            demo_srv.get().convert_to_domain()?;
+        }
+
+        let sub_intf = demo_srv.get().open_sub_interface(15, sf::ProcessId::new())?;
+        assert_eq!(sub_intf.get().get_session().object_info.is_domain(), as_domain);
+        assert_eq!(sub_intf.get().sample_cmd_1(21)?, 315);
+
+        // Sub-interfaces are independent objects
+        let other_sub_intf = demo_srv.get().open_sub_interface(2, sf::ProcessId::new())?;
+        assert_eq!(other_sub_intf.get().sample_cmd_1(21)?, 42);
+        assert_eq!(sub_intf.get().sample_cmd_1(3)?, 45);
+
+        // Errors are sent back as they are
+        assert!(results::hipc::ResultUnsupportedOperation::matches(demo_srv.get().open_sub_interface(0, sf::ProcessId::new()).err().unwrap()));
+        Ok(())
+    }
+
+    // Clones and domain copies are sessions of their own to the same objects, closed along with their clients
+    fn clone_demo_sub_interface(as_domain: bool) -> Result<()> {
+        let handle_count = get_open_handle_count();
+        let demo_srv = connect::<DemoServiceServer, DemoService>()?;
+        if as_domain {
+            demo_srv.get().convert_to_domain()?;
+        }
+        let sub_intf = demo_srv.get().open_sub_interface(15, sf::ProcessId::new())?;
+
+        {
+            let mut sub_intf_clone = DemoSubInterface::new(sub_intf.get().get_session().clone_current_object()?);
+            assert_eq!(sub_intf_clone.get_session().object_info.is_domain(), as_domain);
+            assert_eq!(sub_intf_clone.sample_cmd_1(2)?, 30);
+
+            let mut sub_intf_clone_ex = DemoSubInterface::new(sub_intf.get().get_session().clone_current_object_ex(0)?);
+            assert_eq!(sub_intf_clone_ex.sample_cmd_1(3)?, 45);
+
+            match as_domain {
+                true => {
+                    let mut sub_intf_copy = DemoSubInterface::new(sub_intf.get().get_session().copy_from_current_domain()?);
+                    assert!(!sub_intf_copy.get_session().object_info.is_domain());
+                    assert_eq!(sub_intf_copy.sample_cmd_1(4)?, 60);
+                },
+                false => assert!(results::hipc::ResultTargetNotDomain::matches(sub_intf.get().get_session().copy_from_current_domain().err().unwrap()))
+            };
+        }
+
+        // Closing them leaves the original sessions (and objects) alone
+        assert_eq!(sub_intf.get().sample_cmd_1(5)?, 75);
+        drop(sub_intf);
+        drop(demo_srv);
+        assert_eq!(get_open_handle_count(), handle_count);
+        Ok(())
+    }
+
+    // The only test using the (global) loopback state, which would otherwise be shared by tests running in parallel
+    #[test]
+    fn simple_service() {
+        assert!(!is_initialized());
+        assert!(results::hipc::ResultSessionClosed::matches(connect::<DemoServiceServer, DemoService>().err().unwrap()));
+
+        initialize().unwrap();
+        assert!(is_initialized());
+        use_demo_service(false).unwrap();
+        use_demo_service(true).unwrap();
+        clone_demo_sub_interface(false).unwrap();
+        clone_demo_sub_interface(true).unwrap();
+
+        // Client handles only
+        assert!(results::os::ResultInvalidHandle::matches(loopback_send_sync_request(LOOPBACK_HANDLE_BASE).unwrap_err()));
+
+        finalize();
+        assert!(!is_initialized());
+        assert_eq!(get_open_handle_count(), 0);
+    }
+}
\ No newline at end of file
@@ -18,25 +18,36 @@ pub enum ControlRequestId {
 
 pub type DomainObjectId = u32;
 
+// CMIF is the regular protocol, while TIPC is the simplified one used by some services (like sm) since 12.0.0
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+#[repr(u8)]
+pub enum CommandProtocol {
+    Cmif,
+    Tipc
+}
+
 #[derive(Copy, Clone)]
 #[repr(C)]
 pub struct ObjectInfo {
     pub handle: svc::Handle,
     pub domain_object_id: DomainObjectId,
-    pub owns_handle: bool
+    pub owns_handle: bool,
+    pub protocol: CommandProtocol,
+    // Cached once queried (see get_pointer_buffer_size())
+    pub pointer_buffer_size: Option<u16>
 }
 
 impl ObjectInfo {
     pub const fn new() -> Self {
-        Self { handle: 0, domain_object_id: 0, owns_handle: false }
+        Self { handle: 0, domain_object_id: 0, owns_handle: false, protocol: CommandProtocol::Cmif, pointer_buffer_size: None }
     }
 
     pub const fn from_handle(handle: svc::Handle) -> Self {
-        Self { handle: handle, domain_object_id: 0, owns_handle: true }
+        Self { handle: handle, domain_object_id: 0, owns_handle: true, protocol: CommandProtocol::Cmif, pointer_buffer_size: None }
     }
 
     pub const fn from_domain_object_id(parent_handle: svc::Handle, domain_object_id: DomainObjectId) -> Self {
-        Self { handle: parent_handle, domain_object_id: domain_object_id, owns_handle: false }
+        Self { handle: parent_handle, domain_object_id: domain_object_id, owns_handle: false, protocol: CommandProtocol::Cmif, pointer_buffer_size: None }
     }
 
     pub const fn is_valid(&self) -> bool {
@@ -47,13 +58,57 @@ impl ObjectInfo {
         self.domain_object_id != 0
     }
 
+    pub const fn uses_tipc_protocol(&self) -> bool {
+        matches!(self.protocol, CommandProtocol::Tipc)
+    }
+
     pub fn convert_current_object_to_domain(&mut self) -> Result<DomainObjectId> {
         ipc_client_send_control_command!([*self; ControlRequestId::ConvertCurrentObjectToDomain] () => (domain_object_id: DomainObjectId))
     }
 
+    pub fn copy_from_current_domain(&mut self, domain_object_id: DomainObjectId) -> Result<sf::MoveHandle> {
+        ipc_client_send_control_command!([*self; ControlRequestId::CopyFromCurrentDomain] (domain_object_id) => (handle: sf::MoveHandle))
+    }
+
+    pub fn clone_current_object(&mut self) -> Result<sf::MoveHandle> {
+        ipc_client_send_control_command!([*self; ControlRequestId::CloneCurrentObject] () => (handle: sf::MoveHandle))
+    }
+
+    pub fn clone_current_object_ex(&mut self, tag: u32) -> Result<sf::MoveHandle> {
+        ipc_client_send_control_command!([*self; ControlRequestId::CloneCurrentObjectEx] (tag) => (handle: sf::MoveHandle))
+    }
+
+    // Clones talk to the same (domain) object through their own session handle
+    pub const fn with_cloned_handle(&self, handle: svc::Handle) -> Self {
+        Self { handle: handle, domain_object_id: self.domain_object_id, owns_handle: true, protocol: self.protocol, pointer_buffer_size: self.pointer_buffer_size }
+    }
+
     pub fn query_pointer_buffer_size(&mut self) -> Result<u16> {
         ipc_client_send_control_command!([*self; ControlRequestId::QueryPointerBufferSize] () => (pointer_buffer_size: u16))
     }
+
+    // Only queried the first time, TIPC sessions have no pointer buffer
+    pub fn get_pointer_buffer_size(&mut self) -> Result<u16> {
+        if self.uses_tipc_protocol() {
+            return Ok(0);
+        }
+
+        match self.pointer_buffer_size {
+            Some(pointer_buffer_size) => Ok(pointer_buffer_size),
+            None => {
+                let pointer_buffer_size = self.query_pointer_buffer_size()?;
+                self.pointer_buffer_size = Some(pointer_buffer_size);
+                Ok(pointer_buffer_size)
+            }
+        }
+    }
+}
+
+// The kernel places each pointer buffer at this alignment in the receiver's pointer buffer
+pub const POINTER_BUFFER_ALIGNMENT: usize = 0x10;
+
+pub const fn get_aligned_pointer_buffer_size(size: usize) -> usize {
+    (size + POINTER_BUFFER_ALIGNMENT - 1) & !(POINTER_BUFFER_ALIGNMENT - 1)
 }
 
 #[derive(Copy, Clone, PartialEq, Eq, Debug)]
@@ -85,22 +140,20 @@ impl BufferDescriptor {
         Self { size_low: 0, address_low: 0, bits: 0 }
     }
 
-    pub const fn new(buffer: *const u8, buffer_size: usize, flags: BufferFlags) -> Self {
-        unsafe {
-            let address_low = buffer as usize as u32;
-            let address_mid = ((buffer as usize) >> 32) as u32;
-            let address_high = ((buffer as usize) >> 36) as u32;
-            let size_low = buffer_size as u32;
-            let size_high = (buffer_size >> 32) as u32;
+    pub fn new(buffer: *const u8, buffer_size: usize, flags: BufferFlags) -> Self {
+        let address_low = buffer as usize as u32;
+        let address_mid = ((buffer as usize) >> 32) as u32;
+        let address_high = ((buffer as usize) >> 36) as u32;
+        let size_low = buffer_size as u32;
+        let size_high = (buffer_size >> 32) as u32;
 
-            let mut bits: u32 = 0;
-            write_bits!(0, 1, bits, flags as u32);
-            write_bits!(2, 23, bits, address_high);
-            write_bits!(24, 27, bits, size_high);
-            write_bits!(28, 31, bits, address_mid);
+        let mut bits: u32 = 0;
+        write_bits!(0, 1, bits, flags as u32);
+        write_bits!(2, 23, bits, address_high);
+        write_bits!(24, 27, bits, size_high);
+        write_bits!(28, 31, bits, address_mid);
 
-            Self { size_low: size_low, address_low: address_low, bits: bits }
-        }
+        Self { size_low: size_low, address_low: address_low, bits: bits }
     }
 
     pub const fn get_address(&self) -> *mut u8 {
@@ -127,20 +180,18 @@ impl SendStaticDescriptor {
         Self { bits: 0, address_low: 0 }
     }
 
-    pub const fn new(buffer: *const u8, buffer_size: usize, index: u32) -> Self {
-        unsafe {
-            let address_low = buffer as usize as u32;
-            let address_mid = ((buffer as usize) >> 32) as u32;
-            let address_high = ((buffer as usize) >> 36) as u32;
+    pub fn new(buffer: *const u8, buffer_size: usize, index: u32) -> Self {
+        let address_low = buffer as usize as u32;
+        let address_mid = ((buffer as usize) >> 32) as u32;
+        let address_high = ((buffer as usize) >> 36) as u32;
 
-            let mut bits: u32 = 0;
-            write_bits!(0, 5, bits, index);
-            write_bits!(6, 11, bits, address_high);
-            write_bits!(12, 15, bits, address_mid);
-            write_bits!(16, 31, bits, buffer_size as u32);
+        let mut bits: u32 = 0;
+        write_bits!(0, 5, bits, index);
+        write_bits!(6, 11, bits, address_high);
+        write_bits!(12, 15, bits, address_mid);
+        write_bits!(16, 31, bits, buffer_size as u32);
 
-            Self { bits: bits, address_low: address_low }
-        }
+        Self { bits: bits, address_low: address_low }
     }
 
     pub const fn get_address(&self) -> *mut u8 {
@@ -166,17 +217,15 @@ impl ReceiveStaticDescriptor {
         Self { address_low: 0, bits: 0 }
     }
 
-    pub const fn new(buffer: *const u8, buffer_size: usize) -> Self {
-        unsafe {
-            let address_low = buffer as usize as u32;
-            let address_high = ((buffer as usize) >> 32) as u32;
+    pub fn new(buffer: *const u8, buffer_size: usize) -> Self {
+        let address_low = buffer as usize as u32;
+        let address_high = ((buffer as usize) >> 32) as u32;
 
-            let mut bits: u32 = 0;
-            write_bits!(0, 15, bits, address_high);
-            write_bits!(16, 31, bits, buffer_size as u32);
+        let mut bits: u32 = 0;
+        write_bits!(0, 15, bits, address_high);
+        write_bits!(16, 31, bits, buffer_size as u32);
 
-            Self { address_low: address_low, bits: bits }
-        }
+        Self { address_low: address_low, bits: bits }
     }
 
     pub const fn get_address(&self) -> *mut u8 {
@@ -199,9 +248,14 @@ pub enum CommandType {
     Request = 4,
     Control = 5,
     RequestWithContext = 6,
-    ControlWithContext = 7
+    ControlWithContext = 7,
+    TipcClose = 15,
+    // TIPC requests are encoded as 16 + <request ID>, this value stands for all of them
+    TipcRequest = 16
 }
 
+pub const TIPC_REQUEST_COMMAND_TYPE_BASE: u32 = CommandType::TipcRequest as u32;
+
 #[derive(Copy, Clone)]
 #[repr(C)]
 pub struct CommandHeader {
@@ -239,8 +293,12 @@ impl CommandHeader {
     }
 
     pub const fn new(command_type: CommandType, send_static_count: u32, send_buffer_count: u32, receive_buffer_count: u32, exchange_buffer_count: u32, data_word_count: u32, receive_static_count: u32, has_special_header: bool) -> Self {
+        Self::new_raw(command_type as u32, send_static_count, send_buffer_count, receive_buffer_count, exchange_buffer_count, data_word_count, receive_static_count, has_special_header)
+    }
+
+    pub const fn new_raw(raw_command_type: u32, send_static_count: u32, send_buffer_count: u32, receive_buffer_count: u32, exchange_buffer_count: u32, data_word_count: u32, receive_static_count: u32, has_special_header: bool) -> Self {
         let mut bits_1: u32 = 0;
-        write_bits!(0, 15, bits_1, command_type as u32);
+        write_bits!(0, 15, bits_1, raw_command_type);
         write_bits!(16, 19, bits_1, send_static_count);
         write_bits!(20, 23, bits_1, send_buffer_count);
         write_bits!(24, 27, bits_1, receive_buffer_count);
@@ -254,13 +312,25 @@ impl CommandHeader {
         Self { bits_1: bits_1, bits_2: bits_2 }
     }
 
+    pub const fn get_raw_command_type(&self) -> u32 {
+        read_bits!(0, 15, self.bits_1)
+    }
+
     pub const fn get_command_type(&self) -> CommandType {
-        let raw_type = read_bits!(0, 15, self.bits_1);
-        unsafe {
-            mem::transmute(raw_type as u16)
+        let raw_type = self.get_raw_command_type();
+        match raw_type {
+            0..=7 | 15 => unsafe {
+                mem::transmute(raw_type as u16)
+            },
+            _ if raw_type >= TIPC_REQUEST_COMMAND_TYPE_BASE => CommandType::TipcRequest,
+            _ => CommandType::Invalid
         }
     }
 
+    pub const fn get_tipc_request_id(&self) -> u32 {
+        self.get_raw_command_type() - TIPC_REQUEST_COMMAND_TYPE_BASE
+    }
+
     pub const fn get_send_static_count(&self) -> u32 {
         read_bits!(16, 19, self.bits_1)
     }
@@ -518,12 +588,32 @@ impl CommandIn {
         }
     }
 
-    pub fn add_object(&mut self, object_info: ObjectInfo) -> Result<()> {
-        if object_info.is_domain() {
-            self.add_domain_object(object_info.domain_object_id)
+    pub fn pop_copy_handle(&mut self) -> Result<svc::Handle> {
+        match self.copy_handles.pop_at(0) {
+            Some(handle) => Ok(handle),
+            None => Err(results::cmif::ResultInvalidInObjectCount::make())
         }
-        else {
-            Err(ResultCode::new(0xCCC))
+    }
+
+    pub fn pop_move_handle(&mut self) -> Result<svc::Handle> {
+        match self.move_handles.pop_at(0) {
+            Some(handle) => Ok(handle),
+            None => Err(results::cmif::ResultInvalidInObjectCount::make())
+        }
+    }
+
+    pub fn pop_handle<const M: HandleMode>(&mut self) -> Result<sf::Handle<M>> {
+        let handle = match M {
+            HandleMode::Copy => sf::Handle::from(self.pop_copy_handle()?),
+            HandleMode::Move => sf::Handle::from(self.pop_move_handle()?),
+        };
+        Ok(handle)
+    }
+
+    pub fn pop_domain_object(&mut self) -> Result<DomainObjectId> {
+        match self.objects.pop_at(0) {
+            Some(domain_object_id) => Ok(domain_object_id),
+            None => Err(results::cmif::ResultInvalidInObjectCount::make())
         }
     }
 
@@ -617,12 +707,14 @@ pub struct CommandContext {
     receive_statics: ArrayVec<[ReceiveStaticDescriptor; MAX_COUNT]>,
     send_buffers: ArrayVec<[BufferDescriptor; MAX_COUNT]>,
     receive_buffers: ArrayVec<[BufferDescriptor; MAX_COUNT]>,
-    exchange_buffers: ArrayVec<[BufferDescriptor; MAX_COUNT]>
+    exchange_buffers: ArrayVec<[BufferDescriptor; MAX_COUNT]>,
+    // Space of the server's pointer buffer used by the pointer buffers added so far
+    pointer_buffer_usage: usize
 }
 
 impl CommandContext {
     pub fn empty() -> Self {
-        Self { object_info: ObjectInfo::new(), in_params: CommandIn::empty(), out_params: CommandOut::empty(), send_statics: ArrayVec::new(), receive_statics: ArrayVec::new(), send_buffers: ArrayVec::new(), receive_buffers: ArrayVec::new(), exchange_buffers: ArrayVec::new() }
+        Self { object_info: ObjectInfo::new(), in_params: CommandIn::empty(), out_params: CommandOut::empty(), send_statics: ArrayVec::new(), receive_statics: ArrayVec::new(), send_buffers: ArrayVec::new(), receive_buffers: ArrayVec::new(), exchange_buffers: ArrayVec::new(), pointer_buffer_usage: 0 }
     }
 
     pub fn new(object_info: ObjectInfo) -> Self {
@@ -670,9 +762,17 @@ impl CommandContext {
         let is_in = A.contains(BufferAttribute::In());
         let is_out = A.contains(BufferAttribute::Out());
 
-        if A.contains(BufferAttribute::AutoSelect()) {
-            let pointer_buf_size = self.object_info.query_pointer_buffer_size()?;
-            let buffer_in_static = (pointer_buf_size > 0) && (buffer.size <= pointer_buf_size as usize);
+        // TIPC has no pointer buffers, so auto-select buffers are always sent as map-alias ones
+        let is_tipc = self.object_info.uses_tipc_protocol();
+        result_return_if!(is_tipc && A.contains(BufferAttribute::Pointer()), results::hipc::ResultUnsupportedOperation);
+
+        if A.contains(BufferAttribute::AutoSelect()) && !is_tipc {
+            // Sent as a pointer buffer if it fits in what's left of the server's pointer buffer, otherwise as a map-alias one
+            let pointer_buf_size = self.object_info.get_pointer_buffer_size()? as usize;
+            let buffer_in_static = (buffer.size > 0) && ((self.pointer_buffer_usage + buffer.size) <= pointer_buf_size);
+            if buffer_in_static {
+                self.pointer_buffer_usage += get_aligned_pointer_buffer_size(buffer.size);
+            }
             if is_in {
                 if buffer_in_static {
                     self.add_send_buffer(BufferDescriptor::new(ptr::null(), 0, BufferFlags::Normal))?;
@@ -697,6 +797,8 @@ impl CommandContext {
             }
         }
         else if A.contains(BufferAttribute::Pointer()) {
+            result_return_if!(buffer.size > u16::MAX as usize, results::hipc::ResultPointerBufferTooSmall);
+            self.pointer_buffer_usage += get_aligned_pointer_buffer_size(buffer.size);
             if is_in {
                 self.add_send_static(SendStaticDescriptor::new(buffer.buf, buffer.size, self.send_statics.len() as u32))?;
             }
@@ -707,7 +809,7 @@ impl CommandContext {
                 }
             }
         }
-        else if A.contains(BufferAttribute::MapAlias()) {
+        else if A.contains(BufferAttribute::MapAlias()) || A.contains(BufferAttribute::AutoSelect()) {
             let mut flags = BufferFlags::Normal;
             if A.contains(BufferAttribute::MapTransferAllowsNonSecure()) {
                 flags = BufferFlags::NonSecure;
@@ -768,8 +870,9 @@ impl CommandContext {
     pub fn pop_buffer<const A: BufferAttribute>(&mut self) -> Result<sf::Buffer<A>> {
         let is_in = A.contains(BufferAttribute::In());
         let is_out = A.contains(BufferAttribute::Out());
+        let is_tipc = self.object_info.uses_tipc_protocol();
 
-        if A.contains(BufferAttribute::AutoSelect()) {
+        if A.contains(BufferAttribute::AutoSelect()) && !is_tipc {
             if is_in {
                 if let Ok(static_desc) = self.pop_send_static() {
                     if let Ok(send_desc) = self.pop_send_buffer() {
@@ -807,7 +910,7 @@ impl CommandContext {
                 }
             }
         }
-        else if A.contains(BufferAttribute::MapAlias()) {
+        else if A.contains(BufferAttribute::MapAlias()) || A.contains(BufferAttribute::AutoSelect()) {
             if is_in && is_out {
                 if let Ok(exch_desc) = self.pop_exchange_buffer() {
                     return Ok(sf::Buffer::from_mut(exch_desc.get_address(), exch_desc.get_size()));
@@ -828,15 +931,26 @@ impl CommandContext {
         Err(ResultCode::new(0xBABE))
     }
 
+    // Objects can only be sent to the domain they belong to
+    pub fn add_object(&mut self, object_info: ObjectInfo) -> Result<()> {
+        result_return_unless!(self.object_info.is_domain() && object_info.is_domain(), results::hipc::ResultTargetNotDomain);
+        result_return_unless!(object_info.handle == self.object_info.handle, results::cmif::ResultInvalidInObject);
+        self.in_params.add_domain_object(object_info.domain_object_id)
+    }
+
     pub fn pop_object(&mut self) -> Result<ObjectInfo> {
-        let object_info: ObjectInfo;
+        let mut object_info: ObjectInfo;
         if self.object_info.is_domain() {
             let domain_object_id = self.out_params.pop_domain_object()?;
             object_info = ObjectInfo::from_domain_object_id(self.object_info.handle, domain_object_id);
+            // Domain objects share their parent's session, and thus its pointer buffer
+            object_info.pointer_buffer_size = self.object_info.pointer_buffer_size;
         }
         else {
             let handle: sf::MoveHandle = self.out_params.pop_handle()?;
             object_info = ObjectInfo::from_handle(handle.handle);
+            // Sub-objects talk the same protocol as their parent
+            object_info.protocol = self.object_info.protocol;
         }
         Ok(object_info)
     }
@@ -849,6 +963,50 @@ pub fn get_ipc_buffer() -> *mut u8 {
     }
 }
 
+#[inline(always)]
+pub fn get_ipc_buffer_slice() -> &'static mut [u8] {
+    unsafe {
+        &mut (*thread::get_thread_local_storage()).ipc_buffer
+    }
+}
+
+// Total size of a message with the given headers, used to validate messages before encoding/decoding them
+pub const fn get_message_size(command_header: &CommandHeader, special_header: &CommandSpecialHeader) -> usize {
+    let mut size = mem::size_of::<CommandHeader>();
+    if command_header.get_has_special_header() {
+        size += mem::size_of::<CommandSpecialHeader>();
+        if special_header.get_send_process_id() {
+            size += mem::size_of::<u64>();
+        }
+        size += mem::size_of::<svc::Handle>() * (special_header.get_copy_handle_count() + special_header.get_move_handle_count()) as usize;
+    }
+    size += mem::size_of::<SendStaticDescriptor>() * command_header.get_send_static_count() as usize;
+    size += mem::size_of::<BufferDescriptor>() * (command_header.get_send_buffer_count() + command_header.get_receive_buffer_count() + command_header.get_exchange_buffer_count()) as usize;
+    size += mem::size_of::<u32>() * command_header.get_data_word_count() as usize;
+
+    // 0xFF means a single receive static (the whole pointer buffer)
+    let receive_static_count = match command_header.get_receive_static_count() {
+        0xFF => 1,
+        count => count
+    };
+    size += mem::size_of::<ReceiveStaticDescriptor>() * receive_static_count as usize;
+    size
+}
+
+#[inline(always)]
+pub fn check_message_size(buf: &[u8], size: usize) -> Result<()> {
+    result_return_unless!(size <= buf.len(), results::hipc::ResultInvalidRequestSize);
+    Ok(())
+}
+
+#[inline(always)]
+pub fn check_buffer_range(buf: &[u8], offset: *mut u8, size: usize) -> Result<()> {
+    let buf_start = buf.as_ptr() as usize;
+    let buf_end = buf_start + buf.len();
+    result_return_unless!((offset as usize >= buf_start) && (offset as usize + size <= buf_end), results::hipc::ResultInvalidRequestSize);
+    Ok(())
+}
+
 #[inline(always)]
 pub fn read_array_from_buffer<T: Copy>(buffer: *mut u8, count: u32, array: &mut ArrayVec<[T; MAX_COUNT]>) -> *mut u8 {
     unsafe {
@@ -869,16 +1027,21 @@ pub fn write_array_to_buffer<T: Copy>(buffer: *mut u8, count: u32, array: &Array
 }
 
 #[inline(always)]
-pub const fn get_aligned_data_offset(data_words_offset: *mut u8, base_offset: *mut u8) -> *mut u8 {
-    unsafe {
-        let align = DATA_PADDING as usize - 1;
-        let data_offset = (data_words_offset as usize - base_offset as usize + align) & !align;
-        (data_offset + base_offset as usize) as *mut u8
-    }
+pub fn get_aligned_data_offset(data_words_offset: *mut u8, base_offset: *mut u8) -> *mut u8 {
+    let align = DATA_PADDING as usize - 1;
+    let data_offset = (data_words_offset as usize - base_offset as usize + align) & !align;
+    (data_offset + base_offset as usize) as *mut u8
 }
 
+pub mod transport;
+
+pub mod loopback;
+
 pub mod client;
 
 pub mod server;
 
-pub mod sf;
\ No newline at end of file
+pub mod sf;
+
+#[cfg(test)]
+mod tests;
\ No newline at end of file
@@ -5,28 +5,72 @@ use crate::wait;
 use crate::ipc::sf::IObject;
 use crate::ipc::sf::hipc::IHipcManager;
 use crate::ipc::sf::hipc::IMitmQueryServer;
-use crate::service;
 use crate::service::sm;
-use crate::service::sm::IUserInterface;
 use crate::mem;
+use crate::os;
+use crate::sync;
+use crate::thread;
 use super::*;
 use core::mem as cmem;
+use core::sync::atomic::AtomicBool;
+use core::sync::atomic::Ordering;
+use alloc::boxed::Box;
+use alloc::sync::Arc;
+use alloc::vec::Vec;
 use arrayvec::ArrayVec;
 
-// TODO: support pointer buffer, proper result codes, implement left control commands
+// TODO: proper result codes
 
 const MAX_COUNT: usize = wait::MAX_OBJECT_COUNT as usize;
 
+// Memory where the kernel copies the pointer buffers sent by clients, also used for the pointer buffers sent back to them
+#[derive(Copy, Clone)]
+pub struct PointerBuffer {
+    pub buf: *mut u8,
+    pub size: usize
+}
+
+impl PointerBuffer {
+    pub const fn new(buf: *mut u8, size: usize) -> Self {
+        Self { buf: buf, size: size }
+    }
+
+    pub fn from_slice(slice: &mut [u8]) -> Self {
+        Self::new(slice.as_mut_ptr(), slice.len())
+    }
+
+    pub fn contains(&self, address: *const u8) -> bool {
+        let start = self.buf as usize;
+        ((address as usize) >= start) && ((address as usize) < (start + self.size))
+    }
+}
+
 pub struct ServerContext<'a> {
     pub ctx: &'a mut CommandContext,
     pub raw_data_walker: DataWalker,
-    pub domain_table: DomainTable,
-    pub new_sessions: &'a mut ArrayVec<[ServerHolder; MAX_COUNT]>
+    // None for non-domain sessions
+    pub domain_table: Option<&'a mut DomainTable>,
+    pub new_sessions: &'a mut ArrayVec<[ServerHolder; MAX_COUNT]>,
+    // None for loopback requests, whose buffers are directly accessible
+    pub pointer_buffer: Option<PointerBuffer>,
+    pointer_buffer_offset: usize,
+    receive_static_count: usize
 }
 
 impl<'a> ServerContext<'a> {
-    pub fn new(ctx: &'a mut CommandContext, raw_data_walker: DataWalker, domain_table: DomainTable, new_sessions: &'a mut ArrayVec<[ServerHolder; MAX_COUNT]>) -> Self {
-        Self { ctx: ctx, raw_data_walker: raw_data_walker, domain_table: domain_table, new_sessions: new_sessions }
+    pub fn new(ctx: &'a mut CommandContext, raw_data_walker: DataWalker, domain_table: Option<&'a mut DomainTable>, new_sessions: &'a mut ArrayVec<[ServerHolder; MAX_COUNT]>, pointer_buffer: Option<PointerBuffer>) -> Self {
+        // Pointer buffers sent back are placed after the ones received
+        let mut pointer_buffer_offset: usize = 0;
+        if let Some(pointer_buffer) = pointer_buffer {
+            for send_static in &ctx.send_statics {
+                if pointer_buffer.contains(send_static.get_address()) {
+                    let end_offset = (send_static.get_address() as usize - pointer_buffer.buf as usize) + send_static.get_size();
+                    pointer_buffer_offset = core::cmp::max(pointer_buffer_offset, get_aligned_pointer_buffer_size(end_offset));
+                }
+            }
+        }
+        let receive_static_count = ctx.receive_statics.len();
+        Self { ctx: ctx, raw_data_walker: raw_data_walker, domain_table: domain_table, new_sessions: new_sessions, pointer_buffer: pointer_buffer, pointer_buffer_offset: pointer_buffer_offset, receive_static_count: receive_static_count }
     }
 
     pub fn push_holder(&mut self, server_holder: ServerHolder) -> Result<()> {
@@ -35,104 +79,144 @@ impl<'a> ServerContext<'a> {
             Err(_) => Err(ResultCode::new(23))
         }
     }
+
+    pub fn allocate_pointer_buffer(&mut self, size: usize) -> Result<*mut u8> {
+        match self.pointer_buffer {
+            Some(pointer_buffer) => {
+                result_return_if!((self.pointer_buffer_offset + size) > pointer_buffer.size, results::hipc::ResultPointerBufferTooSmall);
+                let buf = unsafe { pointer_buffer.buf.offset(self.pointer_buffer_offset as isize) };
+                self.pointer_buffer_offset += get_aligned_pointer_buffer_size(size);
+                Ok(buf)
+            },
+            None => Err(results::hipc::ResultUnsupportedOperation::make())
+        }
+    }
 }
 
+// Prepares the IPC buffer to receive a request, with the pointer buffer (if any) as the only receive static
 #[inline(always)]
-pub fn read_command_from_ipc_buffer(ctx: &mut CommandContext) -> CommandType {
+pub fn write_receive_list_on_buffer(buf: &mut [u8], pointer_buffer: PointerBuffer) -> Result<()> {
     unsafe {
-        let mut ipc_buf = get_ipc_buffer();
+        let mut msg_buf = buf.as_mut_ptr();
 
-        let command_header = ipc_buf as *mut CommandHeader;
-        ipc_buf = command_header.offset(1) as *mut u8;
+        let receive_static_count = match pointer_buffer.size {
+            0 => 0,
+            _ => 1
+        };
+        let command_header = CommandHeader::new(CommandType::Invalid, 0, 0, 0, 0, 0, receive_static_count, false);
+        let special_header = CommandSpecialHeader::new(false, 0, 0);
+        check_message_size(buf, get_message_size(&command_header, &special_header))?;
 
-        let command_type = (*command_header).get_command_type();
-        let data_size = (*command_header).get_data_word_count() * cmem::size_of::<u32>() as u32;
-        ctx.in_params.data_size = data_size;
+        *(msg_buf as *mut CommandHeader) = command_header;
+        msg_buf = msg_buf.offset(cmem::size_of::<CommandHeader>() as isize);
 
-        if (*command_header).get_has_special_header() {
-            let special_header = ipc_buf as *mut CommandSpecialHeader;
-            ipc_buf = special_header.offset(1) as *mut u8;
+        if receive_static_count > 0 {
+            *(msg_buf as *mut ReceiveStaticDescriptor) = ReceiveStaticDescriptor::new(pointer_buffer.buf, pointer_buffer.size);
+        }
+        Ok(())
+    }
+}
 
-            ctx.in_params.send_process_id = (*special_header).get_send_process_id();
-            if ctx.in_params.send_process_id {
-                let process_id_ptr = ipc_buf as *mut u64;
-                ctx.in_params.process_id = *process_id_ptr;
-                ipc_buf = process_id_ptr.offset(1) as *mut u8;
-            }
+#[inline(always)]
+pub fn read_command_from_buffer(buf: &mut [u8], ctx: &mut CommandContext) -> Result<CommandType> {
+    unsafe {
+        let mut msg_buf = buf.as_mut_ptr();
 
-            let copy_handle_count = (*special_header).get_copy_handle_count();
-            ipc_buf = read_array_from_buffer(ipc_buf, copy_handle_count, &mut ctx.in_params.copy_handles);
-            let move_handle_count = (*special_header).get_move_handle_count();
-            ipc_buf = read_array_from_buffer(ipc_buf, move_handle_count, &mut ctx.in_params.move_handles);
+        check_message_size(buf, cmem::size_of::<CommandHeader>())?;
+        let command_header = *(msg_buf as *const CommandHeader);
+        msg_buf = msg_buf.offset(cmem::size_of::<CommandHeader>() as isize);
+
+        let mut special_header = CommandSpecialHeader::empty();
+        if command_header.get_has_special_header() {
+            check_message_size(buf, cmem::size_of::<CommandHeader>() + cmem::size_of::<CommandSpecialHeader>())?;
+            special_header = *(msg_buf as *const CommandSpecialHeader);
+            msg_buf = msg_buf.offset(cmem::size_of::<CommandSpecialHeader>() as isize);
         }
+        check_message_size(buf, get_message_size(&command_header, &special_header))?;
 
-        let send_static_count = (*command_header).get_send_static_count();
-        ipc_buf = read_array_from_buffer(ipc_buf, send_static_count, &mut ctx.send_statics);
-        let send_buffer_count = (*command_header).get_send_buffer_count();
-        ipc_buf = read_array_from_buffer(ipc_buf, send_buffer_count, &mut ctx.send_buffers);
-        let receive_buffer_count = (*command_header).get_receive_buffer_count();
-        ipc_buf = read_array_from_buffer(ipc_buf, receive_buffer_count, &mut ctx.receive_buffers);
-        let exchange_buffer_count = (*command_header).get_exchange_buffer_count();
-        ipc_buf = read_array_from_buffer(ipc_buf, exchange_buffer_count, &mut ctx.exchange_buffers);
+        let command_type = command_header.get_command_type();
+        let data_size = command_header.get_data_word_count() * cmem::size_of::<u32>() as u32;
+        ctx.in_params.data_size = data_size;
 
-        ctx.in_params.data_words_offset = ipc_buf;
-        ipc_buf = ipc_buf.offset(data_size as isize);
+        ctx.in_params.send_process_id = special_header.get_send_process_id();
+        if ctx.in_params.send_process_id {
+            // Placed right after the special header, thus only 4-byte aligned
+            ctx.in_params.process_id = ptr::read_unaligned(msg_buf as *const u64);
+            msg_buf = msg_buf.offset(cmem::size_of::<u64>() as isize);
+        }
 
-        let receive_static_count = (*command_header).get_receive_static_count();
-        read_array_from_buffer(ipc_buf, receive_static_count, &mut ctx.receive_statics);
+        msg_buf = read_array_from_buffer(msg_buf, special_header.get_copy_handle_count(), &mut ctx.in_params.copy_handles);
+        msg_buf = read_array_from_buffer(msg_buf, special_header.get_move_handle_count(), &mut ctx.in_params.move_handles);
 
-        command_type
+        msg_buf = read_array_from_buffer(msg_buf, command_header.get_send_static_count(), &mut ctx.send_statics);
+        msg_buf = read_array_from_buffer(msg_buf, command_header.get_send_buffer_count(), &mut ctx.send_buffers);
+        msg_buf = read_array_from_buffer(msg_buf, command_header.get_receive_buffer_count(), &mut ctx.receive_buffers);
+        msg_buf = read_array_from_buffer(msg_buf, command_header.get_exchange_buffer_count(), &mut ctx.exchange_buffers);
+
+        ctx.in_params.data_words_offset = msg_buf;
+        msg_buf = msg_buf.offset(data_size as isize);
+
+        read_array_from_buffer(msg_buf, command_header.get_receive_static_count(), &mut ctx.receive_statics);
+
+        Ok(command_type)
     }
 }
 
 #[inline(always)]
-pub fn write_command_response_on_ipc_buffer(ctx: &mut CommandContext, command_type: CommandType, data_size: u32) {
+pub fn write_command_response_on_buffer(buf: &mut [u8], ctx: &mut CommandContext, command_type: CommandType, data_size: u32) -> Result<()> {
+    write_raw_command_response_on_buffer(buf, ctx, command_type as u32, data_size)
+}
+
+#[inline(always)]
+pub fn write_raw_command_response_on_buffer(buf: &mut [u8], ctx: &mut CommandContext, raw_command_type: u32, data_size: u32) -> Result<()> {
     unsafe {
-        let mut ipc_buf = get_ipc_buffer();
-        
-        let command_header = ipc_buf as *mut CommandHeader;
-        ipc_buf = command_header.offset(1) as *mut u8;
+        let mut msg_buf = buf.as_mut_ptr();
 
         let data_word_count = (data_size + 3) / 4;
         let has_special_header = ctx.out_params.send_process_id || (ctx.out_params.copy_handles.len() > 0) || (ctx.out_params.move_handles.len() > 0);
-        *command_header = CommandHeader::new(command_type, ctx.send_statics.len() as u32, ctx.send_buffers.len() as u32, ctx.receive_buffers.len() as u32, ctx.exchange_buffers.len() as u32, data_word_count, ctx.receive_statics.len() as u32, has_special_header);
+        let command_header = CommandHeader::new_raw(raw_command_type, ctx.send_statics.len() as u32, ctx.send_buffers.len() as u32, ctx.receive_buffers.len() as u32, ctx.exchange_buffers.len() as u32, data_word_count, ctx.receive_statics.len() as u32, has_special_header);
+        let special_header = CommandSpecialHeader::new(ctx.out_params.send_process_id, ctx.out_params.copy_handles.len() as u32, ctx.out_params.move_handles.len() as u32);
+        check_message_size(buf, get_message_size(&command_header, &special_header))?;
+
+        *(msg_buf as *mut CommandHeader) = command_header;
+        msg_buf = msg_buf.offset(cmem::size_of::<CommandHeader>() as isize);
 
         if has_special_header {
-            let special_header = ipc_buf as *mut CommandSpecialHeader;
-            ipc_buf = special_header.offset(1) as *mut u8;
+            *(msg_buf as *mut CommandSpecialHeader) = special_header;
+            msg_buf = msg_buf.offset(cmem::size_of::<CommandSpecialHeader>() as isize);
 
-            *special_header = CommandSpecialHeader::new(ctx.out_params.send_process_id, ctx.out_params.copy_handles.len() as u32, ctx.out_params.move_handles.len() as u32);
             if ctx.out_params.send_process_id {
-                ipc_buf = ipc_buf.offset(cmem::size_of::<u64>() as isize);
+                msg_buf = msg_buf.offset(cmem::size_of::<u64>() as isize);
             }
 
-            ipc_buf = write_array_to_buffer(ipc_buf, ctx.out_params.copy_handles.len() as u32, &ctx.out_params.copy_handles);
-            ipc_buf = write_array_to_buffer(ipc_buf, ctx.out_params.move_handles.len() as u32, &ctx.out_params.move_handles);
+            msg_buf = write_array_to_buffer(msg_buf, ctx.out_params.copy_handles.len() as u32, &ctx.out_params.copy_handles);
+            msg_buf = write_array_to_buffer(msg_buf, ctx.out_params.move_handles.len() as u32, &ctx.out_params.move_handles);
         }
 
-        ipc_buf = write_array_to_buffer(ipc_buf, ctx.send_statics.len() as u32, &ctx.send_statics);
-        ipc_buf = write_array_to_buffer(ipc_buf, ctx.send_buffers.len() as u32, &ctx.send_buffers);
-        ipc_buf = write_array_to_buffer(ipc_buf, ctx.receive_buffers.len() as u32, &ctx.receive_buffers);
-        ipc_buf = write_array_to_buffer(ipc_buf, ctx.exchange_buffers.len() as u32, &ctx.exchange_buffers);
-        ctx.out_params.data_words_offset = ipc_buf;
+        msg_buf = write_array_to_buffer(msg_buf, ctx.send_statics.len() as u32, &ctx.send_statics);
+        msg_buf = write_array_to_buffer(msg_buf, ctx.send_buffers.len() as u32, &ctx.send_buffers);
+        msg_buf = write_array_to_buffer(msg_buf, ctx.receive_buffers.len() as u32, &ctx.receive_buffers);
+        msg_buf = write_array_to_buffer(msg_buf, ctx.exchange_buffers.len() as u32, &ctx.exchange_buffers);
+        ctx.out_params.data_words_offset = msg_buf;
 
-        ipc_buf = ipc_buf.offset((data_word_count * cmem::size_of::<u32>() as u32) as isize);
-        write_array_to_buffer(ipc_buf, ctx.receive_statics.len() as u32, &ctx.receive_statics);
+        msg_buf = msg_buf.offset((data_word_count * cmem::size_of::<u32>() as u32) as isize);
+        write_array_to_buffer(msg_buf, ctx.receive_statics.len() as u32, &ctx.receive_statics);
+        Ok(())
     }
 }
 
 #[inline(always)]
-pub fn read_request_command_from_ipc_buffer(ctx: &mut CommandContext) -> Result<(u32, DomainCommandType, DomainObjectId)> {
+pub fn read_request_command_from_buffer(buf: &mut [u8], ctx: &mut CommandContext) -> Result<(u32, DomainCommandType, DomainObjectId)> {
     unsafe {
         let mut domain_command_type = DomainCommandType::Invalid;
         let mut domain_object_id: DomainObjectId = 0;
-        let ipc_buf = get_ipc_buffer();
-        let mut data_offset = get_aligned_data_offset(ctx.in_params.data_words_offset, ipc_buf);
+        let mut data_offset = get_aligned_data_offset(ctx.in_params.data_words_offset, buf.as_mut_ptr());
 
         // TODO: out pointer
 
         let mut data_header = data_offset as *mut DataHeader;
         if ctx.object_info.is_domain() {
+            check_buffer_range(buf, data_offset, cmem::size_of::<DomainInDataHeader>())?;
             let domain_header = data_offset as *mut DomainInDataHeader;
             data_offset = domain_header.offset(1) as *mut u8;
 
@@ -140,25 +224,37 @@ pub fn read_request_command_from_ipc_buffer(ctx: &mut CommandContext) -> Result<
             let object_count = (*domain_header).object_count;
             domain_object_id = (*domain_header).domain_object_id;
             let objects_offset = data_offset.offset((*domain_header).data_size as isize);
+            check_buffer_range(buf, objects_offset, cmem::size_of::<DomainObjectId>() * object_count as usize)?;
             read_array_from_buffer(objects_offset, object_count as u32, &mut ctx.in_params.objects);
 
             data_header = data_offset as *mut DataHeader;
         }
 
+        // Domain close requests only consist of the domain header
+        if domain_command_type == DomainCommandType::Close {
+            ctx.in_params.data_offset = data_offset;
+            ctx.in_params.data_size = 0;
+            return Ok((0, domain_command_type, domain_object_id));
+        }
+
+        check_buffer_range(buf, data_header as *mut u8, cmem::size_of::<DataHeader>())?;
         result_return_unless!((*data_header).magic == IN_DATA_HEADER_MAGIC, results::cmif::ResultInvalidInputHeader);
         let rq_id = (*data_header).value;
         data_offset = data_header.offset(1) as *mut u8;
 
         ctx.in_params.data_offset = data_offset;
-        ctx.in_params.data_size -= DATA_PADDING + cmem::size_of::<DataHeader>() as u32;
+        ctx.in_params.data_size = ctx.in_params.data_size.saturating_sub(DATA_PADDING + cmem::size_of::<DataHeader>() as u32);
         Ok((rq_id, domain_command_type, domain_object_id))
     }
 }
 
 #[inline(always)]
-pub fn write_request_command_response_on_ipc_buffer(ctx: &mut CommandContext, result: ResultCode, request_type: CommandType) {
+pub fn write_request_command_response_on_buffer(buf: &mut [u8], ctx: &mut CommandContext, result: ResultCode, request_type: CommandType) -> Result<()> {
+    if ctx.object_info.uses_tipc_protocol() {
+        return write_tipc_request_command_response_on_buffer(buf, ctx, result);
+    }
+
     unsafe {
-        let ipc_buf = get_ipc_buffer();
         let mut data_size = DATA_PADDING + cmem::size_of::<DataHeader>() as u32 + ctx.out_params.data_size;
         if ctx.object_info.is_domain() {
             data_size += (cmem::size_of::<DomainOutDataHeader>() + cmem::size_of::<DomainObjectId>() * ctx.out_params.objects.len()) as u32;
@@ -166,8 +262,8 @@ pub fn write_request_command_response_on_ipc_buffer(ctx: &mut CommandContext, re
         data_size = (data_size + 1) & !1;
         // TODO: out pointer
 
-        write_command_response_on_ipc_buffer(ctx, request_type, data_size);
-        let mut data_offset = get_aligned_data_offset(ctx.out_params.data_words_offset, ipc_buf);
+        write_command_response_on_buffer(buf, ctx, request_type, data_size)?;
+        let mut data_offset = get_aligned_data_offset(ctx.out_params.data_words_offset, buf.as_mut_ptr());
 
         // TODO: out pointer
 
@@ -188,36 +284,37 @@ pub fn write_request_command_response_on_ipc_buffer(ctx: &mut CommandContext, re
         };
         *data_header = DataHeader::new(OUT_DATA_HEADER_MAGIC, version, result.get_value(), 0);
         ctx.out_params.data_offset = data_offset;
+        Ok(())
     }
 }
 
 #[inline(always)]
-pub fn read_control_command_from_ipc_buffer(ctx: &mut CommandContext) -> Result<ControlRequestId> {
+pub fn read_control_command_from_buffer(buf: &mut [u8], ctx: &mut CommandContext) -> Result<ControlRequestId> {
     unsafe {
-        let ipc_buf = get_ipc_buffer();
-        let mut data_offset = get_aligned_data_offset(ctx.in_params.data_words_offset, ipc_buf);
+        let mut data_offset = get_aligned_data_offset(ctx.in_params.data_words_offset, buf.as_mut_ptr());
 
+        check_buffer_range(buf, data_offset, cmem::size_of::<DataHeader>())?;
         let data_header = data_offset as *mut DataHeader;
         data_offset = data_header.offset(1) as *mut u8;
 
         result_return_unless!((*data_header).magic == IN_DATA_HEADER_MAGIC, results::cmif::ResultInvalidInputHeader);
         let control_rq_id = (*data_header).value;
+        result_return_unless!(control_rq_id <= ControlRequestId::CloneCurrentObjectEx as u32, results::cmif::ResultInvalidCommandRequestId);
 
         ctx.in_params.data_offset = data_offset;
-        ctx.in_params.data_size -= DATA_PADDING + cmem::size_of::<DataHeader>() as u32;
+        ctx.in_params.data_size = ctx.in_params.data_size.saturating_sub(DATA_PADDING + cmem::size_of::<DataHeader>() as u32);
         Ok(cmem::transmute(control_rq_id))
     }
 }
 
 #[inline(always)]
-pub fn write_control_command_response_on_ipc_buffer(ctx: &mut CommandContext, result: ResultCode, control_type: CommandType) {
+pub fn write_control_command_response_on_buffer(buf: &mut [u8], ctx: &mut CommandContext, result: ResultCode, control_type: CommandType) -> Result<()> {
     unsafe {
-        let ipc_buf = get_ipc_buffer();
         let mut data_size = DATA_PADDING + cmem::size_of::<DataHeader>() as u32 + ctx.out_params.data_size;
         data_size = (data_size + 1) & !1;
 
-        write_command_response_on_ipc_buffer(ctx, control_type, data_size);
-        let mut data_offset = get_aligned_data_offset(ctx.out_params.data_words_offset, ipc_buf);
+        write_command_response_on_buffer(buf, ctx, control_type, data_size)?;
+        let mut data_offset = get_aligned_data_offset(ctx.out_params.data_words_offset, buf.as_mut_ptr());
 
         let data_header = data_offset as *mut DataHeader;
         data_offset = data_header.offset(1) as *mut u8;
@@ -228,12 +325,94 @@ pub fn write_control_command_response_on_ipc_buffer(ctx: &mut CommandContext, re
         };
         *data_header = DataHeader::new(OUT_DATA_HEADER_MAGIC, version, result.get_value(), 0);
         ctx.out_params.data_offset = data_offset;
+        Ok(())
+    }
+}
+
+// TIPC requests/responses have no data header: the request ID is encoded in the command type, and responses start with the result value
+
+#[inline(always)]
+pub fn read_tipc_request_command_from_buffer(buf: &mut [u8], ctx: &mut CommandContext) -> Result<u32> {
+    unsafe {
+        check_message_size(buf, cmem::size_of::<CommandHeader>())?;
+        let command_header = *(buf.as_ptr() as *const CommandHeader);
+        result_return_unless!(command_header.get_command_type() == CommandType::TipcRequest, results::cmif::ResultInvalidInputHeader);
+
+        ctx.object_info.protocol = CommandProtocol::Tipc;
+        ctx.in_params.data_offset = ctx.in_params.data_words_offset;
+        Ok(command_header.get_tipc_request_id())
+    }
+}
+
+#[inline(always)]
+pub fn write_tipc_request_command_response_on_buffer(buf: &mut [u8], ctx: &mut CommandContext, result: ResultCode) -> Result<()> {
+    unsafe {
+        let data_size = cmem::size_of::<u32>() as u32 + ctx.out_params.data_size;
+        write_command_response_on_buffer(buf, ctx, CommandType::TipcRequest, data_size)?;
+
+        let data_offset = ctx.out_params.data_words_offset;
+        *(data_offset as *mut u32) = result.get_value();
+        ctx.out_params.data_offset = data_offset.offset(cmem::size_of::<u32>() as isize);
+        Ok(())
     }
 }
 
 #[inline(always)]
-pub fn write_close_command_response_on_ipc_buffer(ctx: &mut CommandContext) {
-    write_command_response_on_ipc_buffer(ctx, CommandType::Close, 0);
+pub fn write_close_command_response_on_buffer(buf: &mut [u8], ctx: &mut CommandContext) -> Result<()> {
+    write_command_response_on_buffer(buf, ctx, CommandType::Close, 0)
+}
+
+// Shorthands for the above functions using the buffer the transport places the session's messages in (the current thread's IPC buffer for actual IPC requests)
+
+#[inline(always)]
+pub fn read_command_from_ipc_buffer(ctx: &mut CommandContext) -> Result<CommandType> {
+    read_command_from_buffer(transport::get_message_buffer_slice(ctx.object_info.handle), ctx)
+}
+
+#[inline(always)]
+pub fn write_command_response_on_ipc_buffer(ctx: &mut CommandContext, command_type: CommandType, data_size: u32) -> Result<()> {
+    write_command_response_on_buffer(transport::get_message_buffer_slice(ctx.object_info.handle), ctx, command_type, data_size)
+}
+
+#[inline(always)]
+pub fn read_request_command_from_ipc_buffer(ctx: &mut CommandContext) -> Result<(u32, DomainCommandType, DomainObjectId)> {
+    read_request_command_from_buffer(transport::get_message_buffer_slice(ctx.object_info.handle), ctx)
+}
+
+#[inline(always)]
+pub fn write_request_command_response_on_ipc_buffer(ctx: &mut CommandContext, result: ResultCode, request_type: CommandType) -> Result<()> {
+    write_request_command_response_on_buffer(transport::get_message_buffer_slice(ctx.object_info.handle), ctx, result, request_type)
+}
+
+#[inline(always)]
+pub fn read_control_command_from_ipc_buffer(ctx: &mut CommandContext) -> Result<ControlRequestId> {
+    read_control_command_from_buffer(transport::get_message_buffer_slice(ctx.object_info.handle), ctx)
+}
+
+#[inline(always)]
+pub fn write_control_command_response_on_ipc_buffer(ctx: &mut CommandContext, result: ResultCode, control_type: CommandType) -> Result<()> {
+    write_control_command_response_on_buffer(transport::get_message_buffer_slice(ctx.object_info.handle), ctx, result, control_type)
+}
+
+#[inline(always)]
+pub fn read_tipc_request_command_from_ipc_buffer(ctx: &mut CommandContext) -> Result<u32> {
+    read_tipc_request_command_from_buffer(transport::get_message_buffer_slice(ctx.object_info.handle), ctx)
+}
+
+#[inline(always)]
+pub fn write_tipc_request_command_response_on_ipc_buffer(ctx: &mut CommandContext, result: ResultCode) -> Result<()> {
+    write_tipc_request_command_response_on_buffer(transport::get_message_buffer_slice(ctx.object_info.handle), ctx, result)
+}
+
+#[inline(always)]
+pub fn write_close_command_response_on_ipc_buffer(ctx: &mut CommandContext) -> Result<()> {
+    write_close_command_response_on_buffer(transport::get_message_buffer_slice(ctx.object_info.handle), ctx)
+}
+
+// Only needed when receiving through the kernel, thus always on the current thread's IPC buffer
+#[inline(always)]
+pub fn write_receive_list_on_ipc_buffer(pointer_buffer: PointerBuffer) -> Result<()> {
+    write_receive_list_on_buffer(get_ipc_buffer_slice(), pointer_buffer)
 }
 
 pub trait CommandParameter<O> {
@@ -260,7 +439,20 @@ impl<T: Copy> CommandParameter<T> for T {
 
 impl<const A: BufferAttribute> CommandParameter<sf::Buffer<A>> for sf::Buffer<A> {
     fn after_request_read(ctx: &mut ServerContext) -> Result<Self> {
-        ctx.ctx.pop_buffer()
+        // Out pointer buffers point to the client's memory: the data is written to our pointer buffer instead, and sent back in the response
+        let is_out_static = A.contains(BufferAttribute::Out()) && !ctx.ctx.object_info.uses_tipc_protocol() && match ctx.ctx.receive_statics.first() {
+            Some(receive_static) => A.contains(BufferAttribute::Pointer()) || (A.contains(BufferAttribute::AutoSelect()) && !receive_static.get_address().is_null() && (receive_static.get_size() > 0)),
+            None => false
+        };
+        let static_index = ctx.receive_static_count - ctx.ctx.receive_statics.len();
+
+        let buffer: sf::Buffer<A> = ctx.ctx.pop_buffer()?;
+        if is_out_static && ctx.pointer_buffer.is_some() {
+            let buf = ctx.allocate_pointer_buffer(buffer.size)?;
+            ctx.ctx.add_send_static(SendStaticDescriptor::new(buf, buffer.size, static_index as u32))?;
+            return Ok(sf::Buffer::from_mut(buf, buffer.size));
+        }
+        Ok(buffer)
     }
 
     fn before_response_write(_buffer: &Self, _ctx: &mut ServerContext) -> Result<()> {
@@ -273,16 +465,16 @@ impl<const A: BufferAttribute> CommandParameter<sf::Buffer<A>> for sf::Buffer<A>
 }
 
 impl<const M: HandleMode> CommandParameter<sf::Handle<M>> for sf::Handle<M> {
-    fn after_request_read(_ctx: &mut ServerContext) -> Result<Self> {
-        Err(results::hipc::ResultUnsupportedOperation::make())
+    fn after_request_read(ctx: &mut ServerContext) -> Result<Self> {
+        ctx.ctx.in_params.pop_handle()
     }
 
-    fn before_response_write(_handle: &Self, _ctx: &mut ServerContext) -> Result<()> {
-        Err(results::hipc::ResultUnsupportedOperation::make())
+    fn before_response_write(handle: &Self, ctx: &mut ServerContext) -> Result<()> {
+        ctx.ctx.out_params.push_handle(*handle)
     }
 
     fn after_response_write(_handle: &Self, _ctx: &mut ServerContext) -> Result<()> {
-        Err(results::hipc::ResultUnsupportedOperation::make())
+        Ok(())
     }
 }
 
@@ -290,7 +482,10 @@ impl CommandParameter<sf::ProcessId> for sf::ProcessId {
     fn after_request_read(ctx: &mut ServerContext) -> Result<Self> {
         if ctx.ctx.in_params.send_process_id {
             // TODO: is this really how process ID works? (is the in raw u64 just placeholder data?)
-            let _ = ctx.raw_data_walker.advance_get::<u64>();
+            // TIPC requests don't have the placeholder at all
+            if !ctx.ctx.object_info.uses_tipc_protocol() {
+                let _ = ctx.raw_data_walker.advance_get::<u64>();
+            }
             Ok(sf::ProcessId::from(ctx.ctx.in_params.process_id)) 
         }
         else {
@@ -307,22 +502,48 @@ impl CommandParameter<sf::ProcessId> for sf::ProcessId {
     }
 }
 
+// Objects sent from domain sessions are added to their domain, otherwise they get their own session
+fn push_out_object(session: mem::Shared<dyn sf::IObject>, ctx: &mut ServerContext) -> Result<()> {
+    match ctx.domain_table {
+        Some(ref mut domain_table) => {
+            let domain_object_id = domain_table.allocate(session.clone())?;
+            session.get().set_info(ObjectInfo::from_domain_object_id(ctx.ctx.object_info.handle, domain_object_id));
+            ctx.ctx.out_params.push_domain_object(domain_object_id)
+        },
+        None => {
+            let (server_handle, client_handle) = transport::create_session()?;
+            ctx.ctx.out_params.push_handle(sf::MoveHandle::from(client_handle))?;
+            ctx.push_holder(ServerHolder::new_session(server_handle, session))
+        }
+    }
+}
+
 impl CommandParameter<mem::Shared<dyn sf::IObject>> for mem::Shared<dyn sf::IObject> {
+    fn after_request_read(ctx: &mut ServerContext) -> Result<Self> {
+        let domain_object_id = ctx.ctx.in_params.pop_domain_object()?;
+        match ctx.domain_table {
+            Some(ref domain_table) => domain_table.get(domain_object_id),
+            None => Err(results::hipc::ResultTargetNotDomain::make())
+        }
+    }
+
+    fn before_response_write(session: &Self, ctx: &mut ServerContext) -> Result<()> {
+        push_out_object(session.clone(), ctx)
+    }
+
+    fn after_response_write(_session: &Self, _ctx: &mut ServerContext) -> Result<()> {
+        Ok(())
+    }
+}
+
+// Typed sub-interfaces: any object (usually an IServerObject) can be sent for the slot
+impl<S: sf::IObject + 'static> CommandParameter<mem::Shared<S>> for mem::Shared<S> {
     fn after_request_read(_ctx: &mut ServerContext) -> Result<Self> {
         Err(results::hipc::ResultUnsupportedOperation::make())
     }
 
     fn before_response_write(session: &Self, ctx: &mut ServerContext) -> Result<()> {
-        if ctx.ctx.object_info.is_domain() {
-            let domain_object_id = ctx.domain_table.allocate_new()?;
-            ctx.ctx.out_params.push_domain_object(domain_object_id)?;
-            ctx.push_holder(ServerHolder::new_domain_session(ctx.ctx.object_info.handle, domain_object_id, session.clone()))
-        }
-        else {
-            let (server_handle, client_handle) = svc::create_session(false, 0)?;
-            ctx.ctx.out_params.push_handle(sf::MoveHandle::from(client_handle))?;
-            ctx.push_holder(ServerHolder::new_session(server_handle, session.clone()))
-        }
+        push_out_object(session.clone(), ctx)
     }
 
     fn after_response_write(_session: &Self, _ctx: &mut ServerContext) -> Result<()> {
@@ -347,79 +568,107 @@ pub enum WaitHandleType {
     Session
 }
 
-#[derive(Copy, Clone)]
+// Objects of a domain session, the session itself (the base object) included
+#[derive(Clone)]
 pub struct DomainTable {
-    pub table: [DomainObjectId; MAX_COUNT]
+    objects: ArrayVec<[(DomainObjectId, mem::Shared<dyn sf::IObject>); MAX_COUNT]>
 }
 
 impl DomainTable {
-    pub const fn new() -> Self {
-        Self { table: [0; MAX_COUNT] }
-    }
-
-    pub fn allocate_new(&mut self) -> Result<DomainObjectId> {
-        for base_id in 0..self.table.len() {
-            let domain_object_id = (base_id + 1) as DomainObjectId;
-            for i in 0..self.table.len() {
-                match self.table[i] {
-                    0 => {
-                        self.table[i] = domain_object_id;
-                        return Ok(domain_object_id);
-                    },
-                    other => {
-                        if other == domain_object_id {
-                            break;
-                        }
-                    }
-                };
-            }
+    pub fn new() -> Self {
+        Self { objects: ArrayVec::new() }
+    }
+
+    fn find(&self, domain_object_id: DomainObjectId) -> Option<usize> {
+        self.objects.iter().position(|(id, _)| *id == domain_object_id)
+    }
+
+    pub fn allocate(&mut self, object: mem::Shared<dyn sf::IObject>) -> Result<DomainObjectId> {
+        // The lowest free ID is used, starting from 1 (0 means no domain object)
+        let mut domain_object_id: DomainObjectId = 1;
+        while self.find(domain_object_id).is_some() {
+            domain_object_id += 1;
+        }
+        match self.objects.try_push((domain_object_id, object)) {
+            Ok(()) => Ok(domain_object_id),
+            Err(_) => Err(results::cmif::ResultOutOfDomainEntries::make())
+        }
+    }
+
+    pub fn get(&self, domain_object_id: DomainObjectId) -> Result<mem::Shared<dyn sf::IObject>> {
+        match self.find(domain_object_id) {
+            Some(index) => Ok(self.objects[index].1.clone()),
+            None => Err(results::hipc::ResultDomainObjectNotFound::make())
         }
-        Err(ResultCode::new(0xBEBA))
     }
 
     pub fn deallocate(&mut self, domain_object_id: DomainObjectId) -> Result<()> {
-        for i in 0..self.table.len() {
-            if self.table[i] == domain_object_id {
-                self.table[i] = 0;
-                return Ok(());
-            }
+        match self.find(domain_object_id) {
+            Some(index) => {
+                if let Some((_, object)) = self.objects.pop_at(index) {
+                    // Clear the domain ID, so dropping the object doesn't send a close request to its own session
+                    let mut object_info = object.get().get_info();
+                    if !object_info.owns_handle {
+                        object_info.domain_object_id = 0;
+                        object.get().set_info(object_info);
+                    }
+                }
+                Ok(())
+            },
+            None => Err(results::hipc::ResultDomainObjectNotFound::make())
         }
-        Err(ResultCode::new(0xBEB2))
     }
 }
 
+// Objects (and their non-thread-safe refcounts) can be shared by several sessions, which share this lock so that they're never processed at the same time
+pub type ObjectLock = Arc<sync::Mutex<()>>;
+
+fn new_object_lock() -> ObjectLock {
+    Arc::new(sync::Mutex::new(()))
+}
+
 pub struct ServerHolder {
     pub server: mem::Shared<dyn sf::IObject>,
+    // Info of the session (or port) itself, objects only keep theirs to talk back as clients
+    pub info: ObjectInfo,
     pub new_server_fn: Option<NewServerFn>,
     pub handle_type: WaitHandleType,
     pub forward_handle: svc::Handle,
     pub is_mitm_service: bool,
     pub service_name: sm::ServiceName,
-    pub domain_table: DomainTable
+    pub domain_table: Option<Box<DomainTable>>,
+    pub object_lock: ObjectLock
 }
 
 impl ServerHolder {
     pub fn new_server_session<S: IServerObject + 'static>(handle: svc::Handle) -> Self {
-        Self { server: mem::Shared::new(S::new(sf::Session::from_handle(handle))), new_server_fn: None, handle_type: WaitHandleType::Session, forward_handle: 0, is_mitm_service: false, service_name: sm::ServiceName::empty(), domain_table: DomainTable::new() } 
+        Self { server: mem::Shared::new(S::new(sf::Session::from_handle(handle))), info: ObjectInfo::from_handle(handle), new_server_fn: None, handle_type: WaitHandleType::Session, forward_handle: 0, is_mitm_service: false, service_name: sm::ServiceName::empty(), domain_table: None, object_lock: new_object_lock() } 
     }
 
     pub fn new_session(handle: svc::Handle, object: mem::Shared<dyn sf::IObject>) -> Self {
         object.get().set_info(ObjectInfo::from_handle(handle));
-        Self { server: object, new_server_fn: None, handle_type: WaitHandleType::Session, forward_handle: 0, is_mitm_service: false, service_name: sm::ServiceName::empty(), domain_table: DomainTable::new() } 
-    }
-
-    pub fn new_domain_session(handle: svc::Handle, domain_object_id: DomainObjectId, object: mem::Shared<dyn sf::IObject>) -> Self {
-        object.get().set_info(ObjectInfo::from_domain_object_id(handle, domain_object_id));
-        Self { server: object, new_server_fn: None, handle_type: WaitHandleType::Session, forward_handle: 0, is_mitm_service: false, service_name: sm::ServiceName::empty(), domain_table: DomainTable::new() } 
+        Self { server: object, info: ObjectInfo::from_handle(handle), new_server_fn: None, handle_type: WaitHandleType::Session, forward_handle: 0, is_mitm_service: false, service_name: sm::ServiceName::empty(), domain_table: None, object_lock: new_object_lock() } 
     }
     
+    // The object stays owned by another session (or domain), so its info is left untouched
+    pub fn new_shared_session(handle: svc::Handle, object: mem::Shared<dyn sf::IObject>) -> Self {
+        Self { server: object, info: ObjectInfo::from_handle(handle), new_server_fn: None, handle_type: WaitHandleType::Session, forward_handle: 0, is_mitm_service: false, service_name: sm::ServiceName::empty(), domain_table: None, object_lock: new_object_lock() }
+    }
+
     pub fn new_server<S: IServerObject + 'static>(handle: svc::Handle, service_name: sm::ServiceName, is_mitm_service: bool) -> Self {
-        Self { server: mem::Shared::new(S::new(sf::Session::from_handle(handle))), new_server_fn: Some(create_server_object_impl::<S>), handle_type: WaitHandleType::Server, forward_handle: 0, is_mitm_service: is_mitm_service, service_name: service_name, domain_table: DomainTable::new() } 
+        Self { server: mem::Shared::new(S::new(sf::Session::from_handle(handle))), info: ObjectInfo::from_handle(handle), new_server_fn: Some(create_server_object_impl::<S>), handle_type: WaitHandleType::Server, forward_handle: 0, is_mitm_service: is_mitm_service, service_name: service_name, domain_table: None, object_lock: new_object_lock() } 
     }
 
     pub fn make_new_session(&self, handle: svc::Handle, forward_handle: svc::Handle) -> Result<Self> {
         let new_fn = self.get_new_server_fn()?;
-        Ok(Self { server: (new_fn)(sf::Session::from_handle(handle)), new_server_fn: Some(new_fn), handle_type: WaitHandleType::Session, forward_handle: forward_handle, is_mitm_service: self.is_mitm_service, service_name: sm::ServiceName::empty(), domain_table: DomainTable::new() })
+        Ok(Self { server: (new_fn)(sf::Session::from_handle(handle)), info: ObjectInfo::from_handle(handle), new_server_fn: Some(new_fn), handle_type: WaitHandleType::Session, forward_handle: forward_handle, is_mitm_service: self.is_mitm_service, service_name: sm::ServiceName::empty(), domain_table: None, object_lock: new_object_lock() })
+    }
+
+    // The clone shares the same object(s) (and therefore the object lock) with this session, and gets its own copy of the domain table (if any)
+    pub fn make_clone_session(&self, handle: svc::Handle, forward_handle: svc::Handle) -> Self {
+        let mut info = ObjectInfo::from_handle(handle);
+        info.domain_object_id = self.info.domain_object_id;
+        Self { server: self.server.clone(), info: info, new_server_fn: self.new_server_fn, handle_type: WaitHandleType::Session, forward_handle: forward_handle, is_mitm_service: self.is_mitm_service, service_name: sm::ServiceName::empty(), domain_table: self.domain_table.clone(), object_lock: self.object_lock.clone() }
     }
 
     pub fn get_new_server_fn(&self) -> Result<NewServerFn> {
@@ -430,24 +679,42 @@ impl ServerHolder {
     }
 
     pub fn convert_to_domain(&mut self) -> Result<DomainObjectId> {
-        let domain_object_id = self.domain_table.allocate_new()?;
+        if self.domain_table.is_some() {
+            return Ok(self.info.domain_object_id);
+        }
+
+        let mut domain_table = Box::new(DomainTable::new());
+        let domain_object_id = domain_table.allocate(self.server.clone())?;
+        self.info.domain_object_id = domain_object_id;
+        self.domain_table = Some(domain_table);
+
         let mut server_info = self.server.get().get_info();
         server_info.domain_object_id = domain_object_id;
         self.server.get().set_info(server_info);
         Ok(domain_object_id)
     }
 
-    pub fn move_to(&mut self) -> Self {
-        let new_holder = Self { server: self.server.clone(), new_server_fn: self.new_server_fn, handle_type: self.handle_type, forward_handle: self.forward_handle, is_mitm_service: self.is_mitm_service, service_name: self.service_name, domain_table: DomainTable::new() };
-        self.is_mitm_service = false;
-        self.forward_handle = 0;
-        self.service_name = sm::ServiceName::empty();
-        new_holder
+    pub fn get_domain_object(&self, domain_object_id: DomainObjectId) -> Result<mem::Shared<dyn sf::IObject>> {
+        match self.domain_table {
+            Some(ref domain_table) => domain_table.get(domain_object_id),
+            None => Err(results::hipc::ResultTargetNotDomain::make())
+        }
     }
 
     pub fn close(&mut self) -> Result<()> {
+        // Sessions sharing another session's object (clones, domain copies) have a handle of their own, the rest have it closed along with their object's session
+        if self.info.is_valid() && (self.info.handle != self.server.get().get_info().handle) {
+            let _ = transport::close_handle(self.info.handle);
+        }
+        self.info = ObjectInfo::new();
+
+        if self.forward_handle != 0 {
+            let _ = transport::close_handle(self.forward_handle);
+            self.forward_handle = 0;
+        }
+
         if !self.service_name.is_empty() {
-            let sm = service::new_named_port_object::<sm::UserInterface>()?;
+            let sm = sm::get_user_interface()?;
             match self.is_mitm_service {
                 true => sm.get().atmosphere_uninstall_mitm(self.service_name)?,
                 false => sm.get().unregister_service(self.service_name)?
@@ -465,37 +732,56 @@ impl Drop for ServerHolder {
 
 pub struct HipcManager<'a> {
     session: sf::Session,
-    server_holder: &'a mut ServerHolder
+    server_holder: &'a mut ServerHolder,
+    pointer_buffer_size: u16,
+    new_sessions: &'a mut ArrayVec<[ServerHolder; MAX_COUNT]>
 }
 
 impl<'a> HipcManager<'a> {
-    pub fn new(server_holder: &'a mut ServerHolder) -> Self {
-        Self { session: sf::Session::new(), server_holder: server_holder }
+    pub fn new(server_holder: &'a mut ServerHolder, pointer_buffer_size: u16, new_sessions: &'a mut ArrayVec<[ServerHolder; MAX_COUNT]>) -> Self {
+        Self { session: sf::Session::new(), server_holder: server_holder, pointer_buffer_size: pointer_buffer_size, new_sessions: new_sessions }
     }
-}
 
-// TODO: implement control commands (currently stubbed calls)
+    fn push_holder(&mut self, server_holder: ServerHolder) -> Result<()> {
+        match self.new_sessions.try_push(server_holder) {
+            Ok(()) => Ok(()),
+            Err(_) => Err(results::hipc::ResultOutOfSessions::make())
+        }
+    }
+}
 
 impl<'a> IHipcManager for HipcManager<'a> {
     fn convert_current_object_to_domain(&mut self) -> Result<DomainObjectId> {
         self.server_holder.convert_to_domain()
     }
 
-    fn copy_from_current_domain(&mut self, _domain_object_id: DomainObjectId) -> Result<sf::MoveHandle> {
-        Err(ResultCode::new(0xBAD))
+    fn copy_from_current_domain(&mut self, domain_object_id: DomainObjectId) -> Result<sf::MoveHandle> {
+        let object = self.server_holder.get_domain_object(domain_object_id)?;
+        let (server_handle, client_handle) = transport::create_session()?;
+        self.push_holder(ServerHolder::new_shared_session(server_handle, object))?;
+        Ok(sf::MoveHandle::from(client_handle))
     }
 
     fn clone_current_object(&mut self) -> Result<sf::MoveHandle> {
-        Err(ResultCode::new(0xBAD))
+        // Mitm'd sessions need their own forward session too
+        let mut forward_handle: svc::Handle = 0;
+        if self.server_holder.is_mitm_service {
+            forward_handle = ObjectInfo::from_handle(self.server_holder.forward_handle).clone_current_object()?.handle;
+        }
+
+        let (server_handle, client_handle) = transport::create_session()?;
+        let server_holder = self.server_holder.make_clone_session(server_handle, forward_handle);
+        self.push_holder(server_holder)?;
+        Ok(sf::MoveHandle::from(client_handle))
     }
 
     fn query_pointer_buffer_size(&mut self) -> Result<u16> {
-        // TODO
-        Ok(0)
+        Ok(self.pointer_buffer_size)
     }
 
     fn clone_current_object_ex(&mut self, _tag: u32) -> Result<sf::MoveHandle> {
-        Err(ResultCode::new(0xBAD))
+        // The tag is ignored, like Atmosphere does
+        self.clone_current_object()
     }
 }
 
@@ -505,13 +791,7 @@ impl<'a> sf::IObject for HipcManager<'a> {
     }
 
     fn get_command_table(&self) -> sf::CommandMetadataTable {
-        ipc_server_make_command_table!(
-            convert_current_object_to_domain: 0,
-            copy_from_current_domain: 1,
-            clone_current_object: 2,
-            query_pointer_buffer_size: 3,
-            clone_current_object_ex: 4
-        )
+        Self::make_command_table()
     }
 }
 
@@ -532,9 +812,7 @@ impl<S: IMitmService> sf::IObject for MitmQueryServer<S> {
     }
 
     fn get_command_table(&self) -> sf::CommandMetadataTable {
-        ipc_server_make_command_table!(
-            should_mitm: 65000
-        )
+        Self::make_command_table()
     }
 }
 
@@ -559,159 +837,179 @@ pub trait INamedPort: IServerObject {
     fn get_max_sesssions() -> i32;
 }
 
-// TODO: use const generics to reduce memory usage, like libstratosphere does?
+type HolderList = ArrayVec<[ServerHolder; MAX_COUNT]>;
+
+// Copy of a whole request message, as received in the IPC buffer
+type RequestBuffer = [u8; 0x100];
 
-pub struct ServerManager {
-    server_holders: ArrayVec<[ServerHolder; MAX_COUNT]>,
-    wait_handles: [svc::Handle; MAX_COUNT]
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+enum RequestSource {
+    // Received through the kernel once the session gets signaled
+    Kernel,
+    // Already placed in the session's message buffer by the loopback transport, and not replied through the kernel
+    Loopback,
+    // Previously deferred, and copied back to the session's message buffer to be processed again
+    Deferred
 }
 
-impl ServerManager {
-    pub fn new() -> Self {
-        Self { server_holders: ArrayVec::new(), wait_handles: [0; MAX_COUNT] }
+// A session holder (whose domain objects, if any, are kept in its domain table) or a server (port) holder, plus the sessions opened while processing it
+// Worker threads take groups out of the manager's tables while processing them, so each group is only accessed by one thread at a time
+struct HolderGroup {
+    server_holders: HolderList,
+    // Set when a command deferred its response (see ServerManager::trigger_deferred_requests())
+    deferred_request: Option<Box<RequestBuffer>>
+}
+
+impl HolderGroup {
+    fn new() -> Self {
+        Self { server_holders: ArrayVec::new(), deferred_request: None }
     }
-    
-    fn prepare_wait_handles(&mut self) -> &[svc::Handle] {
-        let mut handles_index: usize = 0;
+
+    fn push_holder(&mut self, holder: ServerHolder) -> Result<()> {
+        match self.server_holders.try_push(holder) {
+            Ok(()) => Ok(()),
+            Err(_) => Err(results::hipc::ResultOutOfSessions::make())
+        }
+    }
+
+    fn get_session_holder(&mut self, handle: svc::Handle) -> Result<&mut ServerHolder> {
         for server_holder in &mut self.server_holders {
-            let server_info = server_holder.server.get().get_info();
-            if (server_info.handle != 0) && server_info.owns_handle {
-                self.wait_handles[handles_index] = server_info.handle;
-                handles_index += 1;
+            if (server_holder.info.handle == handle) && (server_holder.handle_type == WaitHandleType::Session) {
+                return Ok(server_holder);
             }
         }
+        Err(results::hipc::ResultSessionClosed::make())
+    }
 
-        unsafe { core::slice::from_raw_parts(self.wait_handles.as_ptr(), handles_index) }
+    fn get_object_lock(&self, handle: svc::Handle) -> Option<ObjectLock> {
+        for server_holder in &self.server_holders {
+            if (server_holder.info.handle == handle) && (server_holder.handle_type == WaitHandleType::Session) {
+                return Some(server_holder.object_lock.clone());
+            }
+        }
+        None
     }
 
-    fn push_holder(&mut self, holder: ServerHolder) -> Result<()> {
-        match self.server_holders.try_push(holder) {
-            Ok(()) => Ok(()),
-            Err(_) => Err(ResultCode::new(0x12))
-        }
-    }
-
-    fn handle_request_command(&mut self, ctx: &mut CommandContext, rq_id: u32, command_type: CommandType, domain_command_type: DomainCommandType, ipc_buf_backup: &[u8], domain_table: DomainTable) -> Result<DomainTable> {
-        let mut new_domain_table = domain_table;
-        let is_domain = ctx.object_info.is_domain();
-        let mut do_handle_request = || -> Result<()> {
-            let mut new_sessions: ArrayVec<[ServerHolder; MAX_COUNT]> = ArrayVec::new();
-            for server_holder in &mut self.server_holders {
-                let server_info = server_holder.server.get().get_info();
-                if (server_info.handle == ctx.object_info.handle) && (server_info.domain_object_id == ctx.object_info.domain_object_id) {
-                    let send_to_forward_handle = || -> Result<()> {
-                        let ipc_buf = get_ipc_buffer();
-                        unsafe {
-                            core::ptr::copy(ipc_buf_backup.as_ptr(), ipc_buf, ipc_buf_backup.len());
-                        }
-                        // Let the original service take care of the command for us.
-                        svc::send_sync_request(server_holder.forward_handle)
-                    };
-                    
-                    // Nothing done on success here, as if the command succeeds it will automatically respond by itself.
-                    let mut command_found = false;
-                    for command in server_holder.server.get().get_command_table() {
-                        if command.rq_id == rq_id {
-                            command_found = true;
-                            let mut server_ctx = ServerContext::new(ctx, DataWalker::empty(), domain_table, &mut new_sessions);
-                            if let Err(rc) = server_holder.server.get().call_self_command(command.command_fn, &mut server_ctx) {
-                                if server_holder.is_mitm_service && results::sm::mitm::ResultShouldForwardToSession::matches(rc) {
-                                    if let Err(rc) = send_to_forward_handle() {
-                                        write_request_command_response_on_ipc_buffer(ctx, rc, command_type);
-                                    }
-                                }
-                                else {
-                                    write_request_command_response_on_ipc_buffer(ctx, rc, command_type);
-                                }
-                            }
-                            else {
-                                new_domain_table = server_ctx.domain_table;
-                            }
+    // Sessions opened by a session's commands (clones, copies, sent objects) may share objects with it, so they share its object lock too
+    fn push_new_sessions(&mut self, new_sessions: &mut ArrayVec<[ServerHolder; MAX_COUNT]>, object_lock: Option<&ObjectLock>) -> Result<()> {
+        while let Some(mut holder) = new_sessions.pop_at(0) {
+            if let Some(object_lock) = object_lock {
+                holder.object_lock = object_lock.clone();
+            }
+            self.push_holder(holder)?;
+        }
+        Ok(())
+    }
+
+    fn handle_request_command(&mut self, ctx: &mut CommandContext, rq_id: u32, command_type: CommandType, domain_command_type: DomainCommandType, ipc_buf_backup: &RequestBuffer, pointer_buffer: Option<PointerBuffer>, can_defer: bool) -> Result<()> {
+        let mut is_deferred = false;
+        let mut new_sessions: ArrayVec<[ServerHolder; MAX_COUNT]> = ArrayVec::new();
+        let object_lock = {
+            let server_holder = self.get_session_holder(ctx.object_info.handle)?;
+            let target = match server_holder.domain_table {
+                Some(ref mut domain_table) => match domain_command_type {
+                    DomainCommandType::SendMessage => domain_table.get(ctx.object_info.domain_object_id)?,
+                    DomainCommandType::Close => {
+                        // Dropping the table's reference is enough, the session (and the rest of the domain) stays open
+                        let rc = match domain_table.deallocate(ctx.object_info.domain_object_id) {
+                            Ok(()) => ResultSuccess::make(),
+                            Err(rc) => rc
+                        };
+                        return write_request_command_response_on_ipc_buffer(ctx, rc, command_type);
+                    },
+                    DomainCommandType::Invalid => return Err(results::cmif::ResultInvalidInputHeader::make())
+                },
+                None => server_holder.server.clone()
+            };
+
+            let forward_handle = server_holder.forward_handle;
+            let send_to_forward_handle = || -> Result<()> {
+                let ipc_buf = transport::get_message_buffer(forward_handle);
+                unsafe {
+                    core::ptr::copy(ipc_buf_backup.as_ptr(), ipc_buf, ipc_buf_backup.len());
+                }
+                // Let the original service take care of the command for us.
+                transport::send_sync_request(forward_handle)
+            };
+
+            // Nothing done on success here, as if the command succeeds it will automatically respond by itself.
+            let mut command_found = false;
+            for command in target.get().get_command_table() {
+                if command.rq_id == rq_id {
+                    command_found = true;
+                    let mut server_ctx = ServerContext::new(ctx, DataWalker::empty(), server_holder.domain_table.as_deref_mut(), &mut new_sessions, pointer_buffer);
+                    if let Err(rc) = target.get().call_self_command(command.command_fn, &mut server_ctx) {
+                        if can_defer && results::cmif::ResultRequestDeferred::matches(rc) {
+                            // No response for now, the command will be processed again once resumed
+                            is_deferred = true;
                         }
-                    }
-                    if !command_found {
-                        if server_holder.is_mitm_service {
+                        else if server_holder.is_mitm_service && results::sm::mitm::ResultShouldForwardToSession::matches(rc) {
                             if let Err(rc) = send_to_forward_handle() {
-                                write_request_command_response_on_ipc_buffer(ctx, rc, command_type);
+                                write_request_command_response_on_ipc_buffer(ctx, rc, command_type)?;
                             }
                         }
                         else {
-                            write_request_command_response_on_ipc_buffer(ctx, results::cmif::ResultInvalidCommandRequestId::make(), command_type);
+                            write_request_command_response_on_ipc_buffer(ctx, rc, command_type)?;
                         }
                     }
-                    break;
                 }
             }
-    
-            loop {
-                match new_sessions.pop_at(0) {
-                    Some(holder) => self.push_holder(holder)?,
-                    None => break
-                };
+            if !command_found {
+                if server_holder.is_mitm_service {
+                    if let Err(rc) = send_to_forward_handle() {
+                        write_request_command_response_on_ipc_buffer(ctx, rc, command_type)?;
+                    }
+                }
+                else {
+                    write_request_command_response_on_ipc_buffer(ctx, results::cmif::ResultInvalidCommandRequestId::make(), command_type)?;
+                }
             }
-
-            Ok(())
+            server_holder.object_lock.clone()
         };
 
-        match domain_command_type {
-            DomainCommandType::Invalid => {
-                // Invalid command type might mean that the session ain't a domain :P
-                match is_domain {
-                    false => do_handle_request()?,
-                    true => return Err(ResultCode::new(0xd3d))
-                };
-            },
-            DomainCommandType::SendMessage => do_handle_request()?,
-            DomainCommandType::Close => {
-                new_domain_table.deallocate(ctx.object_info.domain_object_id)?;
-
-                let mut index: usize = 0;
-                for server_holder in &mut self.server_holders {
-                    let mut server_info = server_holder.server.get().get_info();
-                    if (server_info.handle == ctx.object_info.handle) && (server_info.domain_object_id == ctx.object_info.domain_object_id) {
-                        // Update the info without the domain ID (so dropping would only close the handle, and only if this session owns it)
-                        server_info.domain_object_id = 0;
-                        server_holder.server.get().set_info(server_info);
-
-                        // Now we can just remove the holder from the array so it gets dropped.
-                        self.server_holders.pop_at(index);
-                        break;
-                    }
-                    index += 1;
-                }
+        if is_deferred {
+            // Nothing from this attempt reaches the client: the command will produce its outputs again once resumed
+            new_sessions.clear();
+            while ctx.out_params.pop_copy_handle().is_ok() {}
+            while let Ok(handle) = ctx.out_params.pop_move_handle() {
+                let _ = svc::close_handle(handle);
             }
+            self.deferred_request = Some(Box::new(*ipc_buf_backup));
         }
-
-        Ok(new_domain_table)
+        else {
+            self.push_new_sessions(&mut new_sessions, Some(&object_lock))?;
+        }
+        Ok(())
     }
 
-    fn handle_control_command(&mut self, ctx: &mut CommandContext, rq_id: u32, command_type: CommandType) -> Result<()> {
-        for server_holder in &mut self.server_holders {
-            let server_info = server_holder.server.get().get_info();
-            if server_info.handle == ctx.object_info.handle {
-                let mut hipc_manager = HipcManager::new(server_holder);
-                // Nothing done on success here, as if the command succeeds it will automatically respond by itself.
-                let mut command_found = false;
-                for command in hipc_manager.get_command_table() {
-                    if command.rq_id == rq_id {
-                        command_found = true;
-                        let mut unused_new_sessions: ArrayVec<[ServerHolder; MAX_COUNT]> = ArrayVec::new();
-                        let mut server_ctx = ServerContext::new(ctx, DataWalker::empty(), DomainTable::new(), &mut unused_new_sessions);
-                        if let Err(rc) = hipc_manager.call_self_command(command.command_fn, &mut server_ctx) {
-                            write_control_command_response_on_ipc_buffer(ctx, rc, command_type);
-                        }
+    fn handle_control_command(&mut self, ctx: &mut CommandContext, rq_id: u32, command_type: CommandType, pointer_buffer_size: u16) -> Result<()> {
+        let mut new_sessions: ArrayVec<[ServerHolder; MAX_COUNT]> = ArrayVec::new();
+        let object_lock = {
+            let server_holder = self.get_session_holder(ctx.object_info.handle)?;
+            let object_lock = server_holder.object_lock.clone();
+            let mut hipc_manager = HipcManager::new(server_holder, pointer_buffer_size, &mut new_sessions);
+            // Nothing done on success here, as if the command succeeds it will automatically respond by itself.
+            let mut command_found = false;
+            for command in hipc_manager.get_command_table() {
+                if command.rq_id == rq_id {
+                    command_found = true;
+                    let mut unused_new_sessions: ArrayVec<[ServerHolder; MAX_COUNT]> = ArrayVec::new();
+                    let mut server_ctx = ServerContext::new(ctx, DataWalker::empty(), None, &mut unused_new_sessions, None);
+                    if let Err(rc) = hipc_manager.call_self_command(command.command_fn, &mut server_ctx) {
+                        write_control_command_response_on_ipc_buffer(ctx, rc, command_type)?;
                     }
                 }
-                if !command_found {
-                    write_control_command_response_on_ipc_buffer(ctx, results::cmif::ResultInvalidCommandRequestId::make(), command_type);
-                }
-                break;
             }
-        }
-        Ok(())
+            if !command_found {
+                write_control_command_response_on_ipc_buffer(ctx, results::cmif::ResultInvalidCommandRequestId::make(), command_type)?;
+            }
+            object_lock
+        };
+
+        self.push_new_sessions(&mut new_sessions, Some(&object_lock))
     }
 
-    fn process_signaled_handle(&mut self, handle: svc::Handle) -> Result<()> {
+    fn process_signaled_handle(&mut self, handle: svc::Handle, source: RequestSource, pointer_buffer: PointerBuffer) -> Result<()> {
         let mut server_found = false;
         let mut index: usize = 0;
         let mut should_close_session = false;
@@ -721,18 +1019,18 @@ impl ServerManager {
         let mut command_type = CommandType::Invalid;
         let mut domain_cmd_type = DomainCommandType::Invalid;
         let mut rq_id: u32 = 0;
-        let mut ipc_buf_backup: [u8; 0x100] = [0; 0x100];
-        let mut domain_table = DomainTable::new();
+        let mut ipc_buf_backup: RequestBuffer = [0; 0x100];
 
         for server_holder in &mut self.server_holders {
-            let server_info = server_holder.server.get().get_info();
-            if (server_info.handle == handle) && server_info.owns_handle {
+            let server_info = server_holder.info;
+            if server_info.handle == handle {
                 server_found = true;
                 // index = i;
                 match server_holder.handle_type {
                     WaitHandleType::Session => {
-                        match svc::reply_and_receive(&handle, 1, 0, -1) {
-                            Err(rc) => {
+                        if source == RequestSource::Kernel {
+                            write_receive_list_on_ipc_buffer(pointer_buffer)?;
+                            if let Err(rc) = svc::reply_and_receive(&handle, 1, 0, -1) {
                                 if results::os::ResultSessionClosed::matches(rc) {
                                     should_close_session = true;
                                     break;
@@ -740,14 +1038,13 @@ impl ServerManager {
                                 else {
                                     return Err(rc);
                                 }
-                            },
-                            _ => {}
-                        };
+                            }
+                        }
 
-                        unsafe { core::ptr::copy(get_ipc_buffer(), ipc_buf_backup.as_mut_ptr(), ipc_buf_backup.len()) };
+                        unsafe { core::ptr::copy(transport::get_message_buffer(handle), ipc_buf_backup.as_mut_ptr(), ipc_buf_backup.len()) };
 
                         ctx = CommandContext::new(server_info);
-                        command_type = read_command_from_ipc_buffer(&mut ctx);
+                        command_type = read_command_from_ipc_buffer(&mut ctx)?;
                         match command_type {
                             CommandType::Request | CommandType::RequestWithContext => {
                                 match read_request_command_from_ipc_buffer(&mut ctx) {
@@ -760,11 +1057,13 @@ impl ServerManager {
                                         ctx.object_info = base_info;
                                         domain_cmd_type = domain_command_type;
                                         rq_id = request_id;
-                                        domain_table = server_holder.domain_table;
                                     },
                                     Err(rc) => return Err(rc)
                                 };
                             },
+                            CommandType::TipcRequest => {
+                                rq_id = read_tipc_request_command_from_ipc_buffer(&mut ctx)?;
+                            },
                             CommandType::Control | CommandType::ControlWithContext => {
                                 match read_control_command_from_ipc_buffer(&mut ctx) {
                                     Ok(control_rq_id) => {
@@ -782,7 +1081,7 @@ impl ServerManager {
                         let mut forward_handle: svc::Handle = 0;
                         
                         if server_holder.is_mitm_service {
-                            let sm = service::new_named_port_object::<sm::UserInterface>()?;
+                            let sm = sm::get_user_interface()?;
                             let (_info, session_handle) = sm.get().atmosphere_acknowledge_mitm_session(server_holder.service_name)?;
                             forward_handle = session_handle.handle;
                         }
@@ -796,6 +1095,9 @@ impl ServerManager {
         }
 
         let reply_impl = || -> Result<()> {
+            if source == RequestSource::Loopback {
+                return Ok(());
+            }
             match svc::reply_and_receive(&handle, 0, handle, 0) {
                 Err(rc) => {
                     if results::os::ResultTimeout::matches(rc) || results::os::ResultSessionClosed::matches(rc) {
@@ -810,25 +1112,36 @@ impl ServerManager {
         };
 
         match command_type {
-            CommandType::Request | CommandType::RequestWithContext => {
-                let new_domain_table = self.handle_request_command(&mut ctx, rq_id, command_type, domain_cmd_type, &ipc_buf_backup, domain_table)?;
-                for server_holder in &mut self.server_holders {
-                    let server_info = server_holder.server.get().get_info();
-                    if (server_info.handle == handle) && server_info.owns_handle {
-                        server_holder.domain_table = new_domain_table;
-                        break;
-                    }
+            CommandType::Request | CommandType::RequestWithContext | CommandType::TipcRequest => {
+                // Loopback clients can't be kept waiting for a response, and their buffers are used as they are
+                // Pointer (send static) buffers are left in this thread's pointer buffer, which will be overwritten before the request is resumed
+                let can_defer = (source != RequestSource::Loopback) && ctx.send_statics.is_empty();
+                let request_pointer_buffer = match source {
+                    RequestSource::Loopback => None,
+                    _ => Some(pointer_buffer)
+                };
+                self.handle_request_command(&mut ctx, rq_id, command_type, domain_cmd_type, &ipc_buf_backup, request_pointer_buffer, can_defer)?;
+                if self.deferred_request.is_none() {
+                    reply_impl()?;
                 }
-                reply_impl()?;
             },
             CommandType::Control | CommandType::ControlWithContext => {
-                self.handle_control_command(&mut ctx, rq_id, command_type)?;
+                // Loopback clients have no use for a pointer buffer
+                let pointer_buffer_size = match source {
+                    RequestSource::Loopback => 0,
+                    _ => pointer_buffer.size as u16
+                };
+                self.handle_control_command(&mut ctx, rq_id, command_type, pointer_buffer_size)?;
                 reply_impl()?;
             },
             CommandType::Close => {
-                write_close_command_response_on_ipc_buffer(&mut ctx);
+                write_close_command_response_on_ipc_buffer(&mut ctx)?;
                 reply_impl()?;
                 should_close_session = true;
+            },
+            CommandType::TipcClose => {
+                // TIPC close requests don't get a response
+                should_close_session = true;
             }
             _ => { /* TODO - or maybe nothing to do here? */ }
         };
@@ -837,43 +1150,222 @@ impl ServerManager {
             self.server_holders.pop_at(index);
         }
 
-        loop {
-            match new_sessions.pop_at(0) {
-                Some(holder) => self.push_holder(holder)?,
-                None => break
-            };
-        }
+        // Only accepted sessions, which get their own objects
+        self.push_new_sessions(&mut new_sessions, None)?;
 
         match server_found {
             true => Ok(()),
             false => Err(ResultCode::new(0x123))
         }
     }
-    
-    pub fn register_server<S: IServerObject + 'static>(&mut self, handle: svc::Handle, service_name: sm::ServiceName, is_mitm_service: bool) -> Result<()> {
+}
+
+enum HolderSlot {
+    Empty,
+    Linked(ServerHolder),
+    // Session whose current request was deferred, kept out of the wait list until it's resumed
+    Deferred(ServerHolder, Box<RequestBuffer>),
+    // Taken out by a thread processing it, which will put it back (or free the slot) when done
+    Processing
+}
+
+const EMPTY_HOLDER_SLOT: HolderSlot = HolderSlot::Empty;
+
+struct HolderTable<const N: usize> {
+    slots: [HolderSlot; N]
+}
+
+impl<const N: usize> HolderTable<N> {
+    fn new() -> Self {
+        Self { slots: [EMPTY_HOLDER_SLOT; N] }
+    }
+
+    fn push_holder(&mut self, holder: ServerHolder) -> Result<()> {
+        for slot in self.slots.iter_mut() {
+            if let HolderSlot::Empty = slot {
+                *slot = HolderSlot::Linked(holder);
+                return Ok(());
+            }
+        }
+        Err(results::hipc::ResultOutOfSessions::make())
+    }
+
+    fn push_wait_handles(&mut self, handles: &mut ArrayVec<[svc::Handle; MAX_COUNT]>) -> Result<()> {
+        for slot in self.slots.iter_mut() {
+            if let HolderSlot::Linked(server_holder) = slot {
+                let server_info = server_holder.info;
+                if (server_info.handle != 0) && server_info.owns_handle {
+                    result_return_if!(handles.try_push(server_info.handle).is_err(), results::os::ResultOutOfRange);
+                }
+            }
+        }
+        Ok(())
+    }
+
+    fn push_deferred_handles(&mut self, handles: &mut ArrayVec<[svc::Handle; MAX_COUNT]>) {
+        for slot in self.slots.iter_mut() {
+            if let HolderSlot::Deferred(server_holder, _) = slot {
+                let _ = handles.try_push(server_holder.info.handle);
+            }
+        }
+    }
+
+    // Returns the deferred request of the group's session, if it had one
+    fn take_group(&mut self, handle: svc::Handle, group: &mut HolderGroup, slot_indices: &mut ArrayVec<[usize; MAX_COUNT]>) -> Result<Option<Box<RequestBuffer>>> {
+        let mut deferred_request: Option<Box<RequestBuffer>> = None;
+        for (i, slot) in self.slots.iter_mut().enumerate() {
+            let is_in_group = match slot {
+                HolderSlot::Linked(server_holder) | HolderSlot::Deferred(server_holder, _) => server_holder.info.handle == handle,
+                _ => false
+            };
+            if is_in_group {
+                let server_holder = match cmem::replace(slot, HolderSlot::Processing) {
+                    HolderSlot::Linked(server_holder) => server_holder,
+                    HolderSlot::Deferred(server_holder, request) => {
+                        deferred_request = Some(request);
+                        server_holder
+                    },
+                    _ => unreachable!()
+                };
+                group.push_holder(server_holder)?;
+                let _ = slot_indices.try_push(i);
+            }
+        }
+        Ok(deferred_request)
+    }
+
+    // Holders which don't fit in the group's original slots (new sessions) are left in the group
+    fn put_back_group(&mut self, group: &mut HolderGroup, slot_indices: &ArrayVec<[usize; MAX_COUNT]>) {
+        for i in slot_indices {
+            self.slots[*i] = match group.server_holders.pop_at(0) {
+                Some(server_holder) => {
+                    // The deferred request stays with the session itself, which is the one waited on
+                    let owns_handle = server_holder.info.owns_handle;
+                    match group.deferred_request.take() {
+                        Some(request) if owns_handle => HolderSlot::Deferred(server_holder, request),
+                        request => {
+                            group.deferred_request = request;
+                            HolderSlot::Linked(server_holder)
+                        }
+                    }
+                },
+                None => HolderSlot::Empty
+            };
+        }
+    }
+}
+
+struct ServerTables<const N_SESSIONS: usize, const N_PORTS: usize> {
+    sessions: HolderTable<N_SESSIONS>,
+    ports: HolderTable<N_PORTS>
+}
+
+impl<const N_SESSIONS: usize, const N_PORTS: usize> ServerTables<N_SESSIONS, N_PORTS> {
+    fn push_holder(&mut self, holder: ServerHolder) -> Result<()> {
+        match holder.handle_type {
+            WaitHandleType::Server => self.ports.push_holder(holder),
+            WaitHandleType::Session => self.sessions.push_holder(holder)
+        }
+    }
+}
+
+// Sessions and ports (servers) are kept in separate tables, sized at compile time
+// Several threads may process requests at the same time (see loop_process()): only one of them waits for the handles at once, and signaled sessions/ports are taken out of the wait list while being processed
+// Note that N_SESSIONS + N_PORTS (excluding domain objects, which aren't waited on) can't exceed the kernel's wait limit, minus one handle used internally (checked at compile time when processing)
+pub struct ServerManager<const N_SESSIONS: usize, const N_PORTS: usize> {
+    tables: sync::Mutex<ServerTables<N_SESSIONS, N_PORTS>>,
+    wait_lock: sync::Mutex<()>,
+    // Wakes up the waiting thread when the wait list changes
+    wait_list_updated_event: os::Event,
+    resume_requested: AtomicBool,
+    // Each processing thread has its own pointer buffer of this size
+    pointer_buffer_size: u16
+}
+
+// Holders are only accessed with the tables locked, or by the only thread which took them out for processing
+// Their non-thread-safe objects may be shared by several sessions, but those sessions share an object lock, held while processing any of them
+// Objects shared with sessions not opened through the manager (like ones kept in globals) aren't covered by this, so they must be thread-safe themselves
+unsafe impl<const N_SESSIONS: usize, const N_PORTS: usize> Send for ServerManager<N_SESSIONS, N_PORTS> {}
+unsafe impl<const N_SESSIONS: usize, const N_PORTS: usize> Sync for ServerManager<N_SESSIONS, N_PORTS> {}
+
+impl<const N_SESSIONS: usize, const N_PORTS: usize> ServerManager<N_SESSIONS, N_PORTS> {
+    // Underflows (failing to compile) when the handles could exceed the wait limit, the wait list update event included
+    const FREE_WAIT_HANDLE_COUNT: usize = MAX_COUNT - 1 - (N_SESSIONS + N_PORTS);
+
+    pub fn new() -> Result<Self> {
+        Self::new_with_pointer_buffer_size(0)
+    }
+
+    // Clients send pointer buffers (if they fit) instead of map-alias ones for auto-select buffers, which avoids memory mapping for small buffers
+    pub fn new_with_pointer_buffer_size(pointer_buffer_size: u16) -> Result<Self> {
+        Ok(Self { tables: sync::Mutex::new(ServerTables { sessions: HolderTable::new(), ports: HolderTable::new() }), wait_lock: sync::Mutex::new(()), wait_list_updated_event: os::Event::new(true)?, resume_requested: AtomicBool::new(false), pointer_buffer_size: pointer_buffer_size })
+    }
+
+    fn push_holder(&self, holder: ServerHolder) -> Result<()> {
+        self.tables.lock().push_holder(holder)?;
+        self.wait_list_updated_event.signal()
+    }
+
+    // Returns whether the group is a port one, and the session's deferred request (if any)
+    fn take_group(&self, handle: svc::Handle, group: &mut HolderGroup, slot_indices: &mut ArrayVec<[usize; MAX_COUNT]>) -> Result<(bool, Option<Box<RequestBuffer>>)> {
+        let mut tables = self.tables.lock();
+        tables.ports.take_group(handle, group, slot_indices)?;
+        if !slot_indices.is_empty() {
+            return Ok((true, None));
+        }
+        let deferred_request = tables.sessions.take_group(handle, group, slot_indices)?;
+        Ok((false, deferred_request))
+    }
+
+    fn put_back_group(&self, is_port: bool, group: &mut HolderGroup, slot_indices: &ArrayVec<[usize; MAX_COUNT]>) -> Result<()> {
+        {
+            let mut tables = self.tables.lock();
+            match is_port {
+                true => tables.ports.put_back_group(group, slot_indices),
+                false => tables.sessions.put_back_group(group, slot_indices)
+            };
+            while let Some(server_holder) = group.server_holders.pop_at(0) {
+                tables.sessions.push_holder(server_holder)?;
+            }
+        }
+        self.wait_list_updated_event.signal()
+    }
+
+    fn process_group(&self, handle: svc::Handle, source: RequestSource, pointer_buffer: PointerBuffer, is_port: bool, mut group: HolderGroup, slot_indices: ArrayVec<[usize; MAX_COUNT]>) -> Result<()> {
+        // Sessions sharing objects with this one might be getting processed by other threads right now
+        let object_lock = group.get_object_lock(handle);
+        let _object_guard = object_lock.as_ref().map(|object_lock| object_lock.lock());
+
+        let rc = group.process_signaled_handle(handle, source, pointer_buffer);
+        // Whatever happened, the holders must get back to the tables (or be freed, if their session was closed)
+        self.put_back_group(is_port, &mut group, &slot_indices)?;
+        rc
+    }
+
+    pub fn register_server<S: IServerObject + 'static>(&self, handle: svc::Handle, service_name: sm::ServiceName, is_mitm_service: bool) -> Result<()> {
         self.push_holder(ServerHolder::new_server::<S>(handle, service_name, is_mitm_service))
     }
     
-    pub fn register_session<S: IServerObject + 'static>(&mut self, handle: svc::Handle) -> Result<()> {
+    pub fn register_session<S: IServerObject + 'static>(&self, handle: svc::Handle) -> Result<()> {
         self.push_holder(ServerHolder::new_server_session::<S>(handle))
     }
     
-    pub fn register_service_server<S: IService + 'static>(&mut self) -> Result<()> {
+    pub fn register_service_server<S: IService + 'static>(&self) -> Result<()> {
         let service_name = sm::ServiceName::new(S::get_name());
         
         let service_handle = {
-            let sm = service::new_named_port_object::<sm::UserInterface>()?;
+            let sm = sm::get_user_interface()?;
             sm.get().register_service(service_name, false, S::get_max_sesssions())?
         };
 
         self.register_server::<S>(service_handle.handle, service_name, false)
     }
     
-    pub fn register_mitm_service_server<S: IMitmService + 'static>(&mut self) -> Result<()> {
+    pub fn register_mitm_service_server<S: IMitmService + 'static>(&self) -> Result<()> {
         let service_name = sm::ServiceName::new(S::get_name());
 
         let (mitm_handle, query_handle) = {
-            let sm = service::new_named_port_object::<sm::UserInterface>()?;
+            let sm = sm::get_user_interface()?;
             sm.get().atmosphere_install_mitm(service_name)?
         };
 
@@ -881,35 +1373,128 @@ impl ServerManager {
         self.register_session::<MitmQueryServer<S>>(query_handle.handle)
     }
 
-    pub fn register_named_port_server<S: INamedPort + 'static>(&mut self) -> Result<()> {
+    pub fn register_named_port_server<S: INamedPort + 'static>(&self) -> Result<()> {
         let port_handle = svc::manage_named_port(S::get_port_name().as_ptr(), S::get_max_sesssions())?;
 
         self.register_server::<S>(port_handle, sm::ServiceName::empty(), false)
     }
 
-    fn process_impl(&mut self) -> Result<()> {
-        let handles = self.prepare_wait_handles();
-        let index = wait::wait_handles(handles, -1)?;
+    fn process_impl(&self, pointer_buffer: PointerBuffer) -> Result<()> {
+        // Only managers which wait for requests are limited (loopback ones aren't)
+        let _ = Self::FREE_WAIT_HANDLE_COUNT;
+
+        if self.resume_requested.swap(false, Ordering::SeqCst) {
+            return self.process_deferred_requests(pointer_buffer);
+        }
+
+        let mut group = HolderGroup::new();
+        let mut slot_indices: ArrayVec<[usize; MAX_COUNT]> = ArrayVec::new();
+        let (signaled_handle, is_port) = {
+            // The signaled holders must be taken out before letting any other thread wait, or it would process them too
+            let _wait_guard = self.wait_lock.lock();
+            loop {
+                let mut handles: ArrayVec<[svc::Handle; MAX_COUNT]> = ArrayVec::new();
+                {
+                    let mut tables = self.tables.lock();
+                    tables.ports.push_wait_handles(&mut handles)?;
+                    tables.sessions.push_wait_handles(&mut handles)?;
+                }
+
+                let mut waiters: ArrayVec<[wait::Waiter; MAX_COUNT]> = ArrayVec::new();
+                let _ = waiters.try_push(self.wait_list_updated_event.get_waiter()?);
+                for handle in &handles {
+                    if waiters.try_push(wait::Waiter::from_handle(*handle)).is_err() {
+                        return Err(results::os::ResultOutOfRange::make());
+                    }
+                }
+
+                let index = wait::wait(&waiters, -1)?;
+                if index == 0 {
+                    if self.resume_requested.load(Ordering::SeqCst) {
+                        // Let this (or any other) thread resume the deferred requests
+                        return Ok(());
+                    }
+                    // Some holder was added or put back, wait again with the updated list
+                    continue;
+                }
+
+                let signaled_handle = handles[index - 1];
+                let (is_port, _) = self.take_group(signaled_handle, &mut group, &mut slot_indices)?;
+                break (signaled_handle, is_port);
+            }
+        };
 
-        let signaled_handle = self.wait_handles[index];
-        self.process_signaled_handle(signaled_handle)?;
+        self.process_group(signaled_handle, RequestSource::Kernel, pointer_buffer, is_port, group, slot_indices)
+    }
 
+    // Note that the pointer buffer isn't kept for deferred requests, so commands taking pointer buffers can't defer their response (see process_signaled_handle)
+    fn process_deferred_requests(&self, pointer_buffer: PointerBuffer) -> Result<()> {
+        let mut deferred_handles: ArrayVec<[svc::Handle; MAX_COUNT]> = ArrayVec::new();
+        self.tables.lock().sessions.push_deferred_handles(&mut deferred_handles);
+
+        for handle in deferred_handles {
+            let mut group = HolderGroup::new();
+            let mut slot_indices: ArrayVec<[usize; MAX_COUNT]> = ArrayVec::new();
+            let (is_port, deferred_request) = self.take_group(handle, &mut group, &mut slot_indices)?;
+            match deferred_request {
+                Some(request) => {
+                    transport::get_message_buffer_slice(handle)[..request.len()].copy_from_slice(&request[..]);
+                    // Failing to resume a request shouldn't prevent resuming the rest
+                    let _ = self.process_group(handle, RequestSource::Deferred, pointer_buffer, is_port, group, slot_indices);
+                },
+                // Another thread already resumed it
+                None => self.put_back_group(is_port, &mut group, &slot_indices)?
+            };
+        }
         Ok(())
     }
 
-    pub fn loop_process(&mut self) -> Result<()> {
+    // Makes the manager process all deferred requests again, which will be responded to unless their commands defer them again
+    // Commands defer their response by returning results::cmif::ResultRequestDeferred, usually when waiting for something which another session or event will provide
+    pub fn trigger_deferred_requests(&self) -> Result<()> {
+        self.resume_requested.store(true, Ordering::SeqCst);
+        self.wait_list_updated_event.signal()
+    }
+
+    // Processes a request already placed in the session's message buffer, leaving the response there (see the loopback module)
+    pub fn process_loopback_request(&self, server_handle: svc::Handle) -> Result<()> {
+        let mut group = HolderGroup::new();
+        let mut slot_indices: ArrayVec<[usize; MAX_COUNT]> = ArrayVec::new();
+        let (is_port, _) = self.take_group(server_handle, &mut group, &mut slot_indices)?;
+        self.process_group(server_handle, RequestSource::Loopback, PointerBuffer::new(ptr::null_mut(), 0), is_port, group, slot_indices)
+    }
+
+    // May be called from several threads at once, each of them processing requests independently
+    pub fn loop_process(&self) -> Result<()> {
+        let mut pointer_buffer: Vec<u8> = vec![0; self.pointer_buffer_size as usize];
         loop {
-            match self.process_impl() {
-                Err(rc) => {
-                    // TODO: handle results properly here
-                    if results::os::ResultOperationCanceled::matches(rc) {
-                        break;
-                    }
-                },
-                _ => {}
+            if let Err(rc) = self.process_impl(PointerBuffer::from_slice(&mut pointer_buffer)) {
+                // TODO: handle results properly here
+                if results::os::ResultOperationCanceled::matches(rc) {
+                    break;
+                }
+                // The wait list won't get any smaller by retrying
+                if results::os::ResultOutOfRange::matches(rc) {
+                    return Err(rc);
+                }
             }
         }
 
         Ok(())
     }
+
+    // Runs loop_process() on the current thread and on extra_worker_count new worker threads, until all of them are done
+    pub fn loop_process_with_workers(manager: &Arc<Self>, extra_worker_count: usize, worker_stack_size: usize) -> Result<()> {
+        let mut workers: Vec<thread::JoinHandle<Result<()>>> = Vec::new();
+        for _ in 0..extra_worker_count {
+            let worker_manager = manager.clone();
+            workers.push(thread::Builder::new().name("nx.ipc.ServerWorker").stack_size(worker_stack_size).spawn(move || worker_manager.loop_process())?);
+        }
+
+        let rc = manager.loop_process();
+        for worker in workers {
+            worker.join()??;
+        }
+        rc
+    }
 }
\ No newline at end of file
@@ -56,41 +56,71 @@ pub enum LibraryAppletMode {
     AllForegroundInitiallyHidden,
 }
 
+#[sf::ipc_interface(client = "StorageAccessor")]
 pub trait IStorageAccessor {
-    ipc_interface_define_command!(get_size: () => (size: usize));
-    ipc_interface_define_command!(write: (offset: usize, buf: sf::InAutoSelectBuffer) => ());
-    ipc_interface_define_command!(read: (offset: usize, buf: sf::OutAutoSelectBuffer) => ());
+    #[ipc_rid(0)]
+    fn get_size(&mut self) -> Result<usize>;
+
+    #[ipc_rid(10)]
+    fn write(&mut self, offset: usize, buf: sf::InAutoSelectBuffer) -> Result<()>;
+
+    #[ipc_rid(11)]
+    fn read(&mut self, offset: usize, buf: sf::OutAutoSelectBuffer) -> Result<()>;
 }
 
+#[sf::ipc_interface(client = "Storage")]
 pub trait IStorage {
-    ipc_interface_define_command!(open: () => (storage_accessor: mem::Shared<dyn sf::IObject>));
+    #[ipc_rid(0)]
+    fn open(&mut self) -> Result<mem::Shared<StorageAccessor>>;
 }
 
+#[sf::ipc_interface(client = "LibraryAppletAccessor")]
 pub trait ILibraryAppletAccessor {
-    ipc_interface_define_command!(get_applet_state_changed_event: () => (applet_state_changed_event: sf::CopyHandle));
-    ipc_interface_define_command!(start: () => ());
-    ipc_interface_define_command!(push_in_data: (storage: mem::Shared<dyn sf::IObject>) => ());
+    #[ipc_rid(0)]
+    fn get_applet_state_changed_event(&mut self) -> Result<sf::CopyHandle>;
+
+    #[ipc_rid(10)]
+    fn start(&mut self) -> Result<()>;
+
+    #[ipc_rid(100)]
+    fn push_in_data(&mut self, storage: mem::Shared<dyn sf::IObject>) -> Result<()>;
 }
 
+#[sf::ipc_interface(client = "LibraryAppletCreator")]
 pub trait ILibraryAppletCreator {
-    ipc_interface_define_command!(create_library_applet: (applet_id: AppletId, applet_mode: LibraryAppletMode) => (library_applet_accessor: mem::Shared<dyn sf::IObject>));
-    ipc_interface_define_command!(create_storage: (size: usize) => (storage: mem::Shared<dyn sf::IObject>));
+    #[ipc_rid(0)]
+    fn create_library_applet(&mut self, applet_id: AppletId, applet_mode: LibraryAppletMode) -> Result<mem::Shared<LibraryAppletAccessor>>;
+
+    #[ipc_rid(10)]
+    fn create_storage(&mut self, size: usize) -> Result<mem::Shared<Storage>>;
 }
 
+#[sf::ipc_interface(client = "WindowController")]
 pub trait IWindowController {
-    ipc_interface_define_command!(acquire_foreground_rights: () => ());
+    #[ipc_rid(10)]
+    fn acquire_foreground_rights(&mut self) -> Result<()>;
 }
 
+#[sf::ipc_interface(client = "SelfController")]
 pub trait ISelfController {
-    ipc_interface_define_command!(set_screenshot_permission: (permission: ScreenShotPermission) => ());
+    #[ipc_rid(10)]
+    fn set_screenshot_permission(&mut self, permission: ScreenShotPermission) -> Result<()>;
 }
 
+#[sf::ipc_interface(client = "LibraryAppletProxy")]
 pub trait ILibraryAppletProxy {
-    ipc_interface_define_command!(get_self_controller: () => (self_controller: mem::Shared<dyn sf::IObject>));
-    ipc_interface_define_command!(get_window_controller: () => (window_controller: mem::Shared<dyn sf::IObject>));
-    ipc_interface_define_command!(get_library_applet_creator: () => (library_applet_creator: mem::Shared<dyn sf::IObject>));
+    #[ipc_rid(1)]
+    fn get_self_controller(&mut self) -> Result<mem::Shared<SelfController>>;
+
+    #[ipc_rid(2)]
+    fn get_window_controller(&mut self) -> Result<mem::Shared<WindowController>>;
+
+    #[ipc_rid(11)]
+    fn get_library_applet_creator(&mut self) -> Result<mem::Shared<LibraryAppletCreator>>;
 }
 
+#[sf::ipc_interface(client = "AllSystemAppletProxiesService")]
 pub trait IAllSystemAppletProxiesService {
-    ipc_interface_define_command!(open_library_applet_proxy: (process_id: sf::ProcessId, self_process_handle: sf::CopyHandle, applet_attribute: sf::InMapAliasBuffer) => (library_applet_proxy: mem::Shared<dyn sf::IObject>));
+    #[ipc_rid(201)]
+    fn open_library_applet_proxy(&mut self, process_id: sf::ProcessId, self_process_handle: sf::CopyHandle, applet_attribute: sf::InMapAliasBuffer) -> Result<mem::Shared<LibraryAppletProxy>>;
 }
\ No newline at end of file
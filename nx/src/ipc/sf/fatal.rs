@@ -9,6 +9,8 @@ pub enum Policy {
     ErrorScreen,
 }
 
+#[sf::ipc_interface(client = "Service")]
 pub trait IService {
-    ipc_interface_define_command!(throw_with_policy: (rc: ResultCode, policy: Policy, process_id: sf::ProcessId) => ());
+    #[ipc_rid(1)]
+    fn throw_with_policy(&mut self, rc: ResultCode, policy: Policy, process_id: sf::ProcessId) -> Result<()>;
 }
\ No newline at end of file
@@ -31,6 +31,69 @@ bit_enum! {
     }
 }
 
+bit_enum! {
+    DirectoryOpenMode (u32) {
+        ReadDirectories = bit!(0),
+        ReadFiles = bit!(1),
+        NoFileSize = bit!(31)
+    }
+}
+
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+#[repr(u8)]
+pub enum DirectoryEntryType {
+    Directory = 0,
+    File = 1
+}
+
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+#[repr(u32)]
+pub enum OperationId {
+    Clear = 0,
+    ClearSignature = 1,
+    InvalidateCache = 2,
+    QueryRange = 3
+}
+
+#[derive(Copy, Clone)]
+#[repr(C)]
+pub struct DirectoryEntry {
+    pub name: [u8; 0x301],
+    pub attr: u8,
+    pub pad: [u8; 2],
+    pub entry_type: DirectoryEntryType,
+    pub pad_2: [u8; 3],
+    pub file_size: usize
+}
+
+impl DirectoryEntry {
+    pub const fn empty() -> Self {
+        Self { name: [0; 0x301], attr: 0, pad: [0; 2], entry_type: DirectoryEntryType::Directory, pad_2: [0; 3], file_size: 0 }
+    }
+
+    pub fn get_name(&mut self) -> Result<&'static str> {
+        util::get_str_from_pointer(&mut self.name as *mut _ as *mut u8, self.name.len())
+    }
+}
+
+#[derive(Copy, Clone)]
+#[repr(C)]
+pub struct FileTimeStampRaw {
+    pub create: i64,
+    pub modify: i64,
+    pub access: i64,
+    pub is_local_time: bool,
+    pub pad: [u8; 7]
+}
+
+#[derive(Copy, Clone)]
+#[repr(C)]
+pub struct FileQueryRangeInfo {
+    pub aes_ctr_key_type: u32,
+    pub speed_emulation_type: u32,
+    pub reserved: [u8; 0x38]
+}
+
 pub struct Path {
     pub path: [u8; 0x301]
 }
@@ -43,23 +106,268 @@ impl Path {
     }
 }
 
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+#[repr(u32)]
+pub enum BisPartitionId {
+    BootPartition1Root = 0,
+    BootPartition2Root = 10,
+    UserDataRoot = 20,
+    BootConfigAndPackage2Part1 = 21,
+    BootConfigAndPackage2Part2 = 22,
+    BootConfigAndPackage2Part3 = 23,
+    BootConfigAndPackage2Part4 = 24,
+    BootConfigAndPackage2Part5 = 25,
+    BootConfigAndPackage2Part6 = 26,
+    CalibrationBinary = 27,
+    CalibrationFile = 28,
+    SafeMode = 29,
+    User = 30,
+    System = 31,
+    SystemProperEncryption = 32,
+    SystemProperPartition = 33
+}
+
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+#[repr(u32)]
+pub enum ContentStorageId {
+    System = 0,
+    User = 1,
+    SdCard = 2
+}
+
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+#[repr(u8)]
+pub enum SaveDataSpaceId {
+    System = 0,
+    User = 1,
+    SdSystem = 2,
+    Temporary = 3,
+    SdUser = 4,
+    ProperSystem = 100,
+    SafeMode = 101
+}
+
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+#[repr(u8)]
+pub enum SaveDataType {
+    System = 0,
+    Account = 1,
+    Bcat = 2,
+    Device = 3,
+    Temporary = 4,
+    Cache = 5,
+    SystemBcat = 6
+}
+
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+#[repr(u8)]
+pub enum SaveDataRank {
+    Primary = 0,
+    Secondary = 1
+}
+
+pub type UserId = [u64; 2];
+
+pub const INVALID_USER_ID: UserId = [0; 2];
+
+#[derive(Copy, Clone)]
+#[repr(C)]
+pub struct SaveDataAttribute {
+    pub program_id: u64,
+    pub user_id: UserId,
+    pub system_save_data_id: u64,
+    pub save_data_type: SaveDataType,
+    pub save_data_rank: SaveDataRank,
+    pub save_data_index: u16,
+    pub pad: [u8; 4],
+    pub reserved: [u8; 0x18]
+}
+
+impl SaveDataAttribute {
+    pub const fn new(program_id: u64, user_id: UserId, system_save_data_id: u64, save_data_type: SaveDataType, save_data_rank: SaveDataRank, save_data_index: u16) -> Self {
+        Self { program_id: program_id, user_id: user_id, system_save_data_id: system_save_data_id, save_data_type: save_data_type, save_data_rank: save_data_rank, save_data_index: save_data_index, pad: [0; 4], reserved: [0; 0x18] }
+    }
+
+    pub const fn new_account(program_id: u64, user_id: UserId) -> Self {
+        Self::new(program_id, user_id, 0, SaveDataType::Account, SaveDataRank::Primary, 0)
+    }
+
+    pub const fn new_device(program_id: u64) -> Self {
+        Self::new(program_id, INVALID_USER_ID, 0, SaveDataType::Device, SaveDataRank::Primary, 0)
+    }
+
+    pub const fn new_bcat(program_id: u64) -> Self {
+        Self::new(program_id, INVALID_USER_ID, 0, SaveDataType::Bcat, SaveDataRank::Primary, 0)
+    }
+
+    pub const fn new_cache(program_id: u64, save_data_index: u16) -> Self {
+        Self::new(program_id, INVALID_USER_ID, 0, SaveDataType::Cache, SaveDataRank::Primary, save_data_index)
+    }
+
+    pub const fn new_system(system_save_data_id: u64, user_id: UserId) -> Self {
+        Self::new(0, user_id, system_save_data_id, SaveDataType::System, SaveDataRank::Primary, 0)
+    }
+}
+
+#[derive(Copy, Clone)]
+#[repr(C)]
+pub struct SaveDataInfo {
+    pub save_data_id: u64,
+    pub space_id: SaveDataSpaceId,
+    pub save_data_type: SaveDataType,
+    pub pad: [u8; 6],
+    pub user_id: UserId,
+    pub system_save_data_id: u64,
+    pub program_id: u64,
+    pub size: usize,
+    pub save_data_index: u16,
+    pub save_data_rank: SaveDataRank,
+    pub reserved: [u8; 0x25]
+}
+
+impl SaveDataInfo {
+    pub const fn empty() -> Self {
+        Self { save_data_id: 0, space_id: SaveDataSpaceId::System, save_data_type: SaveDataType::System, pad: [0; 6], user_id: INVALID_USER_ID, system_save_data_id: 0, program_id: 0, size: 0, save_data_index: 0, save_data_rank: SaveDataRank::Primary, reserved: [0; 0x25] }
+    }
+}
+
+#[sf::ipc_interface(client = "File")]
 pub trait IFile {
-    ipc_interface_define_command!(read: (option: FileReadOption, offset: usize, size: usize, buf: sf::OutNonSecureMapAliasBuffer) => (read_size: usize));
-    ipc_interface_define_command!(write: (option: FileWriteOption, offset: usize, size: usize, buf: sf::InNonSecureMapAliasBuffer) => ());
-    ipc_interface_define_command!(get_size: () => (size: usize));
+    #[ipc_rid(0)]
+    fn read(&mut self, option: FileReadOption, offset: usize, size: usize, buf: sf::OutNonSecureMapAliasBuffer) -> Result<usize>;
+
+    #[ipc_rid(1)]
+    fn write(&mut self, option: FileWriteOption, offset: usize, size: usize, buf: sf::InNonSecureMapAliasBuffer) -> Result<()>;
+
+    #[ipc_rid(2)]
+    fn flush(&mut self) -> Result<()>;
+
+    #[ipc_rid(3)]
+    fn set_size(&mut self, size: usize) -> Result<()>;
+
+    #[ipc_rid(4)]
+    fn get_size(&mut self) -> Result<usize>;
+
+    #[ipc_rid(5)]
+    fn operate_range(&mut self, operation_id: OperationId, offset: usize, size: usize) -> Result<FileQueryRangeInfo>;
 }
 
+#[sf::ipc_interface(client = "Directory")]
+pub trait IDirectory {
+    #[ipc_rid(0)]
+    fn read(&mut self, out_entries: sf::OutMapAliasBuffer) -> Result<u64>;
+
+    #[ipc_rid(1)]
+    fn get_entry_count(&mut self) -> Result<u64>;
+}
+
+#[sf::ipc_interface(client = "FileSystem")]
 pub trait IFileSystem {
-    ipc_interface_define_command!(create_file: (attribute: FileAttribute, size: usize, path_buf: sf::InPointerBuffer) => ());
-    ipc_interface_define_command!(delete_file: (path_buf: sf::InPointerBuffer) => ());
-    ipc_interface_define_command!(create_directory: (path_buf: sf::InPointerBuffer) => ());
-    ipc_interface_define_command!(delete_directory: (path_buf: sf::InPointerBuffer) => ());
-    ipc_interface_define_command!(delete_directory_recursively: (path_buf: sf::InPointerBuffer) => ());
-    ipc_interface_define_command!(open_file: (mode: FileOpenMode, path_buf: sf::InPointerBuffer) => (file: mem::Shared<dyn sf::IObject>));
+    #[ipc_rid(0)]
+    fn create_file(&mut self, attribute: FileAttribute, size: usize, path_buf: sf::InPointerBuffer) -> Result<()>;
+
+    #[ipc_rid(1)]
+    fn delete_file(&mut self, path_buf: sf::InPointerBuffer) -> Result<()>;
+
+    #[ipc_rid(2)]
+    fn create_directory(&mut self, path_buf: sf::InPointerBuffer) -> Result<()>;
+
+    #[ipc_rid(3)]
+    fn delete_directory(&mut self, path_buf: sf::InPointerBuffer) -> Result<()>;
+
+    #[ipc_rid(4)]
+    fn delete_directory_recursively(&mut self, path_buf: sf::InPointerBuffer) -> Result<()>;
+
+    #[ipc_rid(5)]
+    fn rename_file(&mut self, old_path_buf: sf::InPointerBuffer, new_path_buf: sf::InPointerBuffer) -> Result<()>;
+
+    #[ipc_rid(6)]
+    fn rename_directory(&mut self, old_path_buf: sf::InPointerBuffer, new_path_buf: sf::InPointerBuffer) -> Result<()>;
+
+    #[ipc_rid(7)]
+    fn get_entry_type(&mut self, path_buf: sf::InPointerBuffer) -> Result<DirectoryEntryType>;
+
+    #[ipc_rid(8)]
+    fn open_file(&mut self, mode: FileOpenMode, path_buf: sf::InPointerBuffer) -> Result<mem::Shared<File>>;
+
+    #[ipc_rid(9)]
+    fn open_directory(&mut self, mode: DirectoryOpenMode, path_buf: sf::InPointerBuffer) -> Result<mem::Shared<Directory>>;
+
+    #[ipc_rid(10)]
+    fn commit(&mut self) -> Result<()>;
+
+    #[ipc_rid(11)]
+    fn get_free_space_size(&mut self, path_buf: sf::InPointerBuffer) -> Result<usize>;
+
+    #[ipc_rid(12)]
+    fn get_total_space_size(&mut self, path_buf: sf::InPointerBuffer) -> Result<usize>;
+
+    #[ipc_rid(13)]
+    fn clean_directory_recursively(&mut self, path_buf: sf::InPointerBuffer) -> Result<()>;
+
+    #[ipc_rid(14)]
+    fn get_file_time_stamp_raw(&mut self, path_buf: sf::InPointerBuffer) -> Result<FileTimeStampRaw>;
+}
+
+#[sf::ipc_interface(client = "Storage")]
+pub trait IStorage {
+    #[ipc_rid(0)]
+    fn read(&mut self, offset: usize, size: usize, buf: sf::OutNonSecureMapAliasBuffer) -> Result<()>;
+
+    #[ipc_rid(1)]
+    fn write(&mut self, offset: usize, size: usize, buf: sf::InNonSecureMapAliasBuffer) -> Result<()>;
+
+    #[ipc_rid(2)]
+    fn flush(&mut self) -> Result<()>;
+
+    #[ipc_rid(3)]
+    fn set_size(&mut self, size: usize) -> Result<()>;
+
+    #[ipc_rid(4)]
+    fn get_size(&mut self) -> Result<usize>;
+
+    #[ipc_rid(5)]
+    fn operate_range(&mut self, operation_id: OperationId, offset: usize, size: usize) -> Result<FileQueryRangeInfo>;
 }
 
+#[sf::ipc_interface(client = "SaveDataInfoReader")]
+pub trait ISaveDataInfoReader {
+    #[ipc_rid(0)]
+    fn read_save_data_info(&mut self, out_infos: sf::OutMapAliasBuffer) -> Result<u64>;
+}
+
+#[sf::ipc_interface(client = "FileSystemProxy")]
 pub trait IFileSystemProxy {
-    ipc_interface_define_command!(set_current_process: (process_id: sf::ProcessId) => ());
-    ipc_interface_define_command!(open_sd_card_filesystem: () => (sd_filesystem: mem::Shared<dyn sf::IObject>));
-    ipc_interface_define_command!(output_access_log_to_sd_card: (access_log: sf::InMapAliasBuffer) => ());
+    #[ipc_rid(1)]
+    fn set_current_process(&mut self, process_id: sf::ProcessId) -> Result<()>;
+
+    #[ipc_rid(11)]
+    fn open_bis_filesystem(&mut self, partition_id: BisPartitionId, path_buf: sf::InPointerBuffer) -> Result<mem::Shared<FileSystem>>;
+
+    #[ipc_rid(18)]
+    fn open_sd_card_filesystem(&mut self) -> Result<mem::Shared<FileSystem>>;
+
+    #[ipc_rid(51)]
+    fn open_save_data_filesystem(&mut self, space_id: SaveDataSpaceId, attribute: SaveDataAttribute) -> Result<mem::Shared<FileSystem>>;
+
+    #[ipc_rid(52)]
+    fn open_save_data_filesystem_by_system_save_data_id(&mut self, space_id: SaveDataSpaceId, attribute: SaveDataAttribute) -> Result<mem::Shared<FileSystem>>;
+
+    #[ipc_rid(53)]
+    fn open_read_only_save_data_filesystem(&mut self, space_id: SaveDataSpaceId, attribute: SaveDataAttribute) -> Result<mem::Shared<FileSystem>>;
+
+    #[ipc_rid(60)]
+    fn open_save_data_info_reader(&mut self) -> Result<mem::Shared<SaveDataInfoReader>>;
+
+    #[ipc_rid(61)]
+    fn open_save_data_info_reader_by_save_data_space_id(&mut self, space_id: SaveDataSpaceId) -> Result<mem::Shared<SaveDataInfoReader>>;
+
+    #[ipc_rid(110)]
+    fn open_content_storage_filesystem(&mut self, content_storage_id: ContentStorageId) -> Result<mem::Shared<FileSystem>>;
+
+    #[ipc_rid(200)]
+    fn open_data_storage_by_current_process(&mut self) -> Result<mem::Shared<Storage>>;
+
+    #[ipc_rid(1006)]
+    fn output_access_log_to_sd_card(&mut self, access_log: sf::InMapAliasBuffer) -> Result<()>;
 }
\ No newline at end of file
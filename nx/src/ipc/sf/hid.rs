@@ -35,16 +35,32 @@ pub enum ControllerId {
     Handheld = 0x20
 }
 
+#[sf::ipc_interface(client = "AppletResource")]
 pub trait IAppletResource {
-    ipc_interface_define_command!(get_shared_memory_handle: () => (shmem_handle: sf::CopyHandle));
+    #[ipc_rid(0)]
+    fn get_shared_memory_handle(&mut self) -> Result<sf::CopyHandle>;
 }
 
+#[sf::ipc_interface(client = "HidServer")]
 pub trait IHidServer {
-    ipc_interface_define_command!(create_applet_resource: (aruid: sf::ProcessId) => (applet_resource: mem::Shared<dyn sf::IObject>));
-    ipc_interface_define_command!(set_supported_npad_style_set: (aruid: sf::ProcessId, npad_style_tag: NpadStyleTag) => ());
-    ipc_interface_define_command!(set_supported_npad_id_type: (aruid: sf::ProcessId, controllers: sf::InPointerBuffer) => ());
-    ipc_interface_define_command!(activate_npad: (aruid: sf::ProcessId) => ());
-    ipc_interface_define_command!(deactivate_npad: (aruid: sf::ProcessId) => ());
-    ipc_interface_define_command!(set_npad_joy_assignment_mode_single: (aruid: sf::ProcessId, controller: ControllerId, joy_type: NpadJoyDeviceType) => ());
-    ipc_interface_define_command!(set_npad_joy_assignment_mode_dual: (aruid: sf::ProcessId, controller: ControllerId) => ());
+    #[ipc_rid(0)]
+    fn create_applet_resource(&mut self, aruid: sf::ProcessId) -> Result<mem::Shared<AppletResource>>;
+
+    #[ipc_rid(100)]
+    fn set_supported_npad_style_set(&mut self, aruid: sf::ProcessId, npad_style_tag: NpadStyleTag) -> Result<()>;
+
+    #[ipc_rid(102)]
+    fn set_supported_npad_id_type(&mut self, aruid: sf::ProcessId, controllers: sf::InPointerBuffer) -> Result<()>;
+
+    #[ipc_rid(103)]
+    fn activate_npad(&mut self, aruid: sf::ProcessId) -> Result<()>;
+
+    #[ipc_rid(104)]
+    fn deactivate_npad(&mut self, aruid: sf::ProcessId) -> Result<()>;
+
+    #[ipc_rid(123)]
+    fn set_npad_joy_assignment_mode_single(&mut self, aruid: sf::ProcessId, controller: ControllerId, joy_type: NpadJoyDeviceType) -> Result<()>;
+
+    #[ipc_rid(124)]
+    fn set_npad_joy_assignment_mode_dual(&mut self, aruid: sf::ProcessId, controller: ControllerId) -> Result<()>;
 }
\ No newline at end of file
@@ -4,14 +4,27 @@ use crate::ipc::sf::sm;
 
 // Interfaces related to core serverside IPC (for control requests and MitM support)
 
+// Served by every server session (see ipc::server::HipcManager), clients send these through ipc::ObjectInfo
+#[sf::ipc_interface(control)]
 pub trait IHipcManager {
-    ipc_control_interface_define_command!(convert_current_object_to_domain: () => (domain_object_id: u32));
-    ipc_control_interface_define_command!(copy_from_current_domain: (domain_object_id: u32) => (handle: sf::MoveHandle));
-    ipc_control_interface_define_command!(clone_current_object: () => (cloned_handle: sf::MoveHandle));
-    ipc_control_interface_define_command!(query_pointer_buffer_size: () => (pointer_buffer_size: u16));
-    ipc_control_interface_define_command!(clone_current_object_ex: (tag: u32) => (cloned_handle: sf::MoveHandle));
+    #[ipc_rid(0)]
+    fn convert_current_object_to_domain(&mut self) -> Result<u32>;
+
+    #[ipc_rid(1)]
+    fn copy_from_current_domain(&mut self, domain_object_id: u32) -> Result<sf::MoveHandle>;
+
+    #[ipc_rid(2)]
+    fn clone_current_object(&mut self) -> Result<sf::MoveHandle>;
+
+    #[ipc_rid(3)]
+    fn query_pointer_buffer_size(&mut self) -> Result<u16>;
+
+    #[ipc_rid(4)]
+    fn clone_current_object_ex(&mut self, tag: u32) -> Result<sf::MoveHandle>;
 }
 
+#[sf::ipc_interface]
 pub trait IMitmQueryServer {
-    ipc_interface_define_command!(should_mitm: (info: sm::MitmProcessInfo) => (should_mitm: bool));
+    #[ipc_rid(65000)]
+    fn should_mitm(&mut self, info: sm::MitmProcessInfo) -> Result<bool>;
 }
\ No newline at end of file
@@ -11,11 +11,17 @@ bit_enum! {
     }
 }
 
+#[sf::ipc_interface(client = "Logger")]
 pub trait ILogger {
-    ipc_interface_define_command!(log: (log_buf: sf::InAutoSelectBuffer) => ());
-    ipc_interface_define_command!(set_destination: (log_destination: LogDestination) => ());
+    #[ipc_rid(0)]
+    fn log(&mut self, log_buf: sf::InAutoSelectBuffer) -> Result<()>;
+
+    #[ipc_rid(1)]
+    fn set_destination(&mut self, log_destination: LogDestination) -> Result<()>;
 }
 
+#[sf::ipc_interface(client = "LogService")]
 pub trait ILogService {
-    ipc_interface_define_command!(open_logger: (process_id: sf::ProcessId) => (logger: mem::Shared<dyn sf::IObject>));
+    #[ipc_rid(0)]
+    fn open_logger(&mut self, process_id: sf::ProcessId) -> Result<mem::Shared<Logger>>;
 }
\ No newline at end of file
@@ -1,10 +1,13 @@
 use super::*;
 use crate::svc;
 use crate::ipc::client;
+use crate::ipc::transport;
 use crate::ipc::server;
 use core::mem;
 use alloc::vec::Vec;
 
+pub use nx_macros::ipc_interface;
+
 #[derive(Copy, Clone)]
 pub struct Buffer<const A: BufferAttribute> {
     pub buf: *const u8,
@@ -109,6 +112,26 @@ impl Session {
         Ok(())
     }
 
+    // Opens a new session to the current object (or the same domain object, for domain sessions)
+    pub fn clone_current_object(&mut self) -> Result<Self> {
+        let handle = self.object_info.clone_current_object()?;
+        Ok(Self::from(self.object_info.with_cloned_handle(handle.handle)))
+    }
+
+    pub fn clone_current_object_ex(&mut self, tag: u32) -> Result<Self> {
+        let handle = self.object_info.clone_current_object_ex(tag)?;
+        Ok(Self::from(self.object_info.with_cloned_handle(handle.handle)))
+    }
+
+    // Opens a new (non-domain) session to the current domain object
+    pub fn copy_from_current_domain(&mut self) -> Result<Self> {
+        result_return_unless!(self.object_info.is_domain(), results::hipc::ResultTargetNotDomain);
+        let handle = self.object_info.copy_from_current_domain(self.object_info.domain_object_id)?;
+        let mut object_info = self.object_info.with_cloned_handle(handle.handle);
+        object_info.domain_object_id = 0;
+        Ok(Self::from(object_info))
+    }
+
     pub fn get_info(&mut self) -> &mut ObjectInfo {
         &mut self.object_info
     }
@@ -119,18 +142,19 @@ impl Session {
 
     pub fn close(&mut self) {
         if self.object_info.is_valid() {
-            if self.object_info.is_domain() {
+            // Closing the whole session also closes every object in its domain (if any)
+            if self.object_info.owns_handle {
                 let mut ctx = CommandContext::new(self.object_info);
-                client::write_request_command_on_ipc_buffer(&mut ctx, None, DomainCommandType::Close);
-                let _ = svc::send_sync_request(self.object_info.handle);
+                if client::write_close_command_on_ipc_buffer(&mut ctx).is_ok() {
+                    let _ = transport::send_sync_request(self.object_info.handle);
+                }
+                let _ = transport::close_handle(self.object_info.handle);
             }
-            else if self.object_info.owns_handle {
+            else if self.object_info.is_domain() {
                 let mut ctx = CommandContext::new(self.object_info);
-                client::write_close_command_on_ipc_buffer(&mut ctx);
-                let _ = svc::send_sync_request(self.object_info.handle);
-            }
-            if self.object_info.owns_handle {
-                let _ = svc::close_handle(self.object_info.handle);
+                if client::write_request_command_on_ipc_buffer(&mut ctx, None, DomainCommandType::Close).is_ok() {
+                    let _ = transport::send_sync_request(self.object_info.handle);
+                }
             }
             self.object_info = ObjectInfo::new();
         }
@@ -36,13 +36,50 @@ pub enum IoctlId {
     NvMapGetId = 0xC008010E,
 
     NvHostCtrlSyncptWait = 0xC00C0016,
+    NvHostCtrlEventSignal = 0xC004001C,
+    NvHostCtrlEventWait = 0xC010001D,
+    NvHostCtrlEventWaitAsync = 0xC010001E,
+    NvHostCtrlEventRegister = 0xC004001F,
+    NvHostCtrlEventUnregister = 0xC0040020,
+    NvHostCtrlEventKill = 0x40080021,
+
+    NvHostAsGpuBindChannel = 0x40044101,
+    NvHostAsGpuAllocSpace = 0xC0184102,
+    NvHostAsGpuUnmapBuffer = 0xC0084105,
+    NvHostAsGpuMapBufferEx = 0xC0284106,
+
+    NvHostGpuSetNvmapFd = 0x40044801,
+    // Sized for the header alone, since the entries are sent inline (see IoctlId::with_size)
+    NvHostGpuSubmitGpfifo = 0xC0184808,
+    NvHostGpuAllocObjCtx = 0xC0104809,
+    NvHostGpuSetErrorNotifier = 0xC018480C,
+    NvHostGpuAllocGpfifoEx2 = 0xC020481A,
+}
+
+impl IoctlId {
+    // Bits 16-29 of the ID encode the size of the data sent along with the ioctl
+    pub const fn with_size(self, size: usize) -> u32 {
+        ((self as u32) & !(0x3FFF << 16)) | (((size as u32) & 0x3FFF) << 16)
+    }
 }
 
 pub type Fd = u32;
 
+// All the nvdrv services share the same interface, the only difference is their service names :P
+#[sf::ipc_interface(client = "AppletNvDrvService", client = "SystemNvDrvService")]
 pub trait INvDrvService {
-    ipc_interface_define_command!(open: (path: sf::InMapAliasBuffer) => (fd: Fd, error_code: ErrorCode));
-    ipc_interface_define_command!(ioctl: (fd: Fd, id: IoctlId, in_buf: sf::InAutoSelectBuffer, out_buf: sf::OutAutoSelectBuffer) => (error_code: ErrorCode));
-    ipc_interface_define_command!(close: (fd: Fd) => (error_code: ErrorCode));
-    ipc_interface_define_command!(initialize: (transfer_mem_size: u32, self_process_handle: sf::CopyHandle, transfer_mem_handle: sf::CopyHandle) => (error_code: ErrorCode));
+    #[ipc_rid(0)]
+    fn open(&mut self, path: sf::InMapAliasBuffer) -> Result<(Fd, ErrorCode)>;
+
+    #[ipc_rid(1)]
+    fn ioctl(&mut self, fd: Fd, id: u32, in_buf: sf::InAutoSelectBuffer, out_buf: sf::OutAutoSelectBuffer) -> Result<ErrorCode>;
+
+    #[ipc_rid(2)]
+    fn close(&mut self, fd: Fd) -> Result<ErrorCode>;
+
+    #[ipc_rid(3)]
+    fn initialize(&mut self, transfer_mem_size: u32, self_process_handle: sf::CopyHandle, transfer_mem_handle: sf::CopyHandle) -> Result<ErrorCode>;
+
+    #[ipc_rid(4)]
+    fn query_event(&mut self, fd: Fd, event_id: u32) -> Result<(sf::CopyHandle, ErrorCode)>;
 }
\ No newline at end of file
@@ -1,6 +1,8 @@
 use crate::result::*;
-// use crate::ipc::sf;
+use crate::ipc::sf;
 
+#[sf::ipc_interface(client = "InformationInterface")]
 pub trait IInformationInterface {
-    ipc_interface_define_command!(get_program_id: (process_id: u64) => (program_id: u64));
+    #[ipc_rid(0)]
+    fn get_program_id(&mut self, process_id: u64) -> Result<u64>;
 }
\ No newline at end of file
@@ -19,14 +19,26 @@ pub enum State {
     ReadyShutdown
 }
 
+#[sf::ipc_interface(client = "PmModule")]
 pub trait IPmModule {
-    ipc_interface_define_command!(initialize: (id: ModuleId, dependencies: sf::InMapAliasBuffer) => (event_handle: sf::CopyHandle));
-    ipc_interface_define_command!(get_request: () => (state: State, flags: u32));
-    ipc_interface_define_command!(acknowledge: () => ());
-    ipc_interface_define_command!(finalize: () => ());
-    ipc_interface_define_command!(acknowledge_ex: (state: State) => ());
+    #[ipc_rid(0)]
+    fn initialize(&mut self, id: ModuleId, dependencies: sf::InMapAliasBuffer) -> Result<sf::CopyHandle>;
+
+    #[ipc_rid(1)]
+    fn get_request(&mut self) -> Result<(State, u32)>;
+
+    #[ipc_rid(2)]
+    fn acknowledge(&mut self) -> Result<()>;
+
+    #[ipc_rid(3)]
+    fn finalize(&mut self) -> Result<()>;
+
+    #[ipc_rid(4)]
+    fn acknowledge_ex(&mut self, state: State) -> Result<()>;
 }
 
+#[sf::ipc_interface(client = "PmService")]
 pub trait IPmService {
-    ipc_interface_define_command!(get_pm_module: () => (pm_module: mem::Shared<dyn sf::IObject>));
+    #[ipc_rid(0)]
+    fn get_pm_module(&mut self) -> Result<mem::Shared<PmModule>>;
 }
\ No newline at end of file
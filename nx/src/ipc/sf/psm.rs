@@ -1,6 +1,8 @@
 use crate::result::*;
-// use crate::ipc::sf;
+use crate::ipc::sf;
 
+#[sf::ipc_interface(client = "PsmServer")]
 pub trait IPsmServer {
-    ipc_interface_define_command!(get_battery_charge_percentage: () => (charge: u32));
+    #[ipc_rid(0)]
+    fn get_battery_charge_percentage(&mut self) -> Result<u32>;
 }
\ No newline at end of file
@@ -37,13 +37,57 @@ pub struct MitmProcessInfo {
     pub override_flags: u64
 }
 
+#[sf::ipc_interface(client = "UserInterface")]
 pub trait IUserInterface {
-    ipc_interface_define_command!(initialize: (process_id: sf::ProcessId) => ());
-    ipc_interface_define_command!(get_service_handle: (name: ServiceName) => (service_handle: sf::MoveHandle));
-    ipc_interface_define_command!(register_service: (name: ServiceName, is_light: bool, max_sessions: i32) => (port_handle: sf::MoveHandle));
-    ipc_interface_define_command!(unregister_service: (name: ServiceName) => ());
-    ipc_interface_define_command!(atmosphere_install_mitm: (name: ServiceName) => (port_handle: sf::MoveHandle, query_handle: sf::MoveHandle));
-    ipc_interface_define_command!(atmosphere_uninstall_mitm: (name: ServiceName) => ());
-    ipc_interface_define_command!(atmosphere_acknowledge_mitm_session: (name: ServiceName) => (info: MitmProcessInfo, session_handle: sf::MoveHandle));
-    ipc_interface_define_command!(atmosphere_has_service: (name: ServiceName) => (has: bool));
+    #[ipc_rid(0)]
+    fn initialize(&mut self, process_id: sf::ProcessId) -> Result<()>;
+
+    #[ipc_rid(1)]
+    fn get_service_handle(&mut self, name: ServiceName) -> Result<sf::MoveHandle>;
+
+    #[ipc_rid(2)]
+    fn register_service(&mut self, name: ServiceName, is_light: bool, max_sessions: i32) -> Result<sf::MoveHandle>;
+
+    #[ipc_rid(3)]
+    fn unregister_service(&mut self, name: ServiceName) -> Result<()>;
+
+    #[ipc_rid(65000)]
+    fn atmosphere_install_mitm(&mut self, name: ServiceName) -> Result<(sf::MoveHandle, sf::MoveHandle)>;
+
+    #[ipc_rid(65001)]
+    fn atmosphere_uninstall_mitm(&mut self, name: ServiceName) -> Result<()>;
+
+    #[ipc_rid(65003)]
+    fn atmosphere_acknowledge_mitm_session(&mut self, name: ServiceName) -> Result<(MitmProcessInfo, sf::MoveHandle)>;
+
+    #[ipc_rid(65100)]
+    fn atmosphere_has_service(&mut self, name: ServiceName) -> Result<bool>;
+}
+
+// sm uses TIPC since 12.0.0: command IDs are the same, but RegisterService takes its parameters in a different order
+#[sf::ipc_interface(client = "TipcUserInterface")]
+pub trait ITipcUserInterface {
+    #[ipc_rid(0)]
+    fn register_client(&mut self, process_id: sf::ProcessId) -> Result<()>;
+
+    #[ipc_rid(1)]
+    fn get_service_handle(&mut self, name: ServiceName) -> Result<sf::MoveHandle>;
+
+    #[ipc_rid(2)]
+    fn register_service(&mut self, name: ServiceName, max_sessions: i32, is_light: bool) -> Result<sf::MoveHandle>;
+
+    #[ipc_rid(3)]
+    fn unregister_service(&mut self, name: ServiceName) -> Result<()>;
+
+    #[ipc_rid(65000)]
+    fn atmosphere_install_mitm(&mut self, name: ServiceName) -> Result<(sf::MoveHandle, sf::MoveHandle)>;
+
+    #[ipc_rid(65001)]
+    fn atmosphere_uninstall_mitm(&mut self, name: ServiceName) -> Result<()>;
+
+    #[ipc_rid(65003)]
+    fn atmosphere_acknowledge_mitm_session(&mut self, name: ServiceName) -> Result<(MitmProcessInfo, sf::MoveHandle)>;
+
+    #[ipc_rid(65100)]
+    fn atmosphere_has_service(&mut self, name: ServiceName) -> Result<bool>;
 }
\ No newline at end of file
@@ -3,6 +3,7 @@ use crate::ipc::sf;
 use crate::mem;
 use crate::util;
 use crate::ipc::sf::applet;
+use crate::ipc::sf::dispdrv;
 
 #[derive(Copy, Clone)]
 #[repr(C)]
@@ -36,32 +37,93 @@ pub enum DisplayServiceMode {
     Privileged = 1
 }
 
+#[sf::ipc_interface(client = "ManagerDisplayService")]
 pub trait IManagerDisplayService {
-    ipc_interface_define_command!(create_managed_layer: (flags: LayerFlags, display_id: DisplayId, aruid: applet::AppletResourceUserId) => (id: LayerId));
-    ipc_interface_define_command!(destroy_managed_layer: (id: LayerId) => ());
+    #[ipc_rid(2010)]
+    fn create_managed_layer(&mut self, flags: LayerFlags, display_id: DisplayId, aruid: applet::AppletResourceUserId) -> Result<LayerId>;
+
+    #[ipc_rid(2011)]
+    fn destroy_managed_layer(&mut self, id: LayerId) -> Result<()>;
 }
 
+#[sf::ipc_interface(client = "SystemDisplayService")]
 pub trait ISystemDisplayService {
-    ipc_interface_define_command!(get_z_order_count_min: (display_id: DisplayId) => (z: i64));
-    ipc_interface_define_command!(get_z_order_count_max: (display_id: DisplayId) => (z: i64));
-    ipc_interface_define_command!(set_layer_position: (x: f32, y: f32, id: LayerId) => ());
-    ipc_interface_define_command!(set_layer_size: (id: LayerId, width: u64, height: u64) => ());
-    ipc_interface_define_command!(set_layer_z: (id: LayerId, z: i64) => ());
-    ipc_interface_define_command!(set_layer_visibility: (visible: bool, id: LayerId) => ());
+    #[ipc_rid(1200)]
+    fn get_z_order_count_min(&mut self, display_id: DisplayId) -> Result<i64>;
+
+    #[ipc_rid(1202)]
+    fn get_z_order_count_max(&mut self, display_id: DisplayId) -> Result<i64>;
+
+    #[ipc_rid(2201)]
+    fn set_layer_position(&mut self, x: f32, y: f32, id: LayerId) -> Result<()>;
+
+    #[ipc_rid(2203)]
+    fn set_layer_size(&mut self, id: LayerId, width: u64, height: u64) -> Result<()>;
+
+    #[ipc_rid(2205)]
+    fn set_layer_z(&mut self, id: LayerId, z: i64) -> Result<()>;
+
+    #[ipc_rid(2207)]
+    fn set_layer_visibility(&mut self, visible: bool, id: LayerId) -> Result<()>;
 }
 
+#[sf::ipc_interface(client = "ApplicationDisplayService")]
 pub trait IApplicationDisplayService {
-    ipc_interface_define_command!(get_relay_service: () => (relay_service: mem::Shared<dyn sf::IObject>));
-    ipc_interface_define_command!(get_system_display_service: () => (relay_service: mem::Shared<dyn sf::IObject>));
-    ipc_interface_define_command!(get_manager_display_service: () => (relay_service: mem::Shared<dyn sf::IObject>));
-    ipc_interface_define_command!(open_display: (name: DisplayName) => (id: DisplayId));
-    ipc_interface_define_command!(close_display: (id: DisplayId) => ());
-    ipc_interface_define_command!(open_layer: (name: DisplayName, id: LayerId, aruid: sf::ProcessId, out_native_window: sf::OutMapAliasBuffer) => (native_window_size: usize));
-    ipc_interface_define_command!(create_stray_layer: (flags: LayerFlags, display_id: DisplayId, out_native_window: sf::OutMapAliasBuffer) => (id: LayerId, native_window_size: usize));
-    ipc_interface_define_command!(destroy_stray_layer: (id: LayerId) => ());
-    ipc_interface_define_command!(get_display_vsync_event: (id: DisplayId) => (event_handle: sf::CopyHandle));
+    #[ipc_rid(100)]
+    fn get_relay_service(&mut self) -> Result<mem::Shared<dispdrv::HOSBinderDriver>>;
+
+    #[ipc_rid(101)]
+    fn get_system_display_service(&mut self) -> Result<mem::Shared<SystemDisplayService>>;
+
+    #[ipc_rid(102)]
+    fn get_manager_display_service(&mut self) -> Result<mem::Shared<ManagerDisplayService>>;
+
+    #[ipc_rid(1010)]
+    fn open_display(&mut self, name: DisplayName) -> Result<DisplayId>;
+
+    #[ipc_rid(1020)]
+    fn close_display(&mut self, id: DisplayId) -> Result<()>;
+
+    #[ipc_rid(2020)]
+    fn open_layer(&mut self, name: DisplayName, id: LayerId, aruid: sf::ProcessId, out_native_window: sf::OutMapAliasBuffer) -> Result<usize>;
+
+    #[ipc_rid(2030)]
+    fn create_stray_layer(&mut self, flags: LayerFlags, display_id: DisplayId, out_native_window: sf::OutMapAliasBuffer) -> Result<(LayerId, usize)>;
+
+    #[ipc_rid(2031)]
+    fn destroy_stray_layer(&mut self, id: LayerId) -> Result<()>;
+
+    #[ipc_rid(5202)]
+    fn get_display_vsync_event(&mut self, id: DisplayId) -> Result<sf::CopyHandle>;
+}
+
+// Root services only differ in the request ID of get_display_service (and the display service modes they allow)
+
+#[sf::ipc_interface(client = "SystemRootService")]
+pub trait ISystemRootService {
+    #[ipc_rid(1)]
+    fn get_display_service(&mut self, mode: DisplayServiceMode) -> Result<mem::Shared<ApplicationDisplayService>>;
+}
+
+#[sf::ipc_interface(client = "ManagerRootService")]
+pub trait IManagerRootService {
+    #[ipc_rid(2)]
+    fn get_display_service(&mut self, mode: DisplayServiceMode) -> Result<mem::Shared<ApplicationDisplayService>>;
 }
 
+// Common to all root service clients, so they can be used interchangeably (see gpu::GpuContext)
 pub trait IRootService {
-    ipc_interface_define_command!(get_display_service: (mode: DisplayServiceMode) => (display_service: mem::Shared<dyn sf::IObject>));
+    fn get_display_service(&mut self, mode: DisplayServiceMode) -> Result<mem::Shared<ApplicationDisplayService>>;
+}
+
+impl IRootService for SystemRootService {
+    fn get_display_service(&mut self, mode: DisplayServiceMode) -> Result<mem::Shared<ApplicationDisplayService>> {
+        ISystemRootService::get_display_service(self, mode)
+    }
+}
+
+impl IRootService for ManagerRootService {
+    fn get_display_service(&mut self, mode: DisplayServiceMode) -> Result<mem::Shared<ApplicationDisplayService>> {
+        IManagerRootService::get_display_service(self, mode)
+    }
 }
\ No newline at end of file
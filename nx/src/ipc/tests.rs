@@ -0,0 +1,453 @@
+// The walkers set up by the macros below go unused for commands without outputs, as with the actual command macros
+#![allow(unused_assignments)]
+// read_response! returns () for commands without outputs, like the client macros do
+#![allow(clippy::unused_unit)]
+
+use super::*;
+use crate::ipc::client;
+use crate::ipc::server;
+use crate::results;
+use crate::wait;
+use core::mem as cmem;
+
+// Round trips of the message encoding: requests written by the client side are read by the server side, whose responses are read back by the client side
+// Buffer addresses are fake ones, since nothing here ever accesses them
+
+const TEST_HANDLE: svc::Handle = 0x1234;
+
+// As aligned as the IPC buffer in the TLS
+#[repr(C, align(16))]
+struct MessageBuffer([u8; 0x100]);
+
+impl MessageBuffer {
+    fn new() -> Self {
+        Self([0; 0x100])
+    }
+
+    fn get_command_header(&self) -> CommandHeader {
+        unsafe {
+            *(self.0.as_ptr() as *const CommandHeader)
+        }
+    }
+
+    fn get_at<T: Copy>(&self, offset: usize) -> T {
+        unsafe {
+            *(self.0.as_ptr().add(offset) as *const T)
+        }
+    }
+
+    fn set_at<T: Copy>(&mut self, offset: usize, t: T) {
+        unsafe {
+            ptr::write_unaligned(self.0.as_mut_ptr().add(offset) as *mut T, t);
+        }
+    }
+}
+
+type InOutMapAliasBuffer = sf::Buffer<{bit_group!{ BufferAttribute [In, Out, MapAlias] }}>;
+
+type ServerSessions = ArrayVec<[server::ServerHolder; wait::MAX_OBJECT_COUNT as usize]>;
+
+fn fake_address(address: usize) -> *const u8 {
+    address as *const u8
+}
+
+// The following mimic what the client/server command macros do
+
+macro_rules! write_request {
+    ($buf:expr, $ctx:expr, $rq_id:expr, ( $( $in_param:expr ),* )) => {{
+        let mut walker = DataWalker::new(ptr::null_mut());
+        $( client::CommandParameter::<_>::before_request_write(&$in_param, &mut walker, &mut $ctx).unwrap(); )*
+        $ctx.in_params.data_size = walker.get_offset() as u32;
+
+        client::write_request_command_on_buffer(&mut $buf.0, &mut $ctx, Some($rq_id), DomainCommandType::SendMessage).unwrap();
+
+        walker.reset_with($ctx.in_params.data_offset);
+        $( client::CommandParameter::<_>::before_send_sync_request(&$in_param, &mut walker, &mut $ctx).unwrap(); )*
+    }};
+}
+
+macro_rules! read_response {
+    ($buf:expr, $ctx:expr, ( $( $out_param_type:ty ),* )) => {{
+        let mut _walker = DataWalker::new(ptr::null_mut());
+        $( <$out_param_type as client::CommandParameter<_>>::before_response_read(&mut _walker, &mut $ctx).unwrap(); )*
+        $ctx.out_params.data_size = _walker.get_offset() as u32;
+
+        client::read_request_command_response_from_buffer(&mut $buf.0, &mut $ctx).map(|_| {
+            _walker.reset_with($ctx.out_params.data_offset);
+            ( $( <$out_param_type as client::CommandParameter<_>>::after_response_read(&mut _walker, &mut $ctx).unwrap(), )* )
+        })
+    }};
+}
+
+macro_rules! read_request {
+    ($ctx:expr, ( $( $in_param_type:ty ),* )) => {{
+        $ctx.raw_data_walker = DataWalker::new($ctx.ctx.in_params.data_offset);
+        ( $( <$in_param_type as server::CommandParameter<_>>::after_request_read(&mut $ctx).unwrap(), )* )
+    }};
+}
+
+macro_rules! write_response {
+    ($buf:expr, $ctx:expr, ( $( $out_param:expr ),* )) => {{
+        $ctx.raw_data_walker = DataWalker::new(ptr::null_mut());
+        $( server::CommandParameter::<_>::before_response_write(&$out_param, &mut $ctx).unwrap(); )*
+        $ctx.ctx.out_params.data_size = $ctx.raw_data_walker.get_offset() as u32;
+
+        server::write_request_command_response_on_buffer(&mut $buf.0, &mut $ctx.ctx, ResultSuccess::make(), CommandType::Request).unwrap();
+
+        $ctx.raw_data_walker = DataWalker::new($ctx.ctx.out_params.data_offset);
+        $( server::CommandParameter::<_>::after_response_write(&$out_param, &mut $ctx).unwrap(); )*
+    }};
+}
+
+fn read_request_header(buf: &mut MessageBuffer, ctx: &mut CommandContext) -> (u32, DomainCommandType, DomainObjectId) {
+    assert_eq!(server::read_command_from_buffer(&mut buf.0, ctx).unwrap(), CommandType::Request);
+    server::read_request_command_from_buffer(&mut buf.0, ctx).unwrap()
+}
+
+#[test]
+fn raw_data_and_handles() {
+    let mut buf = MessageBuffer::new();
+    let mut ctx = CommandContext::new(ObjectInfo::from_handle(TEST_HANDLE));
+    write_request!(buf, ctx, 12, (0xCAFEBABEu32, sf::CopyHandle::from(0x10), 0x1122334455667788u64, sf::MoveHandle::from(0x20), 0x7Fu8));
+
+    let header = buf.get_command_header();
+    assert_eq!(header.get_command_type(), CommandType::Request);
+    assert!(header.get_has_special_header());
+
+    let mut server_ctx = CommandContext::new(ObjectInfo::from_handle(TEST_HANDLE));
+    let (rq_id, domain_command_type, _) = read_request_header(&mut buf, &mut server_ctx);
+    assert_eq!(rq_id, 12);
+    assert_eq!(domain_command_type, DomainCommandType::Invalid);
+
+    let mut new_sessions = ServerSessions::new();
+    let mut sctx = server::ServerContext::new(&mut server_ctx, DataWalker::empty(), None, &mut new_sessions, None);
+    let (a, copy_handle, b, move_handle, c) = read_request!(sctx, (u32, sf::CopyHandle, u64, sf::MoveHandle, u8));
+    assert_eq!(a, 0xCAFEBABE);
+    assert_eq!(copy_handle.handle, 0x10);
+    assert_eq!(b, 0x1122334455667788);
+    assert_eq!(move_handle.handle, 0x20);
+    assert_eq!(c, 0x7F);
+
+    write_response!(buf, sctx, (0xBEEFu16, sf::CopyHandle::from(0x30), 0x8877665544332211u64, sf::MoveHandle::from(0x40)));
+
+    let (d, copy_handle, e, move_handle) = read_response!(buf, ctx, (u16, sf::CopyHandle, u64, sf::MoveHandle)).unwrap();
+    assert_eq!(d, 0xBEEF);
+    assert_eq!(copy_handle.handle, 0x30);
+    assert_eq!(e, 0x8877665544332211);
+    assert_eq!(move_handle.handle, 0x40);
+}
+
+#[test]
+fn process_id() {
+    let mut buf = MessageBuffer::new();
+    let mut ctx = CommandContext::new(ObjectInfo::from_handle(TEST_HANDLE));
+    write_request!(buf, ctx, 1, (sf::ProcessId::new(), 0x55u32));
+
+    // The kernel writes the sender's process ID right after the special header
+    buf.set_at(cmem::size_of::<CommandHeader>() + cmem::size_of::<CommandSpecialHeader>(), 0xABCDu64);
+
+    let mut server_ctx = CommandContext::new(ObjectInfo::from_handle(TEST_HANDLE));
+    read_request_header(&mut buf, &mut server_ctx);
+
+    let mut new_sessions = ServerSessions::new();
+    let mut sctx = server::ServerContext::new(&mut server_ctx, DataWalker::empty(), None, &mut new_sessions, None);
+    let (process_id, value) = read_request!(sctx, (sf::ProcessId, u32));
+    assert_eq!(process_id.process_id, 0xABCD);
+    assert_eq!(value, 0x55);
+
+    write_response!(buf, sctx, ());
+    read_response!(buf, ctx, ()).unwrap();
+}
+
+#[test]
+fn map_alias_buffers() {
+    let mut buf = MessageBuffer::new();
+    let mut ctx = CommandContext::new(ObjectInfo::from_handle(TEST_HANDLE));
+    let in_buf = sf::InMapAliasBuffer::from_const(fake_address(0x12_3456_7000), 0x1000);
+    let out_buf = sf::OutNonSecureMapAliasBuffer::from_const(fake_address(0x7F_0000_2000), 0x2_0000_0000);
+    let inout_buf = InOutMapAliasBuffer::from_const(fake_address(0x3000), 0x30);
+    write_request!(buf, ctx, 2, (in_buf, out_buf, inout_buf));
+
+    let header = buf.get_command_header();
+    assert!(!header.get_has_special_header());
+    assert_eq!(header.get_send_buffer_count(), 1);
+    assert_eq!(header.get_receive_buffer_count(), 1);
+    assert_eq!(header.get_exchange_buffer_count(), 1);
+
+    let receive_buffer: BufferDescriptor = buf.get_at(cmem::size_of::<CommandHeader>() + cmem::size_of::<BufferDescriptor>());
+    assert_eq!(receive_buffer.bits & 0b11, BufferFlags::NonSecure as u32);
+
+    let mut server_ctx = CommandContext::new(ObjectInfo::from_handle(TEST_HANDLE));
+    read_request_header(&mut buf, &mut server_ctx);
+
+    let mut new_sessions = ServerSessions::new();
+    let mut sctx = server::ServerContext::new(&mut server_ctx, DataWalker::empty(), None, &mut new_sessions, None);
+    let (in_buf_2, out_buf_2, inout_buf_2) = read_request!(sctx, (sf::InMapAliasBuffer, sf::OutNonSecureMapAliasBuffer, InOutMapAliasBuffer));
+    assert_eq!((in_buf_2.buf, in_buf_2.size), (in_buf.buf, in_buf.size));
+    assert_eq!((out_buf_2.buf, out_buf_2.size), (out_buf.buf, out_buf.size));
+    assert_eq!((inout_buf_2.buf, inout_buf_2.size), (inout_buf.buf, inout_buf.size));
+
+    write_response!(buf, sctx, ());
+    read_response!(buf, ctx, ()).unwrap();
+}
+
+#[test]
+fn pointer_buffers() {
+    let mut buf = MessageBuffer::new();
+    let mut ctx = CommandContext::new(ObjectInfo::from_handle(TEST_HANDLE));
+    let in_buf = sf::InPointerBuffer::from_const(fake_address(0x12_3456_7890), 0x40);
+    let out_buf = sf::OutPointerBuffer::from_const(fake_address(0x5000), 0x80);
+    write_request!(buf, ctx, 3, (in_buf, out_buf));
+
+    let header = buf.get_command_header();
+    assert_eq!(header.get_send_static_count(), 1);
+    assert_eq!(header.get_receive_static_count(), 1);
+
+    let send_static: SendStaticDescriptor = buf.get_at(cmem::size_of::<CommandHeader>());
+    assert_eq!(send_static.get_address(), in_buf.buf as *mut u8);
+    assert_eq!(send_static.get_size(), 0x40);
+
+    // Without a pointer buffer (like loopback requests) the server gets the client's buffers as they are
+    let mut server_ctx = CommandContext::new(ObjectInfo::from_handle(TEST_HANDLE));
+    read_request_header(&mut buf, &mut server_ctx);
+
+    let mut new_sessions = ServerSessions::new();
+    let mut sctx = server::ServerContext::new(&mut server_ctx, DataWalker::empty(), None, &mut new_sessions, None);
+    let (in_buf_2, out_buf_2) = read_request!(sctx, (sf::InPointerBuffer, sf::OutPointerBuffer));
+    assert_eq!((in_buf_2.buf, in_buf_2.size), (in_buf.buf, in_buf.size));
+    assert_eq!((out_buf_2.buf, out_buf_2.size), (out_buf.buf, out_buf.size));
+
+    write_response!(buf, sctx, ());
+    assert_eq!(buf.get_command_header().get_send_static_count(), 0);
+    read_response!(buf, ctx, ()).unwrap();
+}
+
+#[test]
+fn out_pointer_buffer_goes_through_pointer_buffer() {
+    let pointer_buffer = server::PointerBuffer::new(fake_address(0x10_0000) as *mut u8, 0x100);
+
+    let mut buf = MessageBuffer::new();
+    let mut ctx = CommandContext::new(ObjectInfo::from_handle(TEST_HANDLE));
+    let in_buf = sf::InPointerBuffer::from_const(fake_address(0x4000), 0x28);
+    let out_buf = sf::OutPointerBuffer::from_const(fake_address(0x5000), 0x80);
+    write_request!(buf, ctx, 4, (in_buf, out_buf));
+
+    // The kernel copies the in pointer buffer to the start of the server's pointer buffer
+    buf.set_at(cmem::size_of::<CommandHeader>(), SendStaticDescriptor::new(pointer_buffer.buf, 0x28, 0));
+
+    let mut server_ctx = CommandContext::new(ObjectInfo::from_handle(TEST_HANDLE));
+    read_request_header(&mut buf, &mut server_ctx);
+
+    let mut new_sessions = ServerSessions::new();
+    let mut sctx = server::ServerContext::new(&mut server_ctx, DataWalker::empty(), None, &mut new_sessions, Some(pointer_buffer));
+    let (in_buf_2, out_buf_2) = read_request!(sctx, (sf::InPointerBuffer, sf::OutPointerBuffer));
+    assert_eq!(in_buf_2.buf, pointer_buffer.buf as *const u8);
+    assert_eq!(in_buf_2.size, 0x28);
+    // Placed after the in buffer, at the kernel's alignment
+    assert_eq!(out_buf_2.buf as usize, pointer_buffer.buf as usize + 0x30);
+    assert_eq!(out_buf_2.size, 0x80);
+
+    write_response!(buf, sctx, ());
+
+    // Sent back as a send static targeting the client's receive static
+    let header = buf.get_command_header();
+    assert_eq!(header.get_send_static_count(), 1);
+    let send_static: SendStaticDescriptor = buf.get_at(cmem::size_of::<CommandHeader>());
+    assert_eq!(send_static.get_address(), out_buf_2.buf as *mut u8);
+    assert_eq!(send_static.get_size(), 0x80);
+    assert_eq!(read_bits!(0, 5, send_static.bits), 0);
+
+    read_response!(buf, ctx, ()).unwrap();
+}
+
+#[test]
+fn out_pointer_buffer_larger_than_pointer_buffer() {
+    let pointer_buffer = server::PointerBuffer::new(fake_address(0x10_0000) as *mut u8, 0x100);
+
+    let mut buf = MessageBuffer::new();
+    let mut ctx = CommandContext::new(ObjectInfo::from_handle(TEST_HANDLE));
+    let out_buf = sf::OutPointerBuffer::from_const(fake_address(0x5000), 0x200);
+    write_request!(buf, ctx, 5, (out_buf));
+
+    let mut server_ctx = CommandContext::new(ObjectInfo::from_handle(TEST_HANDLE));
+    read_request_header(&mut buf, &mut server_ctx);
+
+    let mut new_sessions = ServerSessions::new();
+    let mut sctx = server::ServerContext::new(&mut server_ctx, DataWalker::empty(), None, &mut new_sessions, Some(pointer_buffer));
+    sctx.raw_data_walker = DataWalker::new(sctx.ctx.in_params.data_offset);
+    let rc = <sf::OutPointerBuffer as server::CommandParameter<_>>::after_request_read(&mut sctx).err().unwrap();
+    assert!(results::hipc::ResultPointerBufferTooSmall::matches(rc));
+}
+
+#[test]
+fn auto_select_buffers() {
+    let mut buf = MessageBuffer::new();
+    let mut object_info = ObjectInfo::from_handle(TEST_HANDLE);
+    // Already known, otherwise it would be queried first
+    object_info.pointer_buffer_size = Some(0x100);
+    let mut ctx = CommandContext::new(object_info);
+    let in_small_buf = sf::InAutoSelectBuffer::from_const(fake_address(0x6000), 0x40);
+    let in_large_buf = sf::InAutoSelectBuffer::from_const(fake_address(0x7000), 0x1000);
+    let out_small_buf = sf::OutAutoSelectBuffer::from_const(fake_address(0x8000), 0x80);
+    write_request!(buf, ctx, 6, (in_small_buf, in_large_buf, out_small_buf));
+
+    // Each one gets both descriptors, leaving the unused one empty
+    let header = buf.get_command_header();
+    assert_eq!(header.get_send_static_count(), 2);
+    assert_eq!(header.get_send_buffer_count(), 2);
+    assert_eq!(header.get_receive_buffer_count(), 1);
+    assert_eq!(header.get_receive_static_count(), 1);
+
+    let send_statics_size = 2 * cmem::size_of::<SendStaticDescriptor>();
+    let small_send_static: SendStaticDescriptor = buf.get_at(cmem::size_of::<CommandHeader>());
+    let large_send_static: SendStaticDescriptor = buf.get_at(cmem::size_of::<CommandHeader>() + cmem::size_of::<SendStaticDescriptor>());
+    let small_send_buffer: BufferDescriptor = buf.get_at(cmem::size_of::<CommandHeader>() + send_statics_size);
+    let large_send_buffer: BufferDescriptor = buf.get_at(cmem::size_of::<CommandHeader>() + send_statics_size + cmem::size_of::<BufferDescriptor>());
+    assert_eq!(small_send_static.get_address(), in_small_buf.buf as *mut u8);
+    assert!(small_send_buffer.get_address().is_null());
+    assert!(large_send_static.get_address().is_null());
+    assert_eq!(large_send_buffer.get_address(), in_large_buf.buf as *mut u8);
+
+    let mut server_ctx = CommandContext::new(ObjectInfo::from_handle(TEST_HANDLE));
+    read_request_header(&mut buf, &mut server_ctx);
+
+    let mut new_sessions = ServerSessions::new();
+    let mut sctx = server::ServerContext::new(&mut server_ctx, DataWalker::empty(), None, &mut new_sessions, None);
+    let (in_small_buf_2, in_large_buf_2, out_small_buf_2) = read_request!(sctx, (sf::InAutoSelectBuffer, sf::InAutoSelectBuffer, sf::OutAutoSelectBuffer));
+    assert_eq!((in_small_buf_2.buf, in_small_buf_2.size), (in_small_buf.buf, in_small_buf.size));
+    assert_eq!((in_large_buf_2.buf, in_large_buf_2.size), (in_large_buf.buf, in_large_buf.size));
+    assert_eq!((out_small_buf_2.buf, out_small_buf_2.size), (out_small_buf.buf, out_small_buf.size));
+
+    write_response!(buf, sctx, ());
+    read_response!(buf, ctx, ()).unwrap();
+}
+
+#[test]
+fn domain_objects() {
+    let mut buf = MessageBuffer::new();
+    let mut ctx = CommandContext::new(ObjectInfo::from_domain_object_id(TEST_HANDLE, 3));
+    assert!(results::hipc::ResultTargetNotDomain::matches(ctx.add_object(ObjectInfo::from_handle(TEST_HANDLE)).unwrap_err()));
+    ctx.add_object(ObjectInfo::from_domain_object_id(TEST_HANDLE, 7)).unwrap();
+    write_request!(buf, ctx, 7, (0x1234u32));
+
+    let mut server_ctx = CommandContext::new(ObjectInfo::from_domain_object_id(TEST_HANDLE, 1));
+    let (rq_id, domain_command_type, domain_object_id) = read_request_header(&mut buf, &mut server_ctx);
+    assert_eq!(rq_id, 7);
+    assert_eq!(domain_command_type, DomainCommandType::SendMessage);
+    assert_eq!(domain_object_id, 3);
+    assert_eq!(server_ctx.in_params.pop_domain_object().unwrap(), 7);
+
+    let mut new_sessions = ServerSessions::new();
+    let mut sctx = server::ServerContext::new(&mut server_ctx, DataWalker::empty(), None, &mut new_sessions, None);
+    let (value,) = read_request!(sctx, (u32));
+    assert_eq!(value, 0x1234);
+
+    sctx.ctx.out_params.push_domain_object(9).unwrap();
+    write_response!(buf, sctx, (0x5678u32));
+
+    let (value,) = read_response!(buf, ctx, (u32)).unwrap();
+    assert_eq!(value, 0x5678);
+    let object_info = ctx.pop_object().unwrap();
+    assert_eq!(object_info.handle, TEST_HANDLE);
+    assert_eq!(object_info.domain_object_id, 9);
+}
+
+#[test]
+fn domain_close() {
+    let mut buf = MessageBuffer::new();
+    let mut ctx = CommandContext::new(ObjectInfo::from_domain_object_id(TEST_HANDLE, 5));
+    client::write_request_command_on_buffer(&mut buf.0, &mut ctx, None, DomainCommandType::Close).unwrap();
+
+    let mut server_ctx = CommandContext::new(ObjectInfo::from_domain_object_id(TEST_HANDLE, 1));
+    let (_, domain_command_type, domain_object_id) = read_request_header(&mut buf, &mut server_ctx);
+    assert_eq!(domain_command_type, DomainCommandType::Close);
+    assert_eq!(domain_object_id, 5);
+}
+
+#[test]
+fn tipc_request() {
+    let mut buf = MessageBuffer::new();
+    let mut object_info = ObjectInfo::from_handle(TEST_HANDLE);
+    object_info.protocol = CommandProtocol::Tipc;
+    let mut ctx = CommandContext::new(object_info);
+
+    let mut pointer_ctx = CommandContext::new(object_info);
+    let pointer_buf = sf::InPointerBuffer::from_const(fake_address(0x4000), 0x10);
+    assert!(results::hipc::ResultUnsupportedOperation::matches(pointer_ctx.add_buffer(pointer_buf).unwrap_err()));
+
+    write_request!(buf, ctx, 2, (0xAAu32, 0xBBBBu64));
+
+    let header = buf.get_command_header();
+    assert_eq!(header.get_command_type(), CommandType::TipcRequest);
+    assert_eq!(header.get_tipc_request_id(), 2);
+
+    let mut server_ctx = CommandContext::new(ObjectInfo::from_handle(TEST_HANDLE));
+    assert_eq!(server::read_command_from_buffer(&mut buf.0, &mut server_ctx).unwrap(), CommandType::TipcRequest);
+    assert_eq!(server::read_tipc_request_command_from_buffer(&mut buf.0, &mut server_ctx).unwrap(), 2);
+
+    let mut new_sessions = ServerSessions::new();
+    let mut sctx = server::ServerContext::new(&mut server_ctx, DataWalker::empty(), None, &mut new_sessions, None);
+    let (a, b) = read_request!(sctx, (u32, u64));
+    assert_eq!(a, 0xAA);
+    assert_eq!(b, 0xBBBB);
+
+    write_response!(buf, sctx, (0xCCu8));
+
+    let (value,) = read_response!(buf, ctx, (u8)).unwrap();
+    assert_eq!(value, 0xCC);
+}
+
+#[test]
+fn control_request() {
+    let mut buf = MessageBuffer::new();
+    let mut ctx = CommandContext::new(ObjectInfo::from_handle(TEST_HANDLE));
+    client::write_control_command_on_buffer(&mut buf.0, &mut ctx, ControlRequestId::QueryPointerBufferSize).unwrap();
+
+    let mut server_ctx = CommandContext::new(ObjectInfo::from_handle(TEST_HANDLE));
+    assert_eq!(server::read_command_from_buffer(&mut buf.0, &mut server_ctx).unwrap(), CommandType::Control);
+    assert_eq!(server::read_control_command_from_buffer(&mut buf.0, &mut server_ctx).unwrap(), ControlRequestId::QueryPointerBufferSize);
+
+    let mut new_sessions = ServerSessions::new();
+    let mut sctx = server::ServerContext::new(&mut server_ctx, DataWalker::empty(), None, &mut new_sessions, None);
+    sctx.raw_data_walker = DataWalker::new(ptr::null_mut());
+    server::CommandParameter::<_>::before_response_write(&0x500u16, &mut sctx).unwrap();
+    sctx.ctx.out_params.data_size = sctx.raw_data_walker.get_offset() as u32;
+    server::write_control_command_response_on_buffer(&mut buf.0, sctx.ctx, ResultSuccess::make(), CommandType::Control).unwrap();
+    sctx.raw_data_walker = DataWalker::new(sctx.ctx.out_params.data_offset);
+    server::CommandParameter::<_>::after_response_write(&0x500u16, &mut sctx).unwrap();
+
+    client::read_control_command_response_from_buffer(&mut buf.0, &mut ctx).unwrap();
+    let mut walker = DataWalker::new(ctx.out_params.data_offset);
+    let pointer_buffer_size = <u16 as client::CommandParameter<_>>::after_response_read(&mut walker, &mut ctx).unwrap();
+    assert_eq!(pointer_buffer_size, 0x500);
+}
+
+#[test]
+fn error_response() {
+    let mut buf = MessageBuffer::new();
+    let mut ctx = CommandContext::new(ObjectInfo::from_handle(TEST_HANDLE));
+    write_request!(buf, ctx, 8, (0u32));
+
+    let mut server_ctx = CommandContext::new(ObjectInfo::from_handle(TEST_HANDLE));
+    read_request_header(&mut buf, &mut server_ctx);
+    server::write_request_command_response_on_buffer(&mut buf.0, &mut server_ctx, results::hipc::ResultUnsupportedOperation::make(), CommandType::Request).unwrap();
+
+    let rc = read_response!(buf, ctx, (u32)).unwrap_err();
+    assert!(results::hipc::ResultUnsupportedOperation::matches(rc));
+}
+
+#[test]
+fn message_size_is_checked() {
+    let mut buf = MessageBuffer::new();
+    let mut ctx = CommandContext::new(ObjectInfo::from_handle(TEST_HANDLE));
+    ctx.in_params.data_size = 0x100;
+    let rc = client::write_request_command_on_buffer(&mut buf.0, &mut ctx, Some(0), DomainCommandType::SendMessage).unwrap_err();
+    assert!(results::hipc::ResultInvalidRequestSize::matches(rc));
+
+    let mut ctx = CommandContext::new(ObjectInfo::from_handle(TEST_HANDLE));
+    write_request!(buf, ctx, 0, (0u64, 0u64));
+
+    let mut server_ctx = CommandContext::new(ObjectInfo::from_handle(TEST_HANDLE));
+    let rc = server::read_command_from_buffer(&mut buf.0[..0x10], &mut server_ctx).unwrap_err();
+    assert!(results::hipc::ResultInvalidRequestSize::matches(rc));
+}
\ No newline at end of file
@@ -0,0 +1,94 @@
+use crate::result::*;
+use crate::svc;
+use crate::ipc;
+use core::ptr;
+use core::slice;
+use core::sync::atomic::AtomicPtr;
+use core::sync::atomic::Ordering;
+
+// Kernel primitives every IPC client/server goes through, which can be replaced in order to run IPC without the kernel (see the loopback module)
+
+pub type SendSyncRequestFn = fn(svc::Handle) -> Result<()>;
+pub type CreateSessionFn = fn() -> Result<(svc::Handle, svc::Handle)>;
+pub type CloseHandleFn = fn(svc::Handle) -> Result<()>;
+pub type GetMessageBufferFn = fn(svc::Handle) -> *mut u8;
+
+// Same size as the kernel's IPC buffer, which all messages must fit in
+pub const MESSAGE_BUFFER_SIZE: usize = 0x100;
+
+#[derive(Copy, Clone)]
+pub struct Transport {
+    pub send_sync_request_fn: SendSyncRequestFn,
+    pub create_session_fn: CreateSessionFn,
+    pub close_handle_fn: CloseHandleFn,
+    pub get_message_buffer_fn: GetMessageBufferFn
+}
+
+fn kernel_create_session() -> Result<(svc::Handle, svc::Handle)> {
+    svc::create_session(false, 0)
+}
+
+fn kernel_get_message_buffer(_handle: svc::Handle) -> *mut u8 {
+    ipc::get_ipc_buffer()
+}
+
+pub static KERNEL_TRANSPORT: Transport = Transport { send_sync_request_fn: svc::send_sync_request, create_session_fn: kernel_create_session, close_handle_fn: svc::close_handle, get_message_buffer_fn: kernel_get_message_buffer };
+
+// Null while the kernel transport is used, so that regular IPC goes straight to the kernel, with just an atomic load in between
+static G_TRANSPORT: AtomicPtr<Transport> = AtomicPtr::new(ptr::null_mut());
+
+#[inline(always)]
+fn get_replaced_transport() -> Option<&'static Transport> {
+    unsafe { G_TRANSPORT.load(Ordering::Acquire).as_ref() }
+}
+
+pub fn get_transport() -> &'static Transport {
+    match get_replaced_transport() {
+        Some(transport) => transport,
+        None => &KERNEL_TRANSPORT
+    }
+}
+
+pub fn set_transport(transport: &'static Transport) {
+    G_TRANSPORT.store(transport as *const Transport as *mut Transport, Ordering::Release);
+}
+
+pub fn reset_transport() {
+    G_TRANSPORT.store(ptr::null_mut(), Ordering::Release);
+}
+
+#[inline(always)]
+pub fn send_sync_request(handle: svc::Handle) -> Result<()> {
+    match get_replaced_transport() {
+        Some(transport) => (transport.send_sync_request_fn)(handle),
+        None => svc::send_sync_request(handle)
+    }
+}
+
+// Returns (server handle, client handle), like svc::create_session
+pub fn create_session() -> Result<(svc::Handle, svc::Handle)> {
+    match get_replaced_transport() {
+        Some(transport) => (transport.create_session_fn)(),
+        None => kernel_create_session()
+    }
+}
+
+pub fn close_handle(handle: svc::Handle) -> Result<()> {
+    match get_replaced_transport() {
+        Some(transport) => (transport.close_handle_fn)(handle),
+        None => svc::close_handle(handle)
+    }
+}
+
+// Where messages sent/received through the given (client or server) session handle are placed, which is the current thread's IPC buffer for kernel sessions
+#[inline(always)]
+pub fn get_message_buffer(handle: svc::Handle) -> *mut u8 {
+    match get_replaced_transport() {
+        Some(transport) => (transport.get_message_buffer_fn)(handle),
+        None => kernel_get_message_buffer(handle)
+    }
+}
+
+pub fn get_message_buffer_slice(handle: svc::Handle) -> &'static mut [u8] {
+    unsafe { slice::from_raw_parts_mut(get_message_buffer(handle), MESSAGE_BUFFER_SIZE) }
+}
\ No newline at end of file
@@ -27,6 +27,9 @@ global_asm!(include_str!("svc.s"));
 #[macro_use]
 extern crate alloc;
 
+// Code generated by nx-macros refers to this crate as "nx"
+extern crate self as nx;
+
 #[macro_use]
 pub mod macros;
 
@@ -46,6 +49,8 @@ pub mod thread;
 
 pub mod hbl;
 
+pub mod version;
+
 pub mod crt0;
 
 pub mod svc;
@@ -54,10 +59,14 @@ pub mod ipc;
 
 pub mod service;
 
+pub mod fs;
+
 pub mod diag;
 
 pub mod gpu;
 
+pub mod gfx;
+
 pub mod input;
 
 pub mod vmem;
@@ -66,4 +75,6 @@ pub mod arm;
 
 pub mod wait;
 
+pub mod os;
+
 pub use paste;
\ No newline at end of file
@@ -14,8 +14,10 @@ macro_rules! ipc_client_send_request_command {
                 }
             )*
             ctx.in_params.data_size = walker.get_offset() as u32;
+            // Keep the pointer buffer size if it was queried while adding buffers
+            $session.pointer_buffer_size = ctx.object_info.pointer_buffer_size;
             
-            $crate::ipc::client::write_request_command_on_ipc_buffer(&mut ctx, Some($rq_id), $crate::ipc::DomainCommandType::SendMessage);
+            $crate::ipc::client::write_request_command_on_ipc_buffer(&mut ctx, Some($rq_id), $crate::ipc::DomainCommandType::SendMessage)?;
 
             walker.reset_with(ctx.in_params.data_offset);
             $(
@@ -25,7 +27,11 @@ macro_rules! ipc_client_send_request_command {
                 }
             )*
 
-            $crate::svc::send_sync_request($session.handle)?;
+            $crate::ipc::transport::send_sync_request($session.handle)?;
+
+            walker.reset_with(core::ptr::null_mut());
+            $( <$out_param_type as $crate::ipc::client::CommandParameter<_>>::before_response_read(&mut walker, &mut ctx)?; )*
+            ctx.out_params.data_size = walker.get_offset() as u32;
 
             $crate::ipc::client::read_request_command_response_from_ipc_buffer(&mut ctx)?;
 
@@ -53,7 +59,7 @@ macro_rules! ipc_client_send_control_command {
             )*
             ctx.in_params.data_size = walker.get_offset() as u32;
             
-            $crate::ipc::client::write_control_command_on_ipc_buffer(&mut ctx, $control_rq_id);
+            $crate::ipc::client::write_control_command_on_ipc_buffer(&mut ctx, $control_rq_id)?;
 
             walker.reset_with(ctx.in_params.data_offset);
             $(
@@ -63,7 +69,11 @@ macro_rules! ipc_client_send_control_command {
                 }
             )*
 
-            $crate::svc::send_sync_request($session.handle)?;
+            $crate::ipc::transport::send_sync_request($session.handle)?;
+
+            walker.reset_with(core::ptr::null_mut());
+            $( <$out_param_type as $crate::ipc::client::CommandParameter<_>>::before_response_read(&mut walker, &mut ctx)?; )*
+            ctx.out_params.data_size = walker.get_offset() as u32;
 
             $crate::ipc::client::read_control_command_response_from_ipc_buffer(&mut ctx)?;
 
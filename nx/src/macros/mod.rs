@@ -4,4 +4,6 @@ pub mod util;
 
 pub mod ipc;
 
-pub mod diag;
\ No newline at end of file
+pub mod diag;
+
+pub mod thread;
\ No newline at end of file
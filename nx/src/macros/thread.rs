@@ -0,0 +1,15 @@
+#![macro_use]
+
+// Declares a static thread::LocalKey, whose value gets created for each thread the first time it's accessed there
+#[macro_export]
+macro_rules! thread_define_local {
+    ($(#[$attr:meta])* $vis:vis static $name:ident: $t:ty = $init:expr;) => {
+        $(#[$attr])*
+        $vis static $name: $crate::thread::LocalKey<$t> = {
+            fn __init() -> $t {
+                $init
+            }
+            $crate::thread::LocalKey::new(__init)
+        };
+    };
+}
\ No newline at end of file
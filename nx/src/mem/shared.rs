@@ -0,0 +1,290 @@
+use alloc::boxed::Box;
+use core::ops;
+use core::ptr;
+use core::mem;
+use core::marker;
+use core::any;
+use core::sync::atomic::AtomicI64;
+use core::sync::atomic::Ordering;
+
+struct RefcountHolder {
+    use_count: AtomicI64,
+    // Weak references, plus one shared by all the strong ones
+    weak_count: AtomicI64
+}
+
+// Refcounts are atomic, so that objects shared process-wide (like service sessions) can be cloned and dropped from any thread
+// Shared/Weak objects still aren't Send or Sync, since that depends on the object itself: code sharing them across threads must make sure it can be used that way
+#[derive(Copy, Clone)]
+struct Refcount {
+    holder: *mut RefcountHolder
+}
+
+impl Refcount {
+    pub const fn new() -> Self {
+        Self { holder: ptr::null_mut() }
+    }
+    
+    pub fn use_count(&self) -> i64 {
+        if self.holder.is_null() {
+            0
+        }
+        else {
+            unsafe { (*self.holder).use_count.load(Ordering::SeqCst) }
+        }
+    }
+    
+    pub fn acquire<U: ?Sized>(&mut self, ptr: *mut U) {
+        if !ptr.is_null() {
+            unsafe {
+                if self.holder.is_null() {
+                    self.holder = Box::into_raw(Box::new(RefcountHolder { use_count: AtomicI64::new(1), weak_count: AtomicI64::new(1) }));
+                }
+                else {
+                    (*self.holder).use_count.fetch_add(1, Ordering::SeqCst);
+                }
+            }
+        }
+    }
+    
+    pub fn release<U: ?Sized>(&mut self, ptr: *mut U) {
+        if !self.holder.is_null() {
+            unsafe {
+                if (*self.holder).use_count.fetch_sub(1, Ordering::SeqCst) == 1 {
+                    // We created the variable as a Box, so we destroy it the same way
+                    mem::drop(Box::from_raw(ptr));
+                    self.release_weak();
+                }
+            }
+            self.holder = ptr::null_mut();
+        }
+    }
+
+    pub fn acquire_weak(&mut self) {
+        if !self.holder.is_null() {
+            unsafe {
+                (*self.holder).weak_count.fetch_add(1, Ordering::SeqCst);
+            }
+        }
+    }
+
+    pub fn release_weak(&mut self) {
+        if !self.holder.is_null() {
+            unsafe {
+                if (*self.holder).weak_count.fetch_sub(1, Ordering::SeqCst) == 1 {
+                    mem::drop(Box::from_raw(self.holder));
+                }
+            }
+            self.holder = ptr::null_mut();
+        }
+    }
+
+    // Fails if the object was already destroyed (or is being destroyed by another thread)
+    pub fn upgrade(&mut self) -> bool {
+        if self.holder.is_null() {
+            return false;
+        }
+
+        let use_count = unsafe { &(*self.holder).use_count };
+        let mut cur_use_count = use_count.load(Ordering::SeqCst);
+        while cur_use_count > 0 {
+            match use_count.compare_exchange(cur_use_count, cur_use_count + 1, Ordering::SeqCst, Ordering::SeqCst) {
+                Ok(_) => return true,
+                Err(new_use_count) => cur_use_count = new_use_count
+            };
+        }
+        false
+    }
+}
+
+pub struct Shared<T: ?Sized> {
+    object: *mut T,
+    refcount: Refcount
+}
+
+impl<T> Shared<T> {
+    pub fn new(var: T) -> Self {
+        // This is done instead of just &var to avoid dropping the variable inside this function
+        let object = Box::into_raw(Box::new(var));
+        let mut shared = Self { object: object, refcount: Refcount::new() };
+        shared.refcount.acquire(object);
+        shared
+    }
+
+    pub const fn empty() -> Self {
+        Self { object: ptr::null_mut(), refcount: Refcount::new() }
+    }
+}
+
+impl<T: ?Sized> Shared<T> {
+    fn release(&mut self) {
+        self.refcount.release(self.object);
+    }
+    
+    fn acquire(&mut self, object: *mut T) {
+        self.refcount.acquire(object);
+        self.object = object;
+    }
+
+    pub fn is_null(&self) -> bool {
+        self.object.is_null()
+    }
+
+    pub fn use_count(&self) -> i64 {
+        self.refcount.use_count()
+    }
+    
+    pub fn get(&self) -> &mut T {
+        unsafe { &mut *self.object }
+    }
+
+    pub fn reset(&mut self) {
+        self.release();
+    }
+
+    pub fn copy(&self) -> Self {
+        let mut new_shared = Self { object: self.object, refcount: self.refcount };
+        new_shared.acquire(new_shared.object);
+        new_shared
+    }
+
+    pub fn downgrade(&self) -> Weak<T> {
+        let mut weak = Weak { object: self.object, refcount: self.refcount };
+        weak.refcount.acquire_weak();
+        weak
+    }
+}
+
+impl Shared<dyn any::Any> {
+    pub fn downcast<T: any::Any>(&self) -> Option<Shared<T>> {
+        match self.get().is::<T>() {
+            true => {
+                let mut new_shared = Shared { object: self.object as *mut T, refcount: self.refcount };
+                new_shared.acquire(new_shared.object);
+                Some(new_shared)
+            },
+            false => None
+        }
+    }
+}
+
+impl<T: marker::Unsize<U> + ?Sized, U: ?Sized> ops::CoerceUnsized<Shared<U>> for Shared<T> {}
+
+impl<T: ?Sized> Drop for Shared<T> {
+    fn drop(&mut self) {
+        self.release();
+    }
+}
+
+impl<T: ?Sized> Clone for Shared<T> {
+    fn clone(&self) -> Self {
+        let mut new_shared = Self { object: self.object, refcount: self.refcount };
+        new_shared.acquire(new_shared.object);
+        new_shared
+    }
+}
+
+// Doesn't keep the object alive, but can get a Shared to it while something else does
+pub struct Weak<T: ?Sized> {
+    object: *mut T,
+    refcount: Refcount
+}
+
+impl<T: ?Sized> Weak<T> {
+    pub fn upgrade(&self) -> Option<Shared<T>> {
+        let mut refcount = self.refcount;
+        match refcount.upgrade() {
+            true => Some(Shared { object: self.object, refcount: refcount }),
+            false => None
+        }
+    }
+
+    pub fn is_alive(&self) -> bool {
+        self.refcount.use_count() > 0
+    }
+}
+
+impl<T: marker::Unsize<U> + ?Sized, U: ?Sized> ops::CoerceUnsized<Weak<U>> for Weak<T> {}
+
+impl<T: ?Sized> Drop for Weak<T> {
+    fn drop(&mut self) {
+        self.refcount.release_weak();
+    }
+}
+
+impl<T: ?Sized> Clone for Weak<T> {
+    fn clone(&self) -> Self {
+        let mut new_weak = Self { object: self.object, refcount: self.refcount };
+        new_weak.refcount.acquire_weak();
+        new_weak
+    }
+}
+
+impl<T> ops::Deref for Shared<T> {
+    type Target = T;
+    
+    fn deref(&self) -> &T {
+        unsafe { &*self.object }
+    }
+}
+
+impl<T> ops::DerefMut for Shared<T> {
+    fn deref_mut(&mut self) -> &mut T {
+        unsafe { &mut *self.object }
+    }
+}
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloc::vec::Vec;
+
+    struct Counted<'a>(&'a AtomicI64);
+
+    impl<'a> Drop for Counted<'a> {
+        fn drop(&mut self) {
+            self.0.fetch_add(1, Ordering::SeqCst);
+        }
+    }
+
+    #[test]
+    fn weak_references() {
+        let drop_count = AtomicI64::new(0);
+        let shared = Shared::new(Counted(&drop_count));
+        let weak = shared.downgrade();
+        assert!(weak.is_alive());
+
+        let other_shared = weak.upgrade().unwrap();
+        assert_eq!(shared.use_count(), 2);
+        drop(shared);
+        assert!(weak.is_alive());
+
+        drop(other_shared);
+        assert_eq!(drop_count.load(Ordering::SeqCst), 1);
+        assert!(!weak.is_alive());
+        assert!(weak.upgrade().is_none());
+    }
+
+    // Objects shared process-wide get cloned and dropped by any thread
+    struct SendShared(Shared<u32>);
+
+    unsafe impl Send for SendShared {}
+
+    #[test]
+    fn refcounts_are_atomic() {
+        let shared = Shared::new(0u32);
+        let threads: Vec<_> = (0..8).map(|_| {
+            let shared = SendShared(shared.clone());
+            std::thread::spawn(move || {
+                for _ in 0..10000 {
+                    let weak = shared.0.downgrade();
+                    let _other_shared = weak.upgrade().unwrap();
+                    let _ = shared.0.clone();
+                }
+            })
+        }).collect();
+        for thread in threads {
+            thread.join().unwrap();
+        }
+        assert_eq!(shared.use_count(), 1);
+    }
+}
\ No newline at end of file
@@ -0,0 +1,268 @@
+use crate::result::*;
+use crate::results;
+use crate::svc;
+use crate::wait;
+use crate::ipc::sf;
+use core::mem;
+
+pub struct ReadableEvent {
+    handle: svc::Handle
+}
+
+impl ReadableEvent {
+    pub const fn from_handle(handle: svc::Handle) -> Self {
+        Self { handle: handle }
+    }
+
+    pub fn get_handle(&self) -> svc::Handle {
+        self.handle
+    }
+
+    pub fn clear(&self) -> Result<()> {
+        svc::reset_signal(self.handle)
+    }
+
+    // The caller becomes responsible for closing the handle
+    pub fn detach_handle(self) -> svc::Handle {
+        let handle = self.handle;
+        mem::forget(self);
+        handle
+    }
+}
+
+impl Drop for ReadableEvent {
+    fn drop(&mut self) {
+        let _ = svc::close_handle(self.handle);
+    }
+}
+
+pub struct WritableEvent {
+    handle: svc::Handle
+}
+
+impl WritableEvent {
+    pub const fn from_handle(handle: svc::Handle) -> Self {
+        Self { handle: handle }
+    }
+
+    pub fn get_handle(&self) -> svc::Handle {
+        self.handle
+    }
+
+    pub fn signal(&self) -> Result<()> {
+        svc::signal_event(self.handle)
+    }
+
+    pub fn clear(&self) -> Result<()> {
+        svc::clear_event(self.handle)
+    }
+
+    // The caller becomes responsible for closing the handle
+    pub fn detach_handle(self) -> svc::Handle {
+        let handle = self.handle;
+        mem::forget(self);
+        handle
+    }
+}
+
+impl Drop for WritableEvent {
+    fn drop(&mut self) {
+        let _ = svc::close_handle(self.handle);
+    }
+}
+
+// Kernel event whose halves can be shared with other processes (for instance, the readable half being sent as a copy handle)
+pub struct InterProcessEvent {
+    readable: Option<ReadableEvent>,
+    writable: Option<WritableEvent>
+}
+
+impl InterProcessEvent {
+    pub fn new() -> Result<Self> {
+        let (writable_handle, readable_handle) = svc::create_event()?;
+        Ok(Self::from_handles(readable_handle, writable_handle))
+    }
+
+    pub fn from_handles(readable_handle: svc::Handle, writable_handle: svc::Handle) -> Self {
+        Self { readable: Some(ReadableEvent::from_handle(readable_handle)), writable: Some(WritableEvent::from_handle(writable_handle)) }
+    }
+
+    pub fn from_readable_handle(readable_handle: svc::Handle) -> Self {
+        Self { readable: Some(ReadableEvent::from_handle(readable_handle)), writable: None }
+    }
+
+    pub fn from_writable_handle(writable_handle: svc::Handle) -> Self {
+        Self { readable: None, writable: Some(WritableEvent::from_handle(writable_handle)) }
+    }
+
+    pub fn get_readable_handle(&self) -> Result<svc::Handle> {
+        match self.readable {
+            Some(ref readable) => Ok(readable.get_handle()),
+            None => Err(results::lib::os::ResultNoReadableEvent::make())
+        }
+    }
+
+    pub fn get_writable_handle(&self) -> Result<svc::Handle> {
+        match self.writable {
+            Some(ref writable) => Ok(writable.get_handle()),
+            None => Err(results::lib::os::ResultNoWritableEvent::make())
+        }
+    }
+
+    pub fn detach_readable_handle(&mut self) -> Result<svc::Handle> {
+        match self.readable.take() {
+            Some(readable) => Ok(readable.detach_handle()),
+            None => Err(results::lib::os::ResultNoReadableEvent::make())
+        }
+    }
+
+    pub fn detach_writable_handle(&mut self) -> Result<svc::Handle> {
+        match self.writable.take() {
+            Some(writable) => Ok(writable.detach_handle()),
+            None => Err(results::lib::os::ResultNoWritableEvent::make())
+        }
+    }
+
+    pub fn signal(&self) -> Result<()> {
+        match self.writable {
+            Some(ref writable) => writable.signal(),
+            None => Err(results::lib::os::ResultNoWritableEvent::make())
+        }
+    }
+
+    pub fn clear(&self) -> Result<()> {
+        if let Some(ref writable) = self.writable {
+            return writable.clear();
+        }
+        match self.readable {
+            Some(ref readable) => readable.clear(),
+            None => Err(results::lib::os::ResultNoReadableEvent::make())
+        }
+    }
+}
+
+pub struct SystemEvent {
+    event: InterProcessEvent,
+    auto_clear: bool
+}
+
+impl SystemEvent {
+    pub fn new(auto_clear: bool) -> Result<Self> {
+        Ok(Self::from_inter_process_event(InterProcessEvent::new()?, auto_clear))
+    }
+
+    pub const fn from_inter_process_event(event: InterProcessEvent, auto_clear: bool) -> Self {
+        Self { event: event, auto_clear: auto_clear }
+    }
+
+    // Events obtained from services (vsync, applet messages, psc...) only come with their readable half
+    pub fn from_readable_handle(readable_handle: svc::Handle, auto_clear: bool) -> Self {
+        Self::from_inter_process_event(InterProcessEvent::from_readable_handle(readable_handle), auto_clear)
+    }
+
+    pub fn from_copy_handle(handle: sf::CopyHandle, auto_clear: bool) -> Self {
+        Self::from_readable_handle(handle.handle, auto_clear)
+    }
+
+    pub fn is_auto_clear(&self) -> bool {
+        self.auto_clear
+    }
+
+    pub fn get_readable_handle(&self) -> Result<svc::Handle> {
+        self.event.get_readable_handle()
+    }
+
+    pub fn get_writable_handle(&self) -> Result<svc::Handle> {
+        self.event.get_writable_handle()
+    }
+
+    pub fn detach_readable_handle(&mut self) -> Result<svc::Handle> {
+        self.event.detach_readable_handle()
+    }
+
+    pub fn detach_writable_handle(&mut self) -> Result<svc::Handle> {
+        self.event.detach_writable_handle()
+    }
+
+    pub fn signal(&self) -> Result<()> {
+        self.event.signal()
+    }
+
+    pub fn clear(&self) -> Result<()> {
+        self.event.clear()
+    }
+
+    pub fn get_waiter(&self) -> Result<wait::Waiter> {
+        let handle = self.get_readable_handle()?;
+        match self.auto_clear {
+            true => Ok(wait::Waiter::from_handle_with_clear(handle)),
+            false => Ok(wait::Waiter::from_handle(handle))
+        }
+    }
+
+    pub fn timed_wait(&self, timeout: i64) -> Result<()> {
+        wait::wait(&[self.get_waiter()?], timeout)?;
+        Ok(())
+    }
+
+    pub fn wait(&self) -> Result<()> {
+        self.timed_wait(-1)
+    }
+
+    // Returns whether the event was signaled, without blocking
+    pub fn try_wait(&self) -> Result<bool> {
+        let handle = self.get_readable_handle()?;
+        let rc = match self.auto_clear {
+            true => svc::reset_signal(handle),
+            false => svc::wait_synchronization(&handle, 1, 0).map(|_| ())
+        };
+        match rc {
+            Ok(()) => Ok(true),
+            Err(rc) => {
+                if results::os::ResultInvalidState::matches(rc) || results::os::ResultTimeout::matches(rc) {
+                    return Ok(false);
+                }
+                Err(rc)
+            }
+        }
+    }
+}
+
+// Kernel event private to the current process, whose handles are never exposed
+pub struct Event {
+    event: SystemEvent
+}
+
+impl Event {
+    pub fn new(auto_clear: bool) -> Result<Self> {
+        Ok(Self { event: SystemEvent::new(auto_clear)? })
+    }
+
+    pub fn is_auto_clear(&self) -> bool {
+        self.event.is_auto_clear()
+    }
+
+    pub fn signal(&self) -> Result<()> {
+        self.event.signal()
+    }
+
+    pub fn clear(&self) -> Result<()> {
+        self.event.clear()
+    }
+
+    pub fn get_waiter(&self) -> Result<wait::Waiter> {
+        self.event.get_waiter()
+    }
+
+    pub fn timed_wait(&self, timeout: i64) -> Result<()> {
+        self.event.timed_wait(timeout)
+    }
+
+    pub fn wait(&self) -> Result<()> {
+        self.event.wait()
+    }
+
+    pub fn try_wait(&self) -> Result<bool> {
+        self.event.try_wait()
+    }
+}
\ No newline at end of file
@@ -6,5 +6,9 @@ result_define_group!(RESULT_MODULE => {
     InvalidOutputHeader: 212,
     InvalidCommandRequestId: 221,
     InvalidInObjectCount: 235,
-    InvalidOutObjectCount: 236
+    InvalidOutObjectCount: 236,
+    InvalidInObject: 239,
+    TargetNotFound: 261,
+    OutOfDomainEntries: 301,
+    RequestDeferred: 811
 });
\ No newline at end of file
@@ -0,0 +1,20 @@
+pub const RESULT_MODULE: u32 = 2;
+
+result_define_group!(RESULT_MODULE => {
+    PathNotFound: 1,
+    PathAlreadyExists: 2,
+    TargetLocked: 7,
+    DirectoryNotEmpty: 8,
+    UsableSpaceNotEnough: 30,
+    TargetNotFound: 1002,
+    SdCardNotPresent: 2001,
+    OutOfRange: 3005,
+    TooLongPath: 6003,
+    InvalidCharacter: 6004,
+    InvalidPathFormat: 6005,
+    InvalidOffset: 6061,
+    InvalidSize: 6062,
+    NullptrArgument: 6063,
+    InvalidOpenMode: 6072,
+    FileExtensionWithoutOpenModeAllowAppend: 6201
+});
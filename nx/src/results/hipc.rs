@@ -2,5 +2,10 @@ pub const RESULT_MODULE: u32 = 11;
 
 result_define_group!(RESULT_MODULE => {
     UnsupportedOperation: 1,
-    SessionClosed: 301
+    OutOfSessions: 131,
+    PointerBufferTooSmall: 141,
+    SessionClosed: 301,
+    InvalidRequestSize: 402,
+    TargetNotDomain: 491,
+    DomainObjectNotFound: 492
 });
\ No newline at end of file
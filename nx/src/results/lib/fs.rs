@@ -0,0 +1,10 @@
+pub const RESULT_SUBMODULE: u32 = 600;
+
+result_define_subgroup!(super::RESULT_MODULE, RESULT_SUBMODULE => {
+    InvalidMountName: 1,
+    MountNameNotFound: 2,
+    MountNameAlreadyExists: 3,
+    NotInFileOpenMode: 4,
+    InvalidRomFsHeader: 5,
+    InvalidRomFsEntry: 6
+});
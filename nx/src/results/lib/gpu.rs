@@ -40,5 +40,12 @@ result_define_subgroup!(super::RESULT_MODULE, RESULT_SUBMODULE => {
     ParcelNotEnoughReadSpace: 60,
     ParcelNotEnoughWriteSpace: 61,
     ParcelFdsNotSupported: 62,
-    ParcelReadSizeMismatch: 63
+    ParcelReadSizeMismatch: 63,
+    UnsupportedColorFormat: 80,
+    UnsupportedLayout: 81,
+    InvalidBufferSize: 82,
+    InvalidCommandArgument: 83,
+    UnsupportedIoctlFd: 84,
+    CanvasNotStarted: 85,
+    CanvasAlreadyStarted: 86
 });
\ No newline at end of file
@@ -8,4 +8,10 @@ pub mod gpu;
 
 pub mod elf;
 
-pub mod util;
\ No newline at end of file
+pub mod util;
+
+pub mod fs;
+
+pub mod os;
+
+pub mod thread;
\ No newline at end of file
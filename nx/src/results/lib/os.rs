@@ -0,0 +1,6 @@
+pub const RESULT_SUBMODULE: u32 = 700;
+
+result_define_subgroup!(super::RESULT_MODULE, RESULT_SUBMODULE => {
+    NoReadableEvent: 1,
+    NoWritableEvent: 2
+});
\ No newline at end of file
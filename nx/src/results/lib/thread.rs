@@ -0,0 +1,7 @@
+pub const RESULT_SUBMODULE: u32 = 800;
+
+result_define_subgroup!(super::RESULT_MODULE, RESULT_SUBMODULE => {
+    OutOfTlsSlots: 1,
+    InvalidTlsSlot: 2,
+    MissingThreadResult: 3
+});
\ No newline at end of file
@@ -6,4 +6,6 @@ pub mod cmif;
 
 pub mod hipc;
 
+pub mod fs;
+
 pub mod sm;
\ No newline at end of file
@@ -6,7 +6,9 @@ result_define_group!(RESULT_MODULE => {
     InvalidHandle: 114,
     Timeout: 117,
     OperationCanceled: 118,
+    OutOfRange: 119,
     SessionClosed: 123,
     UnhandledException: 124,
+    InvalidState: 125,
     FatalException: 128
 });
\ No newline at end of file
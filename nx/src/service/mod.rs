@@ -2,9 +2,11 @@ use crate::ipc::sf;
 use crate::mem;
 use crate::svc;
 use crate::result::*;
+use crate::sync;
+use core::any;
+use alloc::vec::Vec;
 
 pub mod sm;
-use crate::service::sm::IUserInterface;
 
 pub trait IClientObject: sf::IObject {
     fn new(session: sf::Session) -> Self where Self: Sized;
@@ -28,8 +30,9 @@ pub fn new_named_port_object<T: INamedPort + 'static>() -> Result<mem::Shared<T>
     Ok(mem::Shared::new(object))
 }
 
-pub fn new_service_object<T: IService + 'static>() -> Result<mem::Shared<T>> {
-    let sm = new_named_port_object::<sm::UserInterface>()?;
+// Always opens a new session, unlike new_service_object()
+pub fn open_service_object<T: IService + 'static>() -> Result<mem::Shared<T>> {
+    let sm = sm::get_user_interface()?;
     let session_handle = sm.get().get_service_handle(sm::ServiceName::new(T::get_name()))?;
     let mut object = T::new(sf::Session::from_handle(session_handle.handle));
     if T::as_domain() {
@@ -39,6 +42,39 @@ pub fn new_service_object<T: IService + 'static>() -> Result<mem::Shared<T>> {
     Ok(mem::Shared::new(object))
 }
 
+// Open sessions, shared by every thread: the registry doesn't keep them alive, so they're closed as soon as their last user drops them
+struct ServiceObjects(Vec<mem::Weak<dyn any::Any>>);
+
+// Only accessed with the lock held, and the refcounts of the sessions are atomic
+unsafe impl Send for ServiceObjects {}
+
+static G_SERVICE_OBJECTS: sync::Mutex<ServiceObjects> = sync::Mutex::new(ServiceObjects(Vec::new()));
+
+fn find_service_object<T: IService + 'static>() -> Option<mem::Shared<T>> {
+    let mut service_objects = G_SERVICE_OBJECTS.lock();
+    service_objects.0.retain(|object| object.is_alive());
+    for object in service_objects.0.iter() {
+        if let Some(service_object) = object.upgrade().and_then(|object| object.downcast::<T>()) {
+            return Some(service_object);
+        }
+    }
+    None
+}
+
+// Sessions are shared by every caller, and closed as soon as the last of them drops its object
+pub fn new_service_object<T: IService + 'static>() -> Result<mem::Shared<T>> {
+    if let Some(service_object) = find_service_object::<T>() {
+        return Ok(service_object);
+    }
+
+    // Not locking the registry while opening it, since initializing it might need other service objects
+    // (if another thread opens the same service meanwhile, both sessions stay usable and later callers get the first one registered)
+    let service_object = open_service_object::<T>()?;
+    let object: mem::Shared<dyn any::Any> = service_object.clone();
+    G_SERVICE_OBJECTS.lock().0.push(object.downgrade());
+    Ok(service_object)
+}
+
 pub mod psm;
 
 pub mod fspsrv;
@@ -1,78 +1,137 @@
 use crate::result::*;
+use crate::ipc;
 use crate::ipc::sf;
+use crate::ipc::sf::IObject;
+use crate::mem;
 use crate::service;
+use crate::sync;
+use crate::version;
+use core::sync::atomic::AtomicU8;
+use core::sync::atomic::Ordering;
 
 pub use crate::ipc::sf::sm::*;
 
-pub struct UserInterface {
-    session: sf::Session
-}
-
-impl sf::IObject for UserInterface {
-    fn get_session(&mut self) -> &mut sf::Session {
-        &mut self.session
+impl service::INamedPort for UserInterface {
+    fn get_name() -> &'static str {
+        nul!("sm:")
     }
 
-    fn get_command_table(&self) -> sf::CommandMetadataTable {
-        ipc_server_make_command_table! {
-            initialize: 0,
-            get_service_handle: 1,
-            register_service: 2,
-            unregister_service: 3,
-            atmosphere_install_mitm: 65000,
-            atmosphere_uninstall_mitm: 65001,
-            atmosphere_acknowledge_mitm_session: 65003,
-            atmosphere_has_service: 65100
-        }
+    fn post_initialize(&mut self) -> Result<()> {
+        self.initialize(sf::ProcessId::new())
     }
 }
 
-impl service::IClientObject for UserInterface {
-    fn new(session: sf::Session) -> Self {
-        Self { session: session }
+impl service::INamedPort for TipcUserInterface {
+    fn get_name() -> &'static str {
+        nul!("sm:")
+    }
+
+    fn post_initialize(&mut self) -> Result<()> {
+        self.get_session().object_info.protocol = ipc::CommandProtocol::Tipc;
+        self.register_client(sf::ProcessId::new())
     }
 }
 
-impl IUserInterface for UserInterface {
+// Lets the TIPC client be used through the usual interface, so that callers don't need to care about which one is in use
+impl IUserInterface for TipcUserInterface {
     fn initialize(&mut self, process_id: sf::ProcessId) -> Result<()> {
-        ipc_client_send_request_command!([self.session.object_info; 0] (process_id) => ())
+        self.register_client(process_id)
     }
 
     fn get_service_handle(&mut self, name: ServiceName) -> Result<sf::MoveHandle> {
-        ipc_client_send_request_command!([self.session.object_info; 1] (name) => (service_handle: sf::MoveHandle))
+        ITipcUserInterface::get_service_handle(self, name)
     }
 
     fn register_service(&mut self, name: ServiceName, is_light: bool, max_sessions: i32) -> Result<sf::MoveHandle> {
-        ipc_client_send_request_command!([self.session.object_info; 2] (name, is_light, max_sessions) => (port_handle: sf::MoveHandle))
+        ITipcUserInterface::register_service(self, name, max_sessions, is_light)
     }
 
     fn unregister_service(&mut self, name: ServiceName) -> Result<()> {
-        ipc_client_send_request_command!([self.session.object_info; 3] (name) => ())
+        ITipcUserInterface::unregister_service(self, name)
     }
 
     fn atmosphere_install_mitm(&mut self, name: ServiceName) -> Result<(sf::MoveHandle, sf::MoveHandle)> {
-        ipc_client_send_request_command!([self.session.object_info; 65000] (name) => (port_handle: sf::MoveHandle, query_handle: sf::MoveHandle))
+        ITipcUserInterface::atmosphere_install_mitm(self, name)
     }
 
     fn atmosphere_uninstall_mitm(&mut self, name: ServiceName) -> Result<()> {
-        ipc_client_send_request_command!([self.session.object_info; 65001] (name) => ())
+        ITipcUserInterface::atmosphere_uninstall_mitm(self, name)
     }
-    
+
     fn atmosphere_acknowledge_mitm_session(&mut self, name: ServiceName) -> Result<(MitmProcessInfo, sf::MoveHandle)> {
-        ipc_client_send_request_command!([self.session.object_info; 65003] (name) => (info: MitmProcessInfo, session_handle: sf::MoveHandle))
+        ITipcUserInterface::atmosphere_acknowledge_mitm_session(self, name)
     }
 
     fn atmosphere_has_service(&mut self, name: ServiceName) -> Result<bool> {
-        ipc_client_send_request_command!([self.session.object_info; 65100] (name) => (has: bool))
+        ITipcUserInterface::atmosphere_has_service(self, name)
     }
 }
 
-impl service::INamedPort for UserInterface {
-    fn get_name() -> &'static str {
-        nul!("sm:")
+pub const TIPC_MIN_VERSION: version::Version = version::Version::new(12, 0, 0);
+
+const PROTOCOL_UNKNOWN: u8 = 0;
+const PROTOCOL_CMIF: u8 = 1;
+const PROTOCOL_TIPC: u8 = 2;
+
+// Cached once detected, for processes which don't know the system version
+static G_PROTOCOL: AtomicU8 = AtomicU8::new(PROTOCOL_UNKNOWN);
+
+fn new_cmif_user_interface() -> Result<mem::Shared<dyn IUserInterface>> {
+    let sm: mem::Shared<dyn IUserInterface> = service::new_named_port_object::<UserInterface>()?;
+    G_PROTOCOL.store(PROTOCOL_CMIF, Ordering::SeqCst);
+    Ok(sm)
+}
+
+fn new_tipc_user_interface() -> Result<mem::Shared<dyn IUserInterface>> {
+    let sm: mem::Shared<dyn IUserInterface> = service::new_named_port_object::<TipcUserInterface>()?;
+    G_PROTOCOL.store(PROTOCOL_TIPC, Ordering::SeqCst);
+    Ok(sm)
+}
+
+// Connects to sm with the protocol it uses in the current system
+pub fn new_user_interface() -> Result<mem::Shared<dyn IUserInterface>> {
+    match G_PROTOCOL.load(Ordering::SeqCst) {
+        PROTOCOL_CMIF => new_cmif_user_interface(),
+        PROTOCOL_TIPC => new_tipc_user_interface(),
+        _ => {
+            if version::is_version_known() {
+                match version::get_version() >= TIPC_MIN_VERSION {
+                    true => new_tipc_user_interface(),
+                    false => new_cmif_user_interface()
+                }
+            }
+            else {
+                // Older sm versions reject (and close) TIPC sessions on the first request, so we can just retry with CMIF
+                match new_tipc_user_interface() {
+                    Ok(sm) => Ok(sm),
+                    Err(_) => new_cmif_user_interface()
+                }
+            }
+        }
     }
+}
 
-    fn post_initialize(&mut self) -> Result<()> {
-        self.initialize(sf::ProcessId::new())
+// Client sessions can be used by several threads at once (each one sends its requests through its own IPC buffer), so a single one is shared by the whole process
+struct UserInterfaceHolder(Option<mem::Shared<dyn IUserInterface>>);
+
+// Only accessed with the lock held, and the refcounts of the session are atomic
+unsafe impl Send for UserInterfaceHolder {}
+
+static G_USER_INTERFACE: sync::Mutex<UserInterfaceHolder> = sync::Mutex::new(UserInterfaceHolder(None));
+
+// Opened (and registered with our process ID) the first time it's needed
+pub fn get_user_interface() -> Result<mem::Shared<dyn IUserInterface>> {
+    let mut user_interface = G_USER_INTERFACE.lock();
+    if let Some(ref sm) = user_interface.0 {
+        return Ok(sm.clone());
     }
+
+    let sm = new_user_interface()?;
+    user_interface.0 = Some(sm.clone());
+    Ok(sm)
+}
+
+pub fn close_user_interface() {
+    // Dropped (and closed, unless something else still holds it) after the lock is released
+    let _sm = G_USER_INTERFACE.lock().0.take();
 }
\ No newline at end of file
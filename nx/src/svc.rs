@@ -49,6 +49,8 @@ bit_enum! {
         Read = bit!(0),
         Write = bit!(1),
         Execute = bit!(2),
+        ReadWrite = bit!(0) | bit!(1),
+        ReadExecute = bit!(0) | bit!(2),
         DontCare = bit!(28)
     }
 }
@@ -105,6 +107,32 @@ pub enum InfoId {
     IsApplication = 23,
 }
 
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+#[repr(u32)]
+pub enum LimitableResource {
+    PhysicalMemory = 0,
+    Threads = 1,
+    Events = 2,
+    TransferMemories = 3,
+    Sessions = 4
+}
+
+#[derive(Copy, Clone)]
+#[repr(C)]
+pub struct ThreadContext {
+    pub cpu_gprs: [u64; 29],
+    pub fp: u64,
+    pub lr: u64,
+    pub sp: u64,
+    pub pc: u64,
+    pub psr: u32,
+    pub pad: u32,
+    pub fpu_gprs: [u128; 32],
+    pub fpcr: u32,
+    pub fpsr: u32,
+    pub tpidr: u64
+}
+
 pub type PageInfo = u32;
 pub type Address = *const u8;
 pub type Size = usize;
@@ -127,6 +155,17 @@ pub fn set_heap_size(size: Size) -> Result<*mut u8> {
     }
 }
 
+pub fn set_memory_permission(address: Address, size: Size, value: MemoryPermission) -> Result<()> {
+    extern "C" {
+        fn __nx_svc_set_memory_permission(address: Address, size: Size, value: MemoryPermission) -> ResultCode;
+    }
+
+    unsafe {
+        let rc = __nx_svc_set_memory_permission(address, size, value);
+        wrap(rc, ())
+    }
+}
+
 pub fn set_memory_attribute(address: Address, size: Size, mask: u32, value: MemoryAttribute) -> Result<()> {
     extern "C" {
         fn __nx_svc_set_memory_attribute(address: Address, size: Size, mask: u32, value: MemoryAttribute) -> ResultCode;
@@ -138,6 +177,28 @@ pub fn set_memory_attribute(address: Address, size: Size, mask: u32, value: Memo
     }
 }
 
+pub fn map_memory(dst_address: Address, src_address: Address, size: Size) -> Result<()> {
+    extern "C" {
+        fn __nx_svc_map_memory(dst_address: Address, src_address: Address, size: Size) -> ResultCode;
+    }
+
+    unsafe {
+        let rc = __nx_svc_map_memory(dst_address, src_address, size);
+        wrap(rc, ())
+    }
+}
+
+pub fn unmap_memory(dst_address: Address, src_address: Address, size: Size) -> Result<()> {
+    extern "C" {
+        fn __nx_svc_unmap_memory(dst_address: Address, src_address: Address, size: Size) -> ResultCode;
+    }
+
+    unsafe {
+        let rc = __nx_svc_unmap_memory(dst_address, src_address, size);
+        wrap(rc, ())
+    }
+}
+
 pub fn query_memory(address: *const u8) -> Result<(MemoryInfo, PageInfo)> {
     extern "C" {
         fn __nx_svc_query_memory(out_info: *mut MemoryInfo, out_page_info: *mut PageInfo, address: *const u8) -> ResultCode;
@@ -220,6 +281,64 @@ pub fn get_thread_priority(handle: Handle) -> Result<i32> {
     }
 }
 
+pub fn set_thread_priority(handle: Handle, priority: i32) -> Result<()> {
+    extern "C" {
+        fn __nx_svc_set_thread_priority(handle: Handle, priority: i32) -> ResultCode;
+    }
+
+    unsafe {
+        let rc = __nx_svc_set_thread_priority(handle, priority);
+        wrap(rc, ())
+    }
+}
+
+pub fn get_thread_core_mask(handle: Handle) -> Result<(i32, u64)> {
+    extern "C" {
+        fn __nx_svc_get_thread_core_mask(out_core_id: *mut i32, out_affinity_mask: *mut u64, handle: Handle) -> ResultCode;
+    }
+
+    unsafe {
+        let mut core_id: i32 = 0;
+        let mut affinity_mask: u64 = 0;
+
+        let rc = __nx_svc_get_thread_core_mask(&mut core_id, &mut affinity_mask, handle);
+        wrap(rc, (core_id, affinity_mask))
+    }
+}
+
+pub fn set_thread_core_mask(handle: Handle, core_id: i32, affinity_mask: u64) -> Result<()> {
+    extern "C" {
+        fn __nx_svc_set_thread_core_mask(handle: Handle, core_id: i32, affinity_mask: u64) -> ResultCode;
+    }
+
+    unsafe {
+        let rc = __nx_svc_set_thread_core_mask(handle, core_id, affinity_mask);
+        wrap(rc, ())
+    }
+}
+
+pub fn signal_event(handle: Handle) -> Result<()> {
+    extern "C" {
+        fn __nx_svc_signal_event(handle: Handle) -> ResultCode;
+    }
+
+    unsafe {
+        let rc = __nx_svc_signal_event(handle);
+        wrap(rc, ())
+    }
+}
+
+pub fn clear_event(handle: Handle) -> Result<()> {
+    extern "C" {
+        fn __nx_svc_clear_event(handle: Handle) -> ResultCode;
+    }
+
+    unsafe {
+        let rc = __nx_svc_clear_event(handle);
+        wrap(rc, ())
+    }
+}
+
 pub fn map_shared_memory(handle: Handle, address: Address, size: Size, permission: MemoryPermission) -> Result<()> {
     extern "C" {
         fn __nx_svc_map_shared_memory(handle: Handle, address: Address, size: Size, permission: MemoryPermission) -> ResultCode;
@@ -312,6 +431,27 @@ pub fn arbitrate_unlock(tag_location: Address) -> Result<()> {
     }
 }
 
+pub fn wait_process_wide_key_atomic(tag_location: Address, condvar_key: Address, tag: u32, timeout: i64) -> Result<()> {
+    extern "C" {
+        fn __nx_svc_wait_process_wide_key_atomic(tag_location: Address, condvar_key: Address, tag: u32, timeout: i64) -> ResultCode;
+    }
+
+    unsafe {
+        let rc = __nx_svc_wait_process_wide_key_atomic(tag_location, condvar_key, tag, timeout);
+        wrap(rc, ())
+    }
+}
+
+pub fn signal_process_wide_key(condvar_key: Address, count: i32) {
+    extern "C" {
+        fn __nx_svc_signal_process_wide_key(condvar_key: Address, count: i32);
+    }
+
+    unsafe {
+        __nx_svc_signal_process_wide_key(condvar_key, count);
+    }
+}
+
 pub fn connect_to_named_port(name: Address) -> Result<Handle> {
     extern "C" {
         fn __nx_svc_connect_to_named_port(out_handle: *mut Handle, name: Address) -> ResultCode;
@@ -408,6 +548,81 @@ pub fn get_info(id: InfoId, handle: Handle, sub_id: u64) -> Result<u64> {
     }
 }
 
+pub fn map_physical_memory(address: Address, size: Size) -> Result<()> {
+    extern "C" {
+        fn __nx_svc_map_physical_memory(address: Address, size: Size) -> ResultCode;
+    }
+
+    unsafe {
+        let rc = __nx_svc_map_physical_memory(address, size);
+        wrap(rc, ())
+    }
+}
+
+pub fn unmap_physical_memory(address: Address, size: Size) -> Result<()> {
+    extern "C" {
+        fn __nx_svc_unmap_physical_memory(address: Address, size: Size) -> ResultCode;
+    }
+
+    unsafe {
+        let rc = __nx_svc_unmap_physical_memory(address, size);
+        wrap(rc, ())
+    }
+}
+
+pub fn get_resource_limit_limit_value(handle: Handle, resource: LimitableResource) -> Result<i64> {
+    extern "C" {
+        fn __nx_svc_get_resource_limit_limit_value(out_value: *mut i64, handle: Handle, resource: LimitableResource) -> ResultCode;
+    }
+
+    unsafe {
+        let mut value: i64 = 0;
+
+        let rc = __nx_svc_get_resource_limit_limit_value(&mut value, handle, resource);
+        wrap(rc, value)
+    }
+}
+
+pub fn get_resource_limit_current_value(handle: Handle, resource: LimitableResource) -> Result<i64> {
+    extern "C" {
+        fn __nx_svc_get_resource_limit_current_value(out_value: *mut i64, handle: Handle, resource: LimitableResource) -> ResultCode;
+    }
+
+    unsafe {
+        let mut value: i64 = 0;
+
+        let rc = __nx_svc_get_resource_limit_current_value(&mut value, handle, resource);
+        wrap(rc, value)
+    }
+}
+
+pub fn get_thread_context3(handle: Handle) -> Result<ThreadContext> {
+    extern "C" {
+        fn __nx_svc_get_thread_context3(out_context: *mut ThreadContext, handle: Handle) -> ResultCode;
+    }
+
+    unsafe {
+        let mut context: ThreadContext = mem::zeroed();
+
+        let rc = __nx_svc_get_thread_context3(&mut context, handle);
+        wrap(rc, context)
+    }
+}
+
+// Note: only available on 11.0.0+
+pub fn get_resource_limit_peak_value(handle: Handle, resource: LimitableResource) -> Result<i64> {
+    extern "C" {
+        fn __nx_svc_get_resource_limit_peak_value(out_value: *mut i64, handle: Handle, resource: LimitableResource) -> ResultCode;
+    }
+
+    unsafe {
+        let mut value: i64 = 0;
+
+        let rc = __nx_svc_get_resource_limit_peak_value(&mut value, handle, resource);
+        wrap(rc, value)
+    }
+}
+
 pub fn create_session(is_light: bool, unk_name: u64) -> Result<(Handle, Handle)> {
     extern "C" {
         fn __nx_svc_create_session(out_server_handle: *mut Handle, out_client_handle: *mut Handle, is_light: bool, unk_name: u64) -> ResultCode;
@@ -448,6 +663,55 @@ pub fn reply_and_receive(handles: *const Handle, handle_count: u32, reply_target
     }
 }
 
+pub fn create_event() -> Result<(Handle, Handle)> {
+    extern "C" {
+        fn __nx_svc_create_event(out_writable_handle: *mut Handle, out_readable_handle: *mut Handle) -> ResultCode;
+    }
+
+    unsafe {
+        let mut writable_handle: Handle = 0;
+        let mut readable_handle: Handle = 0;
+
+        let rc = __nx_svc_create_event(&mut writable_handle, &mut readable_handle);
+        wrap(rc, (writable_handle, readable_handle))
+    }
+}
+
+pub fn create_shared_memory(size: Size, local_permission: MemoryPermission, remote_permission: MemoryPermission) -> Result<Handle> {
+    extern "C" {
+        fn __nx_svc_create_shared_memory(out_handle: *mut Handle, size: Size, local_permission: MemoryPermission, remote_permission: MemoryPermission) -> ResultCode;
+    }
+
+    unsafe {
+        let mut handle: Handle = 0;
+
+        let rc = __nx_svc_create_shared_memory(&mut handle, size, local_permission, remote_permission);
+        wrap(rc, handle)
+    }
+}
+
+pub fn map_transfer_memory(handle: Handle, address: Address, size: Size, permission: MemoryPermission) -> Result<()> {
+    extern "C" {
+        fn __nx_svc_map_transfer_memory(handle: Handle, address: Address, size: Size, permission: MemoryPermission) -> ResultCode;
+    }
+
+    unsafe {
+        let rc = __nx_svc_map_transfer_memory(handle, address, size, permission);
+        wrap(rc, ())
+    }
+}
+
+pub fn unmap_transfer_memory(handle: Handle, address: Address, size: Size) -> Result<()> {
+    extern "C" {
+        fn __nx_svc_unmap_transfer_memory(handle: Handle, address: Address, size: Size) -> ResultCode;
+    }
+
+    unsafe {
+        let rc = __nx_svc_unmap_transfer_memory(handle, address, size);
+        wrap(rc, ())
+    }
+}
+
 pub fn manage_named_port(name: Address, max_sessions: i32) -> Result<Handle> {
     extern "C" {
         fn __nx_svc_manage_named_port(out_handle: *mut Handle, name: Address, max_sessions: i32) -> ResultCode;
@@ -459,4 +723,40 @@ pub fn manage_named_port(name: Address, max_sessions: i32) -> Result<Handle> {
         let rc = __nx_svc_manage_named_port(&mut handle, name, max_sessions);
         wrap(rc, handle)
     }
+}
+
+pub fn map_process_memory(dst_address: Address, process_handle: Handle, src_address: u64, size: Size) -> Result<()> {
+    extern "C" {
+        fn __nx_svc_map_process_memory(dst_address: Address, process_handle: Handle, src_address: u64, size: Size) -> ResultCode;
+    }
+
+    unsafe {
+        let rc = __nx_svc_map_process_memory(dst_address, process_handle, src_address, size);
+        wrap(rc, ())
+    }
+}
+
+pub fn unmap_process_memory(dst_address: Address, process_handle: Handle, src_address: u64, size: Size) -> Result<()> {
+    extern "C" {
+        fn __nx_svc_unmap_process_memory(dst_address: Address, process_handle: Handle, src_address: u64, size: Size) -> ResultCode;
+    }
+
+    unsafe {
+        let rc = __nx_svc_unmap_process_memory(dst_address, process_handle, src_address, size);
+        wrap(rc, ())
+    }
+}
+
+pub fn query_process_memory(process_handle: Handle, address: u64) -> Result<(MemoryInfo, PageInfo)> {
+    extern "C" {
+        fn __nx_svc_query_process_memory(out_info: *mut MemoryInfo, out_page_info: *mut PageInfo, process_handle: Handle, address: u64) -> ResultCode;
+    }
+
+    unsafe {
+        let mut memory_info: MemoryInfo = mem::zeroed();
+        let mut page_info: PageInfo = 0;
+
+        let rc = __nx_svc_query_process_memory(&mut memory_info, &mut page_info, process_handle, address);
+        wrap(rc, (memory_info, page_info))
+    }
 }
\ No newline at end of file
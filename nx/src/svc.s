@@ -6,11 +6,26 @@ FN_START __nx_svc_set_heap_size
 	ret
 FN_END
 
+FN_START __nx_svc_set_memory_permission
+	svc 0x2
+	ret
+FN_END
+
 FN_START __nx_svc_set_memory_attribute
 	svc 0x3
 	ret
 FN_END
 
+FN_START __nx_svc_map_memory
+	svc 0x4
+	ret
+FN_END
+
+FN_START __nx_svc_unmap_memory
+	svc 0x5
+	ret
+FN_END
+
 FN_START __nx_svc_query_memory
 	str x1, [sp, #-16]!
 	svc 0x6
@@ -55,6 +70,35 @@ FN_START __nx_svc_get_thread_priority
 	ret
 FN_END
 
+FN_START __nx_svc_set_thread_priority
+	svc 0xD
+	ret
+FN_END
+
+FN_START __nx_svc_get_thread_core_mask
+	stp x0, x1, [sp, #-16]!
+	svc 0xE
+	ldp x3, x4, [sp], #16
+	str w1, [x3]
+	str x2, [x4]
+	ret
+FN_END
+
+FN_START __nx_svc_set_thread_core_mask
+	svc 0xF
+	ret
+FN_END
+
+FN_START __nx_svc_signal_event
+	svc 0x11
+	ret
+FN_END
+
+FN_START __nx_svc_clear_event
+	svc 0x12
+	ret
+FN_END
+
 FN_START __nx_svc_map_shared_memory
 	svc 0x13
 	ret
@@ -101,6 +145,16 @@ FN_START __nx_svc_arbitrate_unlock
 	ret
 FN_END
 
+FN_START __nx_svc_wait_process_wide_key_atomic
+	svc 0x1C
+	ret
+FN_END
+
+FN_START __nx_svc_signal_process_wide_key
+	svc 0x1D
+	ret
+FN_END
+
 FN_START __nx_svc_connect_to_named_port
 	str x0, [sp, #-16]!
 	svc 0x1F
@@ -153,6 +207,45 @@ FN_START __nx_svc_get_info
 	ret
 FN_END
 
+FN_START __nx_svc_map_physical_memory
+	svc 0x2C
+	ret
+FN_END
+
+FN_START __nx_svc_unmap_physical_memory
+	svc 0x2D
+	ret
+FN_END
+
+FN_START __nx_svc_get_resource_limit_limit_value
+	str x0, [sp, #-16]!
+	svc 0x30
+	ldr x2, [sp], #16
+	str x1, [x2]
+	ret
+FN_END
+
+FN_START __nx_svc_get_resource_limit_current_value
+	str x0, [sp, #-16]!
+	svc 0x31
+	ldr x2, [sp], #16
+	str x1, [x2]
+	ret
+FN_END
+
+FN_START __nx_svc_get_thread_context3
+	svc 0x33
+	ret
+FN_END
+
+FN_START __nx_svc_get_resource_limit_peak_value
+	str x0, [sp, #-16]!
+	svc 0x37
+	ldr x2, [sp], #16
+	str x1, [x2]
+	ret
+FN_END
+
 FN_START __nx_svc_create_session
 	stp x0, x1, [sp, #-16]!
 	svc 0x40
@@ -178,10 +271,55 @@ FN_START __nx_svc_reply_and_receive
 	ret
 FN_END
 
+FN_START __nx_svc_create_event
+	stp x0, x1, [sp, #-16]!
+	svc 0x45
+	ldp x3, x4, [sp], #16
+	str w1, [x3]
+	str w2, [x4]
+	ret
+FN_END
+
+FN_START __nx_svc_create_shared_memory
+	str x0, [sp, #-16]!
+	svc 0x50
+	ldr x2, [sp], #16
+	str w1, [x2]
+	ret
+FN_END
+
+FN_START __nx_svc_map_transfer_memory
+	svc 0x51
+	ret
+FN_END
+
+FN_START __nx_svc_unmap_transfer_memory
+	svc 0x52
+	ret
+FN_END
+
 FN_START __nx_svc_manage_named_port
 	str x0, [sp, #-16]!
 	svc 0x71
 	ldr x2, [sp], #16
 	str w1, [x2]
 	ret
+FN_END
+
+FN_START __nx_svc_map_process_memory
+	svc 0x74
+	ret
+FN_END
+
+FN_START __nx_svc_unmap_process_memory
+	svc 0x75
+	ret
+FN_END
+
+FN_START __nx_svc_query_process_memory
+	str x1, [sp, #-16]!
+	svc 0x76
+	ldr x2, [sp], #16
+	str w1, [x2]
+	ret
 FN_END
\ No newline at end of file
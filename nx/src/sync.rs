@@ -1,12 +1,14 @@
+use crate::result::*;
+use crate::results;
 use crate::svc;
 use crate::thread;
-
-pub struct Mutex {
-    value: u32,
-    is_recursive: bool,
-    counter: u32,
-    thread_handle: u32,
-}
+use core::cell::Cell;
+use core::cell::UnsafeCell;
+use core::marker::PhantomData;
+use core::ops::Deref;
+use core::ops::DerefMut;
+use core::sync::atomic::AtomicBool;
+use core::sync::atomic::Ordering;
 
 const HANDLE_WAIT_MASK: u32 = 0x40000000;
 
@@ -104,68 +106,83 @@ fn try_lock_impl(handle_ref: *mut u32) -> bool {
     false
 }
 
-impl Mutex {
+// Kernel-arbitrated lock with no data attached (the tag is the owner thread's handle)
+pub struct RawMutex {
+    value: UnsafeCell<u32>,
+    is_recursive: bool,
+    counter: UnsafeCell<u32>,
+    thread_handle: UnsafeCell<u32>
+}
+
+unsafe impl Send for RawMutex {}
+unsafe impl Sync for RawMutex {}
+
+impl RawMutex {
     pub const fn new(recursive: bool) -> Self {
-        Self { value: 0, is_recursive: recursive, counter: 0, thread_handle: 0 }
+        Self { value: UnsafeCell::new(0), is_recursive: recursive, counter: UnsafeCell::new(0), thread_handle: UnsafeCell::new(0) }
     }
 
-    pub fn lock(&mut self) {
-        let mut do_lock = true;
-        if self.is_recursive {
-            do_lock = false;
-            let thr_handle = get_current_thread_handle();
-            if self.thread_handle != thr_handle {
-                do_lock = true;
-                self.thread_handle = thr_handle;
-            }
-            self.counter += 1;
-        }
-
-        if do_lock {
-            lock_impl(&mut self.value);
-        }
+    fn get_tag_address(&self) -> *mut u32 {
+        self.value.get()
     }
 
-    pub fn unlock(&mut self) {
-        let mut do_unlock = true;
-        if self.is_recursive {
-            do_unlock = false;
-            self.counter -= 1;
-            if self.counter == 0 {
-                self.thread_handle = 0;
-                do_unlock = true;
+    pub fn lock(&self) {
+        unsafe {
+            if self.is_recursive {
+                let thr_handle = get_current_thread_handle();
+                if *self.thread_handle.get() != thr_handle {
+                    lock_impl(self.get_tag_address());
+                    *self.thread_handle.get() = thr_handle;
+                }
+                *self.counter.get() += 1;
+            }
+            else {
+                lock_impl(self.get_tag_address());
             }
         }
+    }
 
-        if do_unlock {
-            unlock_impl(&mut self.value);
+    pub fn unlock(&self) {
+        unsafe {
+            if self.is_recursive {
+                *self.counter.get() -= 1;
+                if *self.counter.get() == 0 {
+                    *self.thread_handle.get() = 0;
+                    unlock_impl(self.get_tag_address());
+                }
+            }
+            else {
+                unlock_impl(self.get_tag_address());
+            }
         }
     }
 
-    pub fn try_lock(&mut self) -> bool {
-        if self.is_recursive {
-            let thr_handle = get_current_thread_handle();
-            if self.thread_handle != thr_handle {
-                if !try_lock_impl(&mut self.value) {
-                    return false;
+    pub fn try_lock(&self) -> bool {
+        unsafe {
+            if self.is_recursive {
+                let thr_handle = get_current_thread_handle();
+                if *self.thread_handle.get() != thr_handle {
+                    if !try_lock_impl(self.get_tag_address()) {
+                        return false;
+                    }
+                    *self.thread_handle.get() = thr_handle;
                 }
-                self.thread_handle = thr_handle;
+                *self.counter.get() += 1;
+                true
+            }
+            else {
+                try_lock_impl(self.get_tag_address())
             }
-            self.counter += 1;
-            true
-        }
-        else {
-            try_lock_impl(&mut self.value)
         }
     }
 }
 
 pub struct ScopedLock<'a> {
-    lock: &'a mut Mutex,
+    lock: &'a RawMutex
 }
 
 impl<'a> ScopedLock<'a> {
-    pub fn new(lock: &'a mut Mutex) -> Self {
+    pub fn new(lock: &'a RawMutex) -> Self {
         lock.lock();
         Self { lock: lock }
     }
@@ -177,30 +194,414 @@ impl<'a> Drop for ScopedLock<'a> {
     }
 }
 
-pub struct Locked<T> {
-    lock: Mutex,
-    object: T,
+pub struct Mutex<T: ?Sized> {
+    raw: RawMutex,
+    object: UnsafeCell<T>
 }
 
-impl<T> Locked<T> {
-    pub const fn new(recursive: bool, t: T) -> Self {
-        Self { lock: Mutex::new(recursive), object: t }
+// Only the guard gives access to the object, so sharing the mutex just requires the object to be sendable
+unsafe impl<T: ?Sized + Send> Send for Mutex<T> {}
+unsafe impl<T: ?Sized + Send> Sync for Mutex<T> {}
+
+impl<T> Mutex<T> {
+    pub const fn new(t: T) -> Self {
+        Self { raw: RawMutex::new(false), object: UnsafeCell::new(t) }
     }
 
-    pub fn get(&mut self) -> &mut T {
-        self.lock.lock();
-        let obj_ref = &mut self.object;
-        self.lock.unlock();
-        obj_ref
+    pub fn into_inner(self) -> T {
+        self.object.into_inner()
+    }
+}
+
+impl<T: ?Sized> Mutex<T> {
+    pub fn lock(&self) -> MutexGuard<'_, T> {
+        self.raw.lock();
+        MutexGuard { mutex: self, _not_send: PhantomData }
     }
 
-    pub fn set(&mut self, t: T) {
-        self.lock.lock();
-        self.object = t;
-        self.lock.unlock();
+    pub fn try_lock(&self) -> Option<MutexGuard<'_, T>> {
+        match self.raw.try_lock() {
+            true => Some(MutexGuard { mutex: self, _not_send: PhantomData }),
+            false => None
+        }
+    }
+
+    pub fn get_mut(&mut self) -> &mut T {
+        self.object.get_mut()
+    }
+}
+
+// The kernel tracks the owner thread, so guards must be dropped on the thread which locked them
+pub struct MutexGuard<'a, T: ?Sized> {
+    mutex: &'a Mutex<T>,
+    _not_send: PhantomData<*const ()>
+}
+
+unsafe impl<'a, T: ?Sized + Sync> Sync for MutexGuard<'a, T> {}
+
+impl<'a, T: ?Sized> Deref for MutexGuard<'a, T> {
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        unsafe { &*self.mutex.object.get() }
+    }
+}
+
+impl<'a, T: ?Sized> DerefMut for MutexGuard<'a, T> {
+    fn deref_mut(&mut self) -> &mut T {
+        unsafe { &mut *self.mutex.object.get() }
+    }
+}
+
+impl<'a, T: ?Sized> Drop for MutexGuard<'a, T> {
+    fn drop(&mut self) {
+        self.mutex.raw.unlock();
+    }
+}
+
+pub struct CondVar {
+    key: UnsafeCell<u32>
+}
+
+unsafe impl Send for CondVar {}
+unsafe impl Sync for CondVar {}
+
+impl CondVar {
+    pub const fn new() -> Self {
+        Self { key: UnsafeCell::new(0) }
+    }
+
+    fn wait_raw(&self, mutex: &RawMutex, timeout: i64) -> Result<()> {
+        unsafe {
+            // The kernel only knows about the tag, so any recursive lock state needs to be restored by us after waking up
+            let counter = *mutex.counter.get();
+            let thread_handle = *mutex.thread_handle.get();
+            if mutex.is_recursive {
+                *mutex.counter.get() = 0;
+                *mutex.thread_handle.get() = 0;
+            }
+
+            let rc = svc::wait_process_wide_key_atomic(mutex.get_tag_address() as *mut u8, self.key.get() as *mut u8, get_current_thread_handle(), timeout);
+            if let Err(rc) = rc {
+                // On timeout the kernel won't reacquire the mutex for us
+                if results::os::ResultTimeout::matches(rc) {
+                    lock_impl(mutex.get_tag_address());
+                }
+            }
+
+            if mutex.is_recursive {
+                *mutex.counter.get() = counter;
+                *mutex.thread_handle.get() = thread_handle;
+            }
+            rc
+        }
+    }
+
+    // The guard's mutex is released while waiting, and it's locked again once this returns (even on timeout)
+    pub fn wait_timeout<T: ?Sized>(&self, guard: &mut MutexGuard<'_, T>, timeout: i64) -> Result<()> {
+        self.wait_raw(&guard.mutex.raw, timeout)
+    }
+
+    pub fn wait<T: ?Sized>(&self, guard: &mut MutexGuard<'_, T>) -> Result<()> {
+        self.wait_timeout(guard, -1)
+    }
+
+    pub fn signal(&self) {
+        svc::signal_process_wide_key(self.key.get() as *mut u8, 1);
+    }
+
+    pub fn broadcast(&self) {
+        svc::signal_process_wide_key(self.key.get() as *mut u8, -1);
+    }
+}
+
+pub struct Semaphore {
+    count: Mutex<u64>,
+    condvar: CondVar
+}
+
+impl Semaphore {
+    pub const fn new(count: u64) -> Self {
+        Self { count: Mutex::new(count), condvar: CondVar::new() }
+    }
+
+    pub fn wait(&self) -> Result<()> {
+        let mut count = self.count.lock();
+        while *count == 0 {
+            self.condvar.wait(&mut count)?;
+        }
+        *count -= 1;
+        Ok(())
+    }
+
+    pub fn try_wait(&self) -> bool {
+        let mut count = self.count.lock();
+        if *count == 0 {
+            return false;
+        }
+        *count -= 1;
+        true
+    }
+
+    pub fn signal(&self) {
+        let mut count = self.count.lock();
+        *count += 1;
+        self.condvar.signal();
+    }
+
+    pub fn get_count(&self) -> u64 {
+        *self.count.lock()
     }
+}
+
+struct RwLockState {
+    reader_count: u32,
+    writer_waiter_count: u32,
+    is_write_locked: bool
+}
+
+// Writer-preferring: new readers wait while a writer holds or waits for the lock
+pub struct RwLock<T: ?Sized> {
+    state: Mutex<RwLockState>,
+    reader_condvar: CondVar,
+    writer_condvar: CondVar,
+    object: UnsafeCell<T>
+}
+
+unsafe impl<T: ?Sized + Send> Send for RwLock<T> {}
+unsafe impl<T: ?Sized + Send + Sync> Sync for RwLock<T> {}
+
+impl<T> RwLock<T> {
+    pub const fn new(t: T) -> Self {
+        Self { state: Mutex::new(RwLockState { reader_count: 0, writer_waiter_count: 0, is_write_locked: false }), reader_condvar: CondVar::new(), writer_condvar: CondVar::new(), object: UnsafeCell::new(t) }
+    }
+
+    pub fn into_inner(self) -> T {
+        self.object.into_inner()
+    }
+}
+
+impl<T: ?Sized> RwLock<T> {
+    pub fn read(&self) -> Result<RwLockReadGuard<'_, T>> {
+        let mut state = self.state.lock();
+        while state.is_write_locked || (state.writer_waiter_count > 0) {
+            self.reader_condvar.wait(&mut state)?;
+        }
+        state.reader_count += 1;
+        Ok(RwLockReadGuard { rwlock: self })
+    }
+
+    pub fn try_read(&self) -> Option<RwLockReadGuard<'_, T>> {
+        let mut state = self.state.lock();
+        if state.is_write_locked || (state.writer_waiter_count > 0) {
+            return None;
+        }
+        state.reader_count += 1;
+        Some(RwLockReadGuard { rwlock: self })
+    }
+
+    pub fn write(&self) -> Result<RwLockWriteGuard<'_, T>> {
+        let mut state = self.state.lock();
+        state.writer_waiter_count += 1;
+        while state.is_write_locked || (state.reader_count > 0) {
+            if let Err(rc) = self.writer_condvar.wait(&mut state) {
+                state.writer_waiter_count -= 1;
+                return Err(rc);
+            }
+        }
+        state.writer_waiter_count -= 1;
+        state.is_write_locked = true;
+        Ok(RwLockWriteGuard { rwlock: self })
+    }
+
+    pub fn try_write(&self) -> Option<RwLockWriteGuard<'_, T>> {
+        let mut state = self.state.lock();
+        if state.is_write_locked || (state.reader_count > 0) {
+            return None;
+        }
+        state.is_write_locked = true;
+        Some(RwLockWriteGuard { rwlock: self })
+    }
+
+    pub fn get_mut(&mut self) -> &mut T {
+        self.object.get_mut()
+    }
+
+    fn read_unlock(&self) {
+        let mut state = self.state.lock();
+        state.reader_count -= 1;
+        if (state.reader_count == 0) && (state.writer_waiter_count > 0) {
+            self.writer_condvar.signal();
+        }
+    }
+
+    fn write_unlock(&self) {
+        let mut state = self.state.lock();
+        state.is_write_locked = false;
+        if state.writer_waiter_count > 0 {
+            self.writer_condvar.signal();
+        }
+        else {
+            self.reader_condvar.broadcast();
+        }
+    }
+}
+
+pub struct RwLockReadGuard<'a, T: ?Sized> {
+    rwlock: &'a RwLock<T>
+}
+
+impl<'a, T: ?Sized> Deref for RwLockReadGuard<'a, T> {
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        unsafe { &*self.rwlock.object.get() }
+    }
+}
+
+impl<'a, T: ?Sized> Drop for RwLockReadGuard<'a, T> {
+    fn drop(&mut self) {
+        self.rwlock.read_unlock();
+    }
+}
+
+pub struct RwLockWriteGuard<'a, T: ?Sized> {
+    rwlock: &'a RwLock<T>
+}
+
+impl<'a, T: ?Sized> Deref for RwLockWriteGuard<'a, T> {
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        unsafe { &*self.rwlock.object.get() }
+    }
+}
+
+impl<'a, T: ?Sized> DerefMut for RwLockWriteGuard<'a, T> {
+    fn deref_mut(&mut self) -> &mut T {
+        unsafe { &mut *self.rwlock.object.get() }
+    }
+}
+
+impl<'a, T: ?Sized> Drop for RwLockWriteGuard<'a, T> {
+    fn drop(&mut self) {
+        self.rwlock.write_unlock();
+    }
+}
+
+struct BarrierState {
+    waiting_count: u32,
+    generation: u64
+}
+
+pub struct Barrier {
+    state: Mutex<BarrierState>,
+    condvar: CondVar,
+    thread_count: u32
+}
+
+impl Barrier {
+    pub const fn new(thread_count: u32) -> Self {
+        Self { state: Mutex::new(BarrierState { waiting_count: 0, generation: 0 }), condvar: CondVar::new(), thread_count: thread_count }
+    }
+
+    // Blocks until thread_count threads are waiting, returning true only for the thread which released the rest
+    pub fn wait(&self) -> Result<bool> {
+        let mut state = self.state.lock();
+        let generation = state.generation;
+        state.waiting_count += 1;
+        if state.waiting_count >= self.thread_count {
+            state.waiting_count = 0;
+            state.generation += 1;
+            self.condvar.broadcast();
+            return Ok(true);
+        }
+
+        while generation == state.generation {
+            self.condvar.wait(&mut state)?;
+        }
+        Ok(false)
+    }
+}
+
+pub struct Once {
+    lock: RawMutex,
+    done: AtomicBool
+}
+
+impl Once {
+    pub const fn new() -> Self {
+        Self { lock: RawMutex::new(false), done: AtomicBool::new(false) }
+    }
+
+    pub fn is_completed(&self) -> bool {
+        self.done.load(Ordering::Acquire)
+    }
+
+    pub fn call_once<F: FnOnce()>(&self, f: F) {
+        if self.is_completed() {
+            return;
+        }
+
+        let _guard = ScopedLock::new(&self.lock);
+        if !self.done.load(Ordering::Relaxed) {
+            (f)();
+            self.done.store(true, Ordering::Release);
+        }
+    }
+
+    // Like call_once, but a failed call doesn't count as completed (the next call will try again)
+    pub fn try_call_once<F: FnOnce() -> Result<()>>(&self, f: F) -> Result<()> {
+        if self.is_completed() {
+            return Ok(());
+        }
+
+        let _guard = ScopedLock::new(&self.lock);
+        if !self.done.load(Ordering::Relaxed) {
+            (f)()?;
+            self.done.store(true, Ordering::Release);
+        }
+        Ok(())
+    }
+}
+
+pub struct Lazy<T, F = fn() -> T> {
+    once: Once,
+    init_fn: Cell<Option<F>>,
+    value: UnsafeCell<Option<T>>
+}
+
+unsafe impl<T: Send + Sync, F: Send> Sync for Lazy<T, F> {}
+
+impl<T, F> Lazy<T, F> {
+    pub const fn new(init_fn: F) -> Self {
+        Self { once: Once::new(), init_fn: Cell::new(Some(init_fn)), value: UnsafeCell::new(None) }
+    }
+}
+
+impl<T, F: FnOnce() -> T> Lazy<T, F> {
+    pub fn force(this: &Self) -> &T {
+        this.once.call_once(|| {
+            if let Some(init_fn) = this.init_fn.take() {
+                unsafe {
+                    *this.value.get() = Some((init_fn)());
+                }
+            }
+        });
+
+        unsafe {
+            match *this.value.get() {
+                Some(ref value) => value,
+                None => unreachable!()
+            }
+        }
+    }
+}
+
+impl<T, F: FnOnce() -> T> Deref for Lazy<T, F> {
+    type Target = T;
 
-    pub fn get_lock(&self) -> &Mutex {
-        &self.lock
+    fn deref(&self) -> &T {
+        Self::force(self)
     }
 }
\ No newline at end of file
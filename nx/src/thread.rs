@@ -1,10 +1,18 @@
 extern crate alloc;
 
 use crate::result::*;
+use crate::results;
 use crate::svc;
+use crate::sync;
 use crate::util;
 use crate::mem;
+use alloc::boxed::Box;
+use alloc::string::String;
+use alloc::sync::Arc;
+use alloc::vec::Vec;
+use core::cell::UnsafeCell;
 use core::ptr;
+use core::time::Duration;
 
 pub type ThreadName = [u8; 0x20];
 
@@ -29,11 +37,15 @@ extern fn thread_entry_impl(thread_arg: *mut u8) -> ! {
         }
     }
 
-    svc::exit_thread();
+    exit();
 }
 
 pub const INVALID_PRIORITY: i32 = -1;
 
+pub const DEFAULT_CPU_ID: i32 = -2;
+
+pub const DEFAULT_STACK_SIZE: usize = 0x8000;
+
 #[repr(C)]
 pub struct Thread {
     pub self_ref: *mut Thread,
@@ -123,6 +135,8 @@ impl Thread {
     }
 
     pub fn set_name(&mut self, name: &str) -> Result<()> {
+        // The thread object might have been moved since it was created
+        self.name_addr = &mut self.name as *mut ThreadName as *mut u8;
         util::copy_str_to_pointer(name, self.name_addr)
     }
 
@@ -138,6 +152,18 @@ impl Thread {
         svc::get_thread_priority(self.handle)
     }
 
+    pub fn set_priority(&self, priority: i32) -> Result<()> {
+        svc::set_thread_priority(self.handle, priority)
+    }
+
+    pub fn get_core_mask(&self) -> Result<(i32, u64)> {
+        svc::get_thread_core_mask(self.handle)
+    }
+
+    pub fn set_core_mask(&self, preferred_core: i32, affinity_mask: u64) -> Result<()> {
+        svc::set_thread_core_mask(self.handle, preferred_core, affinity_mask)
+    }
+
     pub fn get_id(&self) -> Result<u64> {
         svc::get_thread_id(self.handle)
     }
@@ -152,8 +178,8 @@ impl Thread {
     }
 
     pub fn join(&self) -> Result<()> {
-        svc::wait_synchronization(&self.handle, 1, -1)?;
-        Ok(())
+        // TLS values are destroyed by the thread itself on exit(), never by the joining thread
+        svc::wait_synchronization(&self.handle, 1, -1)
     }
 }
 
@@ -205,4 +231,298 @@ pub fn get_current_thread() -> &'static mut Thread {
         let tls = get_thread_local_storage();
         &mut *(*tls).thread_ref
     }
+}
+
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+#[repr(i64)]
+pub enum YieldType {
+    WithoutCoreMigration = 0,
+    WithCoreMigration = -1,
+    ToAnyThread = -2
+}
+
+pub fn sleep(duration: Duration) -> Result<()> {
+    svc::sleep_thread(duration.as_nanos() as i64)
+}
+
+pub fn yield_now(yield_type: YieldType) -> Result<()> {
+    svc::sleep_thread(yield_type as i64)
+}
+
+struct SpawnPacket<T> {
+    result: UnsafeCell<Option<T>>
+}
+
+// The result is only written by the spawned thread before exiting, and only read after joining it
+unsafe impl<T: Send> Sync for SpawnPacket<T> {}
+
+type SpawnFn = Box<dyn FnOnce()>;
+
+fn spawn_entry(arg: *mut u8) {
+    let spawn_fn = unsafe { Box::from_raw(arg as *mut SpawnFn) };
+    (spawn_fn)();
+}
+
+// Detached threads, whose objects (and stacks) are kept until they exit
+struct DetachedThreads(Vec<Box<Thread>>);
+
+// The objects are only accessed by their own thread (while it runs) and by the reaping below, with the lock held
+unsafe impl Send for DetachedThreads {}
+
+static G_DETACHED_THREADS: sync::Mutex<DetachedThreads> = sync::Mutex::new(DetachedThreads(Vec::new()));
+
+fn detach_thread(thread: Box<Thread>) {
+    G_DETACHED_THREADS.lock().0.push(thread);
+}
+
+// A thread can't free its own stack, so detached threads are freed (closing their handle too) by the next spawn after they exit
+fn reap_detached_threads() {
+    // Thread handles are signaled once the thread exits, so this doesn't wait for the ones still running
+    G_DETACHED_THREADS.lock().0.retain(|thread| svc::wait_synchronization(&thread.handle, 1, 0).is_err());
+}
+
+pub struct JoinHandle<T> {
+    thread: Option<Box<Thread>>,
+    packet: Arc<SpawnPacket<T>>
+}
+
+impl<T> JoinHandle<T> {
+    pub fn get_thread(&self) -> &Thread {
+        // Only None after being joined or detached, which consume the handle
+        match self.thread {
+            Some(ref thread) => thread,
+            None => unreachable!()
+        }
+    }
+
+    pub fn join(mut self) -> Result<T> {
+        if let Some(thread) = self.thread.take() {
+            if let Err(rc) = thread.join() {
+                // The thread might still be running, so its object and stack can't be freed yet
+                detach_thread(thread);
+                return Err(rc);
+            }
+        }
+
+        // The thread might have exited (via exit()) before its closure returned
+        match unsafe { (*self.packet.result.get()).take() } {
+            Some(result) => Ok(result),
+            None => Err(results::lib::thread::ResultMissingThreadResult::make())
+        }
+    }
+
+    // The thread keeps running on its own, and its object and stack are freed once it exits
+    pub fn detach(mut self) {
+        if let Some(thread) = self.thread.take() {
+            detach_thread(thread);
+        }
+    }
+}
+
+impl<T> Drop for JoinHandle<T> {
+    fn drop(&mut self) {
+        // Like std, dropping the handle detaches the thread instead of blocking until it finishes
+        if let Some(thread) = self.thread.take() {
+            detach_thread(thread);
+        }
+    }
+}
+
+pub struct Builder {
+    name: String,
+    stack_size: usize,
+    priority: i32,
+    cpu_id: i32
+}
+
+impl Builder {
+    pub fn new() -> Self {
+        Self { name: String::new(), stack_size: DEFAULT_STACK_SIZE, priority: INVALID_PRIORITY, cpu_id: DEFAULT_CPU_ID }
+    }
+
+    pub fn name(mut self, name: &str) -> Self {
+        self.name = String::from(name);
+        self
+    }
+
+    pub fn stack_size(mut self, stack_size: usize) -> Self {
+        self.stack_size = stack_size;
+        self
+    }
+
+    pub fn priority(mut self, priority: i32) -> Self {
+        self.priority = priority;
+        self
+    }
+
+    pub fn cpu_id(mut self, cpu_id: i32) -> Self {
+        self.cpu_id = cpu_id;
+        self
+    }
+
+    pub fn spawn<F: FnOnce() -> T + Send + 'static, T: Send + 'static>(self, f: F) -> Result<JoinHandle<T>> {
+        reap_detached_threads();
+
+        let packet = Arc::new(SpawnPacket { result: UnsafeCell::new(None) });
+        let thread_packet = packet.clone();
+        let spawn_fn: SpawnFn = Box::new(move || {
+            let result = (f)();
+            unsafe {
+                *thread_packet.result.get() = Some(result);
+            }
+        });
+        let spawn_fn_arg = Box::into_raw(Box::new(spawn_fn)) as *mut u8;
+
+        let stack_size = (self.stack_size + mem::PAGE_ALIGNMENT - 1) & !(mem::PAGE_ALIGNMENT - 1);
+        let mut thread = match Thread::new(spawn_entry, spawn_fn_arg, ptr::null_mut(), stack_size, &self.name) {
+            Ok(thread) => Box::new(thread),
+            Err(rc) => {
+                unsafe { drop(Box::from_raw(spawn_fn_arg as *mut SpawnFn)); }
+                return Err(rc);
+            }
+        };
+
+        // The thread must be boxed before being created, since the kernel gets its address
+        if let Err(rc) = thread.create_and_start(self.priority, self.cpu_id) {
+            // The thread never ran, so the closure is still ours to free
+            unsafe { drop(Box::from_raw(spawn_fn_arg as *mut SpawnFn)); }
+            return Err(rc);
+        }
+
+        Ok(JoinHandle { thread: Some(thread), packet: packet })
+    }
+}
+
+pub fn spawn<F: FnOnce() -> T + Send + 'static, T: Send + 'static>(f: F) -> Result<JoinHandle<T>> {
+    Builder::new().spawn(f)
+}
+
+pub const MAX_TLS_SLOT_COUNT: usize = 0x20;
+
+pub type TlsDestructorFn = fn(*mut u8);
+
+struct TlsSlotTable {
+    used_slots: [bool; MAX_TLS_SLOT_COUNT],
+    destructors: [Option<TlsDestructorFn>; MAX_TLS_SLOT_COUNT]
+}
+
+static G_TLS_SLOT_TABLE: sync::Mutex<TlsSlotTable> = sync::Mutex::new(TlsSlotTable { used_slots: [false; MAX_TLS_SLOT_COUNT], destructors: [None; MAX_TLS_SLOT_COUNT] });
+
+// Note: values other threads hold in a freed slot aren't cleared, so they should be cleared (or destroyed) before freeing it
+pub fn allocate_tls_slot(destructor: Option<TlsDestructorFn>) -> Result<usize> {
+    let mut table = G_TLS_SLOT_TABLE.lock();
+    match table.used_slots.iter().position(|used| !*used) {
+        Some(slot) => {
+            table.used_slots[slot] = true;
+            table.destructors[slot] = destructor;
+            get_current_thread().tls_slots[slot] = ptr::null_mut();
+            Ok(slot)
+        },
+        None => Err(results::lib::thread::ResultOutOfTlsSlots::make())
+    }
+}
+
+pub fn free_tls_slot(slot: usize) -> Result<()> {
+    let mut table = G_TLS_SLOT_TABLE.lock();
+    result_return_unless!((slot < MAX_TLS_SLOT_COUNT) && table.used_slots[slot], results::lib::thread::ResultInvalidTlsSlot);
+
+    table.used_slots[slot] = false;
+    table.destructors[slot] = None;
+    Ok(())
+}
+
+pub fn get_tls_slot_value(slot: usize) -> *mut u8 {
+    get_current_thread().tls_slots[slot]
+}
+
+pub fn set_tls_slot_value(slot: usize, value: *mut u8) {
+    get_current_thread().tls_slots[slot] = value;
+}
+
+fn run_tls_destructors(thread: &mut Thread) {
+    // Destructors may use TLS themselves (even setting new values), so keep going until every slot is clear
+    loop {
+        let mut any_destroyed = false;
+        for slot in 0..MAX_TLS_SLOT_COUNT {
+            let value = thread.tls_slots[slot];
+            if !value.is_null() {
+                thread.tls_slots[slot] = ptr::null_mut();
+
+                // Don't hold the table lock while calling the destructor
+                let destructor = G_TLS_SLOT_TABLE.lock().destructors[slot];
+                if let Some(destructor_fn) = destructor {
+                    (destructor_fn)(value);
+                    any_destroyed = true;
+                }
+            }
+        }
+
+        if !any_destroyed {
+            break;
+        }
+    }
+}
+
+// Exits the current thread, destroying its TLS values
+pub fn exit() -> ! {
+    run_tls_destructors(get_current_thread());
+    svc::exit_thread();
+}
+
+// Only meant to be used by crt0, since the main thread doesn't exit through exit()
+pub(crate) fn exit_main_thread() {
+    run_tls_destructors(get_current_thread());
+}
+
+fn destroy_local_value<T>(value: *mut u8) {
+    unsafe {
+        drop(Box::from_raw(value as *mut T));
+    }
+}
+
+// Check thread_define_local! for declaring these
+pub struct LocalKey<T: 'static> {
+    init_fn: fn() -> T,
+    slot_once: sync::Once,
+    slot: UnsafeCell<usize>
+}
+
+// Safety: the key itself only holds the slot index, which is written once (guarded by slot_once) and read-only afterwards
+// Values are stored per thread and only ever accessed (or destroyed) by the thread owning them, so they are never shared, like std's LocalKey
+unsafe impl<T: 'static> Sync for LocalKey<T> {}
+
+impl<T: 'static> LocalKey<T> {
+    pub const fn new(init_fn: fn() -> T) -> Self {
+        Self { init_fn: init_fn, slot_once: sync::Once::new(), slot: UnsafeCell::new(0) }
+    }
+
+    fn get_slot(&'static self) -> Result<usize> {
+        self.slot_once.try_call_once(|| {
+            let slot = allocate_tls_slot(Some(destroy_local_value::<T>))?;
+            unsafe {
+                *self.slot.get() = slot;
+            }
+            Ok(())
+        })?;
+        unsafe {
+            Ok(*self.slot.get())
+        }
+    }
+
+    pub fn try_with<F: FnOnce(&T) -> R, R>(&'static self, f: F) -> Result<R> {
+        let slot = self.get_slot()?;
+        let mut value = get_tls_slot_value(slot) as *mut T;
+        if value.is_null() {
+            value = Box::into_raw(Box::new((self.init_fn)()));
+            set_tls_slot_value(slot, value as *mut u8);
+        }
+
+        unsafe {
+            Ok((f)(&*value))
+        }
+    }
+
+    pub fn with<F: FnOnce(&T) -> R, R>(&'static self, f: F) -> R {
+        self.try_with(f).unwrap()
+    }
 }
\ No newline at end of file
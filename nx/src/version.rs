@@ -0,0 +1,63 @@
+use core::sync::atomic::AtomicBool;
+use core::sync::atomic::AtomicU32;
+use core::sync::atomic::Ordering;
+
+// System (HOS) version, as reported by the homebrew loader
+// Other processes (like sysmodules) don't get it by default, and may set it themselves (e.g. with the one obtained from set:sys)
+
+#[derive(Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Debug)]
+pub struct Version {
+    pub major: u8,
+    pub minor: u8,
+    pub micro: u8
+}
+
+impl Version {
+    pub const fn empty() -> Self {
+        Self { major: 0, minor: 0, micro: 0 }
+    }
+
+    pub const fn new(major: u8, minor: u8, micro: u8) -> Self {
+        Self { major: major, minor: minor, micro: micro }
+    }
+
+    // Same format as the loader's value (and libnx's hosversion): 0x00MMmmuu
+    pub const fn from_raw(raw: u32) -> Self {
+        Self::new(read_bits!(16, 23, raw) as u8, read_bits!(8, 15, raw) as u8, read_bits!(0, 7, raw) as u8)
+    }
+
+    pub const fn to_raw(&self) -> u32 {
+        ((self.major as u32) << 16) | ((self.minor as u32) << 8) | (self.micro as u32)
+    }
+
+    pub const fn is_empty(&self) -> bool {
+        (self.major == 0) && (self.minor == 0) && (self.micro == 0)
+    }
+}
+
+// "ATMOSPHR", sent by the loader along with the version when running on Atmosphere
+pub const ATMOSPHERE_MAGIC: u64 = 0x41544D4F53504852;
+
+// Atomics instead of mutexes, since this gets set by crt0 before the main thread is ready
+static G_VERSION: AtomicU32 = AtomicU32::new(0);
+static G_IS_ATMOSPHERE: AtomicBool = AtomicBool::new(false);
+
+pub fn get_version() -> Version {
+    Version::from_raw(G_VERSION.load(Ordering::SeqCst))
+}
+
+pub fn set_version(version: Version) {
+    G_VERSION.store(version.to_raw(), Ordering::SeqCst);
+}
+
+pub fn is_version_known() -> bool {
+    !get_version().is_empty()
+}
+
+pub fn is_atmosphere() -> bool {
+    G_IS_ATMOSPHERE.load(Ordering::SeqCst)
+}
+
+pub fn set_is_atmosphere(is_atmosphere: bool) {
+    G_IS_ATMOSPHERE.store(is_atmosphere, Ordering::SeqCst);
+}
\ No newline at end of file
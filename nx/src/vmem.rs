@@ -25,39 +25,30 @@ pub enum VirtualRegionType {
     LegacyAlias
 }
 
-static mut G_STACK_REGION: VirtualRegion = VirtualRegion::new();
-static mut G_HEAP_REGION: VirtualRegion = VirtualRegion::new();
-static mut G_LEGACY_ALIAS_REGION: VirtualRegion = VirtualRegion::new();
-static mut G_ADDRESS_SPACE: VirtualRegion = VirtualRegion::new();
-static mut G_CURRENT_ADDRESS: usize = 0;
-static mut G_LOCK: sync::Mutex = sync::Mutex::new(false);
+struct State {
+    stack_region: VirtualRegion,
+    heap_region: VirtualRegion,
+    legacy_alias_region: VirtualRegion,
+    address_space: VirtualRegion,
+    current_address: usize
+}
+
+static G_STATE: sync::Mutex<State> = sync::Mutex::new(State { stack_region: VirtualRegion::new(), heap_region: VirtualRegion::new(), legacy_alias_region: VirtualRegion::new(), address_space: VirtualRegion::new(), current_address: 0 });
 
 pub fn get_address_space() -> VirtualRegion {
-    unsafe {
-        let _ = sync::ScopedLock::new(&mut G_LOCK);
-        G_ADDRESS_SPACE
-    }
+    G_STATE.lock().address_space
 }
 
 pub fn get_stack_region() -> VirtualRegion {
-    unsafe {
-        let _ = sync::ScopedLock::new(&mut G_LOCK);
-        G_STACK_REGION
-    }
+    G_STATE.lock().stack_region
 }
 
 pub fn get_heap_region() -> VirtualRegion {
-    unsafe {
-        let _ = sync::ScopedLock::new(&mut G_LOCK);
-        G_HEAP_REGION
-    }
+    G_STATE.lock().heap_region
 }
 
 pub fn get_legacy_alias_region() -> VirtualRegion {
-    unsafe {
-        let _ = sync::ScopedLock::new(&mut G_LOCK);
-        G_LEGACY_ALIAS_REGION
-    }
+    G_STATE.lock().legacy_alias_region
 }
 
 fn read_region_info(region: &mut VirtualRegion, address_info_id: svc::InfoId, size_info_id: svc::InfoId) -> Result<()> {
@@ -70,61 +61,57 @@ fn read_region_info(region: &mut VirtualRegion, address_info_id: svc::InfoId, si
 }
 
 pub fn initialize() -> Result<()> {
-    unsafe {
-        let _ = sync::ScopedLock::new(&mut G_LOCK);
-        read_region_info(&mut G_ADDRESS_SPACE, svc::InfoId::AslrRegionAddress, svc::InfoId::AslrRegionSize)?;
-        read_region_info(&mut G_STACK_REGION, svc::InfoId::StackRegionAddress, svc::InfoId::StackRegionSize)?;
-        read_region_info(&mut G_HEAP_REGION, svc::InfoId::HeapRegionAddress, svc::InfoId::HeapRegionSize)?;
-        read_region_info(&mut G_LEGACY_ALIAS_REGION, svc::InfoId::AliasRegionAddress, svc::InfoId::AliasRegionSize)?;
-    }
+    let mut state = G_STATE.lock();
+    read_region_info(&mut state.address_space, svc::InfoId::AslrRegionAddress, svc::InfoId::AslrRegionSize)?;
+    read_region_info(&mut state.stack_region, svc::InfoId::StackRegionAddress, svc::InfoId::StackRegionSize)?;
+    read_region_info(&mut state.heap_region, svc::InfoId::HeapRegionAddress, svc::InfoId::HeapRegionSize)?;
+    read_region_info(&mut state.legacy_alias_region, svc::InfoId::AliasRegionAddress, svc::InfoId::AliasRegionSize)?;
     Ok(())
 }
 
 pub fn allocate(size: usize) -> Result<*mut u8> {
-    unsafe {
-        let _ = sync::ScopedLock::new(&mut G_LOCK);
-
-        let mut address = G_CURRENT_ADDRESS;
-
-        loop {
-            address += mem::PAGE_ALIGNMENT;
-
-            if !G_ADDRESS_SPACE.contains(address) {
-                address = G_ADDRESS_SPACE.start;
-            }
-
-            let current_address = address + size;
-            let (memory_info, _) = svc::query_memory(address as *mut u8)?;
-            let info_address = memory_info.base_address as usize + memory_info.size as usize;
-            if memory_info.memory_state != svc::MemoryState::Free {
-                address = info_address;
-                continue;
-            }
-
-            if current_address > info_address {
-                address = info_address;
-                continue;
-            }
-
-            let end = current_address - 1;
-
-            if G_STACK_REGION.contains(address) || G_STACK_REGION.contains(end) {
-                address = G_STACK_REGION.end;
-                continue;
-            }
-            if G_HEAP_REGION.contains(address) || G_HEAP_REGION.contains(end) {
-                address = G_HEAP_REGION.end;
-                continue;
-            }
-            if G_LEGACY_ALIAS_REGION.contains(address) || G_LEGACY_ALIAS_REGION.contains(end) {
-                address = G_LEGACY_ALIAS_REGION.end;
-                continue;
-            }
-
-            break;
+    let mut state = G_STATE.lock();
+
+    let mut address = state.current_address;
+
+    loop {
+        address += mem::PAGE_ALIGNMENT;
+
+        if !state.address_space.contains(address) {
+            address = state.address_space.start;
         }
 
-        G_CURRENT_ADDRESS = address + size;
-        Ok(address as *mut u8)
+        let current_address = address + size;
+        let (memory_info, _) = svc::query_memory(address as *mut u8)?;
+        let info_address = memory_info.base_address as usize + memory_info.size as usize;
+        if memory_info.memory_state != svc::MemoryState::Free {
+            address = info_address;
+            continue;
+        }
+
+        if current_address > info_address {
+            address = info_address;
+            continue;
+        }
+
+        let end = current_address - 1;
+
+        if state.stack_region.contains(address) || state.stack_region.contains(end) {
+            address = state.stack_region.end;
+            continue;
+        }
+        if state.heap_region.contains(address) || state.heap_region.contains(end) {
+            address = state.heap_region.end;
+            continue;
+        }
+        if state.legacy_alias_region.contains(address) || state.legacy_alias_region.contains(end) {
+            address = state.legacy_alias_region.end;
+            continue;
+        }
+
+        break;
     }
+
+    state.current_address = address + size;
+    Ok(address as *mut u8)
 }
\ No newline at end of file
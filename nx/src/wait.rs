@@ -10,7 +10,6 @@ pub enum WaiterType {
 
 pub const MAX_OBJECT_COUNT: u32 = 0x40;
 
-#[allow(dead_code)]
 pub struct Waiter {
     handle: svc::Handle,
     wait_type: WaiterType
@@ -36,21 +35,38 @@ fn handles_wait_fn(handles: &[svc::Handle], timeout: i64) -> Result<usize> {
     Ok(svc::wait_synchronization(handles.as_ptr(), handles.len() as u32, timeout)? as usize)
 }
 
-fn waiters_wait_fn(_waiters: &[Waiter], _timeout: i64) -> Result<usize> {
-    todo!();
+fn waiters_wait_fn(waiters: &[Waiter], timeout: i64) -> Result<usize> {
+    result_return_if!(waiters.len() > MAX_OBJECT_COUNT as usize, results::os::ResultOutOfRange);
+
+    let mut handles: [svc::Handle; MAX_OBJECT_COUNT as usize] = [0; MAX_OBJECT_COUNT as usize];
+    for (i, waiter) in waiters.iter().enumerate() {
+        handles[i] = waiter.handle;
+    }
+
+    let index = svc::wait_synchronization(handles.as_ptr(), waiters.len() as u32, timeout)? as usize;
+    if let WaiterType::HandleWithClear = waiters[index].wait_type {
+        // If someone else already cleared the signal, treat it as a cancelled wait so that we keep waiting
+        if let Err(rc) = svc::reset_signal(waiters[index].handle) {
+            if results::os::ResultInvalidState::matches(rc) {
+                return Err(results::os::ResultOperationCanceled::make());
+            }
+            return Err(rc);
+        }
+    }
+    Ok(index)
 }
 
 fn wait_impl<W>(wait_objects: &[W], timeout: i64, wait_fn: WaitFn<W>) -> Result<usize> {
     let has_timeout = timeout != -1;
     let mut deadline: u64 = 0;
     if has_timeout {
-        deadline = arm::get_system_tick() - arm::nanoseconds_to_ticks(timeout as u64);
+        deadline = arm::get_system_tick() + arm::nanoseconds_to_ticks(timeout as u64);
     }
 
     loop {
         let this_timeout = match has_timeout {
             true => {
-                let remaining = deadline - arm::get_system_tick();
+                let remaining = deadline.saturating_sub(arm::get_system_tick());
                 arm::ticks_to_nanoseconds(remaining) as i64
             },
             false => -1
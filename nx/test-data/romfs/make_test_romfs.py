@@ -0,0 +1,151 @@
+#!/usr/bin/env python3
+# Generates test.romfs, the RomFs image used by nx's RomFs host tests
+# Layout follows the usual RomFs builders: header, file data, then the directory/file hash tables and entry tables
+
+import struct
+import os
+
+EMPTY_ENTRY = 0xFFFFFFFF
+HEADER_SIZE = 0x50
+FILE_DATA_OFFSET = 0x200
+FILE_DATA_ALIGNMENT = 0x10
+
+# (name, contents) for files, (name, [children]) for directories
+TREE = [
+    ('a.txt', b'Hello from the RomFs!\n'),
+    # Shares its file hash table bucket with empty.bin (which comes first in the bucket's chain)
+    ('b.txt', b'bbbb'),
+    ('empty.bin', b''),
+    ('data', [
+        ('numbers.bin', bytes(i % 0x100 for i in range(300))),
+        ('nested', [
+            ('deep.txt', b'deep'),
+        ]),
+    ]),
+    ('docs', []),
+]
+
+def align_up(value, align):
+    return (value + align - 1) & ~(align - 1)
+
+def calculate_path_hash(parent, name):
+    hash = parent ^ 123456789
+    for c in name:
+        hash = ((hash >> 5) | (hash << 27)) & 0xFFFFFFFF
+        hash ^= c
+    return hash
+
+def get_hash_table_count(entry_count):
+    if entry_count < 3:
+        return 3
+    if entry_count < 19:
+        return entry_count | 1
+    count = entry_count
+    while any((count % i) == 0 for i in range(2, 17)):
+        count += 1
+    return count
+
+class Dir:
+    def __init__(self, name, parent):
+        self.name = name.encode()
+        self.parent = parent
+        self.dirs = []
+        self.files = []
+        self.offset = 0
+
+class File:
+    def __init__(self, name, parent, data):
+        self.name = name.encode()
+        self.parent = parent
+        self.data = data
+        self.offset = 0
+        self.data_offset = 0
+
+def build_tree(dir, children, all_dirs, all_files):
+    for name, contents in sorted(children, key=lambda child: child[0]):
+        if isinstance(contents, list):
+            child = Dir(name, dir)
+            dir.dirs.append(child)
+        else:
+            child = File(name, dir, contents)
+            dir.files.append(child)
+            all_files.append(child)
+    for child in dir.dirs:
+        all_dirs.append(child)
+        build_tree(child, dict(children)[child.name.decode()], all_dirs, all_files)
+
+def get_entry_size(header_size, name):
+    return header_size + align_up(len(name), 4)
+
+def build():
+    root = Dir('', None)
+    all_dirs = [root]
+    all_files = []
+    build_tree(root, TREE, all_dirs, all_files)
+
+    # Assign table offsets and data offsets
+    offset = 0
+    for dir in all_dirs:
+        dir.offset = offset
+        offset += get_entry_size(0x18, dir.name)
+    dir_table_size = offset
+    offset = 0
+    data_offset = 0
+    for file in all_files:
+        file.offset = offset
+        offset += get_entry_size(0x20, file.name)
+        file.data_offset = data_offset
+        data_offset = align_up(data_offset + len(file.data), FILE_DATA_ALIGNMENT)
+    file_table_size = offset
+    file_data_size = data_offset
+
+    dir_hash_table = [EMPTY_ENTRY] * get_hash_table_count(len(all_dirs))
+    file_hash_table = [EMPTY_ENTRY] * get_hash_table_count(len(all_files))
+
+    dir_table = bytearray()
+    for dir in all_dirs:
+        parent_offset = dir.parent.offset if dir.parent is not None else 0
+        siblings = dir.parent.dirs if dir.parent is not None else [dir]
+        index = siblings.index(dir)
+        sibling = siblings[index + 1].offset if (index + 1) < len(siblings) else EMPTY_ENTRY
+        child_dir = dir.dirs[0].offset if dir.dirs else EMPTY_ENTRY
+        child_file = dir.files[0].offset if dir.files else EMPTY_ENTRY
+        bucket = calculate_path_hash(parent_offset, dir.name) % len(dir_hash_table)
+        next_hash = dir_hash_table[bucket]
+        dir_hash_table[bucket] = dir.offset
+        dir_table += struct.pack('<6I', parent_offset, sibling, child_dir, child_file, next_hash, len(dir.name))
+        dir_table += dir.name.ljust(align_up(len(dir.name), 4), b'\0')
+
+    file_table = bytearray()
+    for file in all_files:
+        siblings = file.parent.files
+        index = siblings.index(file)
+        sibling = siblings[index + 1].offset if (index + 1) < len(siblings) else EMPTY_ENTRY
+        bucket = calculate_path_hash(file.parent.offset, file.name) % len(file_hash_table)
+        next_hash = file_hash_table[bucket]
+        file_hash_table[bucket] = file.offset
+        file_table += struct.pack('<2I2Q2I', file.parent.offset, sibling, file.data_offset, len(file.data), next_hash, len(file.name))
+        file_table += file.name.ljust(align_up(len(file.name), 4), b'\0')
+
+    assert len(dir_table) == dir_table_size
+    assert len(file_table) == file_table_size
+
+    dir_hash_table_data = struct.pack('<%dI' % len(dir_hash_table), *dir_hash_table)
+    file_hash_table_data = struct.pack('<%dI' % len(file_hash_table), *file_hash_table)
+
+    dir_hash_table_offset = align_up(FILE_DATA_OFFSET + file_data_size, 4)
+    dir_table_offset = dir_hash_table_offset + len(dir_hash_table_data)
+    file_hash_table_offset = dir_table_offset + len(dir_table)
+    file_table_offset = file_hash_table_offset + len(file_hash_table_data)
+
+    header = struct.pack('<10Q', HEADER_SIZE, dir_hash_table_offset, len(dir_hash_table_data), dir_table_offset, len(dir_table), file_hash_table_offset, len(file_hash_table_data), file_table_offset, len(file_table), FILE_DATA_OFFSET)
+
+    image = bytearray(header.ljust(dir_hash_table_offset, b'\0'))
+    for file in all_files:
+        image[FILE_DATA_OFFSET + file.data_offset:FILE_DATA_OFFSET + file.data_offset + len(file.data)] = file.data
+    image += dir_hash_table_data + dir_table + file_hash_table_data + file_table
+    return bytes(image)
+
+if __name__ == '__main__':
+    with open(os.path.join(os.path.dirname(os.path.abspath(__file__)), 'test.romfs'), 'wb') as f:
+        f.write(build())
@@ -15,6 +15,7 @@ use nx::diag::log;
 use nx::service::vi;
 use nx::service::nv;
 use nx::gpu;
+use nx::gfx;
 use nx::service::hid;
 use nx::input;
 
@@ -42,16 +43,16 @@ pub struct Square {
     y_incr: i32,
     x_mult: i32,
     y_mult: i32,
-    color: ui2d::RGBA8
+    color: gfx::RGBA8
 }
 
 impl Square {
-    pub fn new(x: i32, y: i32, size: i32, color: ui2d::RGBA8) -> Self {
+    pub fn new(x: i32, y: i32, size: i32, color: gfx::RGBA8) -> Self {
         Self { x: x, y: y, size: size, x_incr: 1, y_incr: 1, x_mult: 1, y_mult: 1, color: color }
     }
 
-    pub fn handle_render<NS: nv::INvDrvService>(&mut self, surface: &mut ui2d::SurfaceEx<NS>) {
-        surface.draw(self.x, self.y, self.size, self.size, self.color);
+    pub fn handle_render(&mut self, canvas: &mut gfx::Canvas) {
+        canvas.fill_rect(self.x, self.y, self.size, self.size, self.color);
 
         self.x += self.x_incr * self.x_mult;
         self.y += self.y_incr * self.y_mult;
@@ -64,7 +65,7 @@ impl Square {
             self.x += self.x_incr * self.x_mult;
             self.x_mult += 1;
         }
-        else if (self.x + self.size) as u32 >= surface.get_width() {
+        else if (self.x + self.size) as u32 >= canvas.get_width() {
             if self.x_incr > 0 {
                 self.x_incr += 1;
                 self.x_incr = -self.x_incr;
@@ -81,7 +82,7 @@ impl Square {
             self.y += self.y_incr * self.y_mult;
             self.y_mult += 1;
         }
-        else if (self.y + self.size) as u32 >= surface.get_height() {
+        else if (self.y + self.size) as u32 >= canvas.get_height() {
             if self.y_incr > 0 {
                 self.y_incr += 1;
                 self.y_incr = -self.y_incr;
@@ -105,14 +106,14 @@ pub fn gpu_main() -> Result<()> {
 
     let mut squares: Vec<Square> = Vec::new();
 
-    let c_white = ui2d::RGBA8::new_rgb(0xFF, 0xFF, 0xFF);
-    let c_black = ui2d::RGBA8::new_rgb(0, 0, 0);
-    let c_royal_blue = ui2d::RGBA8::new_rgb(65, 105, 225);
+    let c_white = gfx::RGBA8::new_rgb(0xFF, 0xFF, 0xFF);
+    let c_black = gfx::RGBA8::new_rgb(0, 0, 0);
+    let c_royal_blue = gfx::RGBA8::new_rgb(65, 105, 225);
 
     let font_data = include_bytes!("../../font/Roboto-Medium.ttf");
     let font = rusttype::Font::try_from_bytes(font_data as &[u8]).unwrap();
 
-    let mut surface = ui2d::SurfaceEx::from(gpu_ctx.create_stray_layer_surface("Default", 2, color_fmt, gpu::PixelFormat::RGBA_8888, gpu::Layout::BlockLinear)?);
+    let mut surface = gfx::SurfaceCanvas::new(gpu_ctx.create_stray_layer_surface("Default", 2, color_fmt, gpu::PixelFormat::RGBA_8888, gpu::Layout::BlockLinear)?)?;
 
     loop {
         let mut input_player = match input_ctx.is_controller_connected(hid::ControllerId::Player1) {
@@ -129,13 +130,14 @@ pub fn gpu_main() -> Result<()> {
         }
 
         surface.start()?;
+        let canvas = surface.get_canvas();
         
-        surface.clear(c_white);
-        surface.draw_font_text(&font, String::from("(Drawn with Roboto TTF font)\n\nHello world from aarch64-switch-rs!\nPress A to spawn moving squares.\nPress + to exit this test."), c_black, 25.0, 10, 10);
-        surface.draw_bitmap_text(String::from("(Drawn with standard bitmap font)\n\nHello world from aarch64-switch-rs!\nPress A to spawn moving squares.\nPress + to exit this test."), c_black, 2, 10, 250);
+        canvas.clear(c_white);
+        ui2d::draw_font_text(canvas, &font, String::from("(Drawn with Roboto TTF font)\n\nHello world from aarch64-switch-rs!\nPress A to spawn moving squares.\nPress + to exit this test."), c_black, 25.0, 10, 10);
+        ui2d::draw_bitmap_text(canvas, String::from("(Drawn with standard bitmap font)\n\nHello world from aarch64-switch-rs!\nPress A to spawn moving squares.\nPress + to exit this test."), c_black, 2, 10, 250);
 
         for square in squares.iter_mut() {
-            square.handle_render(&mut surface);
+            square.handle_render(canvas);
         }
 
         surface.end()?;
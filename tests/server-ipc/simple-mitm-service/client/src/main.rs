@@ -19,37 +19,11 @@ use nx::ipc::sf;
 
 use core::panic;
 
-// Same interface as /server project
+// Same interface as /server project, whose client struct is generated
+#[sf::ipc_interface(client = "AccountServiceForApplication")]
 pub trait IAccountServiceForApplication {
-    ipc_interface_define_command!(get_user_count: () => (out_value: u32));
-}
-
-pub struct AccountServiceForApplication {
-    session: sf::Session
-}
-
-impl sf::IObject for AccountServiceForApplication {
-    fn get_session(&mut self) -> &mut sf::Session {
-        &mut self.session
-    }
-    
-    fn get_command_table(&self) -> sf::CommandMetadataTable {
-        ipc_server_make_command_table!(
-            get_user_count: 0
-        )
-    }
-}
-
-impl IAccountServiceForApplication for AccountServiceForApplication {
-    fn get_user_count(&mut self) -> Result<u32> {
-        ipc_client_send_request_command!([self.session.object_info; 0] () => (count: u32))
-    }
-}
-
-impl service::IClientObject for AccountServiceForApplication {
-    fn new(session: sf::Session) -> Self {
-        Self { session: session }
-    }
+    #[ipc_rid(0)]
+    fn get_user_count(&mut self) -> Result<u32>;
 }
 
 impl service::IService for AccountServiceForApplication {
@@ -7,8 +7,7 @@ extern crate nx;
 #[macro_use]
 extern crate alloc;
 use alloc::vec::Vec;
-
-extern crate paste;
+use alloc::sync::Arc;
 
 use nx::result::*;
 use nx::results;
@@ -22,8 +21,10 @@ use nx::ipc::server;
 
 use core::panic;
 
+#[sf::ipc_interface]
 pub trait IAccountServiceForApplication {
-    ipc_interface_define_command!(get_user_count: () => (out_value: u32));
+    #[ipc_rid(0)]
+    fn get_user_count(&mut self) -> Result<u32>;
 }
 
 pub struct AccountServiceForApplication {
@@ -36,9 +37,7 @@ impl sf::IObject for AccountServiceForApplication {
     }
     
     fn get_command_table(&self) -> sf::CommandMetadataTable {
-        ipc_server_make_command_table!(
-            get_user_count: 0
-        )
+        Self::make_command_table()
     }
 }
 
@@ -77,9 +76,10 @@ pub fn initialize_heap(_hbl_heap: util::PointerAndSize) -> util::PointerAndSize
 }
 
 pub fn server_main() -> Result<()> {
-    let mut manager = server::ServerManager::new();
+    // Slow commands on one session shouldn't block other clients, so process requests in several threads
+    let manager = Arc::new(server::ServerManager::<0x20, 1>::new()?);
     manager.register_mitm_service_server::<AccountServiceForApplication>()?;
-    manager.loop_process()?;
+    server::ServerManager::loop_process_with_workers(&manager, 2, 0x4000)?;
 
     Ok(())
 }
@@ -19,12 +19,17 @@ use nx::service;
 
 use core::panic;
 
+// Same interfaces as /server project, whose client structs are generated
+#[sf::ipc_interface(client = "DemoSubInterface")]
 pub trait IDemoSubInterface {
-    ipc_interface_define_command!(sample_cmd_1: (input: u32) => (output: u32));
+    #[ipc_rid(246)]
+    fn sample_cmd_1(&mut self, input: u32) -> Result<u32>;
 }
 
+#[sf::ipc_interface(client = "DemoService")]
 pub trait IDemoService {
-    ipc_interface_define_command!(open_sub_interface: (value: u32, pid: sf::ProcessId) => (sub_interface: mem::Shared<dyn sf::IObject>));
+    #[ipc_rid(123)]
+    fn open_sub_interface(&mut self, value: u32, pid: sf::ProcessId) -> Result<mem::Shared<DemoSubInterface>>;
 }
 
 #[derive(Copy, Clone)]
@@ -36,62 +41,6 @@ pub struct SampleStruct {
     val4: char
 }
 
-pub struct DemoSubInterface {
-    session: sf::Session
-}
-
-impl sf::IObject for DemoSubInterface {
-    fn get_session(&mut self) -> &mut sf::Session {
-        &mut self.session
-    }
-
-    fn get_command_table(&self) -> sf::CommandMetadataTable {
-        ipc_server_make_command_table!(
-            sample_cmd_1: 246
-        )
-    }
-}
-
-impl service::IClientObject for DemoSubInterface {
-    fn new(session: sf::Session) -> Self {
-        Self { session: session }
-    }
-}
-
-impl IDemoSubInterface for DemoSubInterface {
-    fn sample_cmd_1(&mut self, input: u32) -> Result<u32> {
-        ipc_client_send_request_command!([self.session.object_info; 246] (input) => (output: u32))
-    }
-}
-
-pub struct DemoService {
-    session: sf::Session
-}
-
-impl sf::IObject for DemoService {
-    fn get_session(&mut self) -> &mut sf::Session {
-        &mut self.session
-    }
-
-    fn get_command_table(&self) -> sf::CommandMetadataTable {
-        ipc_server_make_command_table!(
-            open_sub_interface: 123
-        )
-    }
-}
-
-impl service::IClientObject for DemoService {
-    fn new(session: sf::Session) -> Self {
-        Self { session: session }
-    }
-}
-
-impl IDemoService for DemoService {
-    fn open_sub_interface(&mut self, value: u32, pid: sf::ProcessId) -> Result<mem::Shared<dyn sf::IObject>> {
-        ipc_client_send_request_command!([self.session.object_info; 123] (value, pid) => (sub_interface: mem::Shared<DemoSubInterface>))
-    }
-}
-
 impl service::IService for DemoService {
     fn get_name() -> &'static str {
         nul!("dmo-srv")
@@ -124,7 +73,7 @@ pub fn client_main() -> Result<()> {
     let a: u32 = 15;
     let b: u32 = 21;
 
-    let subintf = demosrv.get().open_sub_interface(a, sf::ProcessId::new())?.to::<DemoSubInterface>();
+    let subintf = demosrv.get().open_sub_interface(a, sf::ProcessId::new())?;
     let output = subintf.get().sample_cmd_1(b)?;
 
     diag_log!(log::LmLogger { log::LogSeverity::Trace, false } => "Result: {} times {} = {}", a, b, output);
@@ -7,8 +7,6 @@ extern crate nx;
 #[macro_use]
 extern crate alloc;
 
-extern crate paste;
-
 use nx::result::*;
 use nx::results;
 use nx::util;
@@ -20,12 +18,16 @@ use nx::ipc::server;
 
 use core::panic;
 
+#[sf::ipc_interface]
 pub trait IDemoSubInterface {
-    ipc_interface_define_command!(sample_cmd_1: (input: u32) => (output: u32));
+    #[ipc_rid(246)]
+    fn sample_cmd_1(&mut self, input: u32) -> Result<u32>;
 }
 
+#[sf::ipc_interface]
 pub trait IDemoService {
-    ipc_interface_define_command!(open_sub_interface: (value: u32, pid: sf::ProcessId) => (sub_interface: mem::Shared<dyn sf::IObject>));
+    #[ipc_rid(123)]
+    fn open_sub_interface(&mut self, value: u32, pid: sf::ProcessId) -> Result<mem::Shared<DemoSubInterface>>;
 }
 
 #[derive(Copy, Clone)]
@@ -60,9 +62,7 @@ impl sf::IObject for DemoSubInterface {
     }
 
     fn get_command_table(&self) -> sf::CommandMetadataTable {
-        ipc_server_make_command_table!(
-            sample_cmd_1: 246
-        )
+        Self::make_command_table()
     }
 }
 
@@ -71,7 +71,9 @@ pub struct DemoService {
 }
 
 impl IDemoService for DemoService {
-    fn open_sub_interface(&mut self, value: u32, pid: sf::ProcessId) -> Result<mem::Shared<dyn sf::IObject>> {
+    type DemoSubInterface = DemoSubInterface;
+
+    fn open_sub_interface(&mut self, value: u32, pid: sf::ProcessId) -> Result<mem::Shared<DemoSubInterface>> {
         diag_log!(log::LmLogger { log::LogSeverity::Error, true } => "Opening interface (process ID 0x{:X}) with value {}", pid.process_id, value);
         Ok(mem::Shared::new(DemoSubInterface::new(value)))
     }
@@ -83,9 +85,7 @@ impl sf::IObject for DemoService {
     }
 
     fn get_command_table(&self) -> sf::CommandMetadataTable {
-        ipc_server_make_command_table!(
-            open_sub_interface: 123
-        )
+        Self::make_command_table()
     }
 }
 
@@ -116,7 +116,7 @@ pub fn initialize_heap(_hbl_heap: util::PointerAndSize) -> util::PointerAndSize
 }
 
 pub fn server_main() -> Result<()> {
-    let mut manager = server::ServerManager::new();
+    let manager = server::ServerManager::<0x20, 1>::new()?;
     manager.register_service_server::<DemoService>()?;
     manager.loop_process()?;
 